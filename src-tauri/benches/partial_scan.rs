@@ -0,0 +1,92 @@
+//! partial_scan - Compare the partial GVAS scan with the full parse
+//! Times both readers on every `.sav` in a SaveGames directory (the configured one
+//! when none is given), checks they agree, and prints the speedup.
+//!
+//!     cargo bench --bench partial_scan -- [DIR] [ITERATIONS]
+
+use etbsavemanager_lib::cli_handlers;
+use etbsavemanager_lib::get_file_path;
+use etbsavemanager_lib::save_paths::SavePaths;
+use etbsavemanager_lib::save_scan;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: u32 = 5;
+
+fn main() {
+    // cargo passes `--bench`; everything else positional is ours
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with("--"))
+        .collect();
+    let paths = match args.first() {
+        Some(dir) => SavePaths::new(dir, std::env::temp_dir()),
+        None => SavePaths::resolve().expect("no SaveGames directory configured; pass DIR"),
+    };
+    let iterations: u32 = args
+        .get(1)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+
+    let files: Vec<PathBuf> =
+        get_file_path::list_save_paths(&paths).expect("failed to list SaveGames");
+    if files.is_empty() {
+        eprintln!("no saves in {}", paths.save_games_dir().display());
+        return;
+    }
+    let bytes: u64 = files
+        .iter()
+        .filter_map(|p| p.metadata().ok())
+        .map(|m| m.len())
+        .sum();
+    println!(
+        "{} saves, {:.1} MiB, {} iteration(s) in {}",
+        files.len(),
+        bytes as f64 / (1024.0 * 1024.0),
+        iterations,
+        paths.save_games_dir().display()
+    );
+
+    let mut full = Duration::ZERO;
+    let mut scan = Duration::ZERO;
+    let (mut mismatches, mut scan_failures) = (0, 0);
+    for path in &files {
+        let start = Instant::now();
+        let mut parsed = None;
+        for _ in 0..iterations {
+            parsed = cli_handlers::parse_sav_file(path).ok();
+        }
+        full += start.elapsed();
+
+        let start = Instant::now();
+        let mut scanned = None;
+        for _ in 0..iterations {
+            scanned = save_scan::scan_file(path).ok();
+        }
+        scan += start.elapsed();
+
+        match (parsed, scanned) {
+            (Some(save), Some(summary))
+                if save.current_level() != summary.current_level()
+                    || save.difficulty() != summary.difficulty() =>
+            {
+                mismatches += 1;
+                println!("  mismatch: {}", path.display());
+            }
+            (Some(_), None) => {
+                scan_failures += 1;
+                println!("  scan fell back: {}", path.display());
+            }
+            _ => {}
+        }
+    }
+
+    let per_pass = |total: Duration| total / iterations;
+    println!("full parse:   {:>10.2?} per pass", per_pass(full));
+    println!("partial scan: {:>10.2?} per pass", per_pass(scan));
+    println!(
+        "speedup:      {:>9.1}x",
+        full.as_secs_f64() / scan.as_secs_f64().max(f64::EPSILON)
+    );
+    println!("{} mismatch(es), {} fallback(s)", mismatches, scan_failures);
+}
@@ -0,0 +1,1287 @@
+//! etbsave - Headless command-line front end for the save manager
+//! Drives the same save_loader / save_editor / new_save / save_deleter logic as the
+//! Tauri commands, so the save library can be scripted without a WebView.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use etbsavemanager_lib::error::{AppError, AppResult};
+use etbsavemanager_lib::etb_save::{pure_player_id, EtbSave};
+use etbsavemanager_lib::save_diff::{self, DryRunReport};
+use etbsavemanager_lib::save_paths::SavePaths;
+use etbsavemanager_lib::{
+    cli_handlers, common, get_file_path, mainsave_manager, new_save, path_resolver, player_data,
+    property_path, save_backup, save_batch, save_bulk, save_deleter, save_editor, save_lint,
+    save_loader, save_query, save_shared, save_trash, save_utils, save_verify, snapshot_store,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Template used by `create` when `--template` is not given (same file the GUI loads)
+const BASIC_ARCHIVE: &str = include_str!("../../../public/BasicArchive.json");
+
+#[derive(Parser)]
+#[command(
+    name = "etbsave",
+    version,
+    about = "Manage Escape the Backrooms saves from the shell"
+)]
+struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Use this SaveGames directory instead of the configured one
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "library")]
+    save_dir: Option<PathBuf>,
+
+    /// Use a named library profile instead of the active one
+    #[arg(long, global = true, value_name = "NAME")]
+    library: Option<String>,
+
+    /// Show what a mutating command would change without writing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Log progress to stderr (repeat for debug output)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List every save in the library
+    List,
+    /// Search the library, e.g. `etbsave query "player:7656119 has:Crowbar sort:-date"`
+    Query {
+        /// Query terms: name, level, difficulty, mode, player, has, visible, hidden, sort
+        query: String,
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+        #[arg(long, default_value_t = 50)]
+        limit: u32,
+    },
+    /// Show level, difficulty and players of one save
+    Inspect {
+        /// Save path, file name or archive name
+        save: String,
+        /// Dump the full parsed save as JSON
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Edit level, difficulty, name or players of a save
+    Edit {
+        /// Save path, file name or archive name
+        save: String,
+        /// New archive name (renames the file)
+        #[arg(long)]
+        name: Option<String>,
+        /// New current level (e.g. Level0, Pipes2, TheHub)
+        #[arg(long)]
+        level: Option<String>,
+        /// New difficulty (file name and in-game)
+        #[arg(long, value_enum)]
+        difficulty: Option<Difficulty>,
+        /// In-game difficulty only, when it should differ from the file name
+        #[arg(long, value_enum)]
+        actual_difficulty: Option<Difficulty>,
+        /// Remove a player (steam id or full PlayerData key)
+        #[arg(long, value_name = "ID")]
+        remove_player: Vec<String>,
+        #[command(flatten)]
+        players: PlayerArgs,
+    },
+    /// Create a new save from a template
+    Create {
+        /// Archive name
+        #[arg(long)]
+        name: String,
+        /// Starting level (e.g. Level0, Pipes2, TheHub)
+        #[arg(long, default_value = "Level0")]
+        level: String,
+        #[arg(long, value_enum, default_value = "normal")]
+        difficulty: Difficulty,
+        /// In-game difficulty, when it should differ from the file name
+        #[arg(long, value_enum)]
+        actual_difficulty: Option<Difficulty>,
+        /// Mark the side storyline as completed instead of the main ending
+        #[arg(long)]
+        side_story: bool,
+        /// Unlock the M.E.G. base
+        #[arg(long)]
+        meg_unlocked: bool,
+        /// Template save JSON (defaults to the bundled BasicArchive.json)
+        #[arg(long, value_name = "FILE")]
+        template: Option<PathBuf>,
+        #[command(flatten)]
+        players: PlayerArgs,
+    },
+    /// Unlock every hub door
+    UnlockDoors {
+        /// Save path, file name or archive name
+        save: String,
+    },
+    /// Move a save to the trash (.sav.trash) and hide it
+    Delete {
+        /// Save path, file name or archive name
+        save: String,
+    },
+    /// Restore a trashed save
+    Restore {
+        /// Original save path or file name (without .trash)
+        save: String,
+        /// Restore under this save name instead of the original one
+        #[arg(long = "as")]
+        new_name: Option<String>,
+    },
+    /// Apply one operation to many saves as a single transaction
+    Bulk {
+        #[arg(value_enum)]
+        action: BulkOp,
+        /// Save paths, file names or archive names
+        #[arg(required = true)]
+        saves: Vec<String>,
+    },
+    /// Inspect or repair MAINSAVE's save list
+    Mainsave {
+        #[command(subcommand)]
+        action: MainsaveCommand,
+    },
+    /// List trashed saves, newest first
+    Trashed,
+    /// Permanently delete every trashed save
+    EmptyTrash,
+    /// Toggle whether the game lists a save
+    Toggle {
+        /// Save path, file name or archive name
+        save: String,
+    },
+    /// Check that a save re-serializes byte-for-byte
+    Verify {
+        /// Save path, file name or archive name
+        save: String,
+    },
+    /// List backups of a save (MAINSAVE for MAINSAVE.sav), or every save that has some
+    Backups {
+        /// Save path, file name or archive name
+        save: Option<String>,
+    },
+    /// Restore a backup over the current file
+    RestoreBackup {
+        /// Save path, file name or archive name
+        save: String,
+        /// Backup id, as listed by `backups`
+        id: String,
+    },
+    /// Store a deduplicated snapshot of a save (or MAINSAVE.sav)
+    Snapshot {
+        /// Save path, file name or archive name
+        save: String,
+        /// Why the snapshot was taken
+        #[arg(long, default_value = "manual")]
+        reason: String,
+    },
+    /// List snapshots of the current library, optionally for one save
+    Snapshots {
+        /// Save path, file name or archive name
+        save: Option<String>,
+    },
+    /// Write a snapshot back to its original path
+    RestoreSnapshot {
+        /// Snapshot id, as listed by `snapshots`
+        id: String,
+    },
+    /// Remove snapshot blobs no longer referenced
+    GcSnapshots,
+    /// Show what changed between two saves (A before, B after)
+    Diff {
+        /// Old save: path, file name or archive name
+        a: String,
+        /// New save: path, file name or archive name
+        b: String,
+    },
+    /// Read one value by property path (e.g. 'LevelsCompleted[4].HasCompleted_4_...')
+    Get {
+        /// Save path, file name or archive name
+        save: String,
+        path: String,
+    },
+    /// Set one value by property path, creating missing properties
+    Set {
+        /// Save path, file name or archive name
+        save: String,
+        path: String,
+        value: String,
+        /// Value type: bool, int, int64, float, double, str, name, byte, byteLabel, enum
+        #[arg(long = "type", default_value = "str")]
+        value_type: String,
+    },
+    /// Report consistency issues in a save
+    Lint {
+        /// Save path, file name or archive name
+        save: String,
+        /// Apply every available fix
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+/// Player options shared by `edit` and `create`
+#[derive(Args)]
+struct PlayerArgs {
+    /// Add a player by steam id
+    #[arg(long, value_name = "ID")]
+    player: Vec<String>,
+    /// Set a player's inventory: ID=Item,Item,... (names or item ids, up to 12)
+    #[arg(long, value_name = "ID=ITEMS")]
+    inventory: Vec<String>,
+    /// Set a player's sanity: ID=0..100
+    #[arg(long, value_name = "ID=VALUE")]
+    sanity: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum MainsaveCommand {
+    /// Show every entry with its file status, and the saves it leaves out
+    Status,
+    /// Move the named saves to the top, in this order
+    Reorder {
+        #[arg(required = true)]
+        archive_names: Vec<String>,
+    },
+    /// Drop entries whose save is missing, trashed, hidden or listed twice
+    DropOrphans,
+    /// Add every save MAINSAVE leaves out to SingleplayerSaves
+    AddAll {
+        #[arg(long, value_enum, default_value = "newest-first")]
+        order: AddOrder,
+        /// Add after the current entries instead of before them
+        #[arg(long)]
+        append: bool,
+    },
+    /// Rebuild a missing or unreadable MAINSAVE, listing every save on disk
+    Repair {
+        /// MAINSAVE.sav to take the header from (defaults to the newest readable backup)
+        #[arg(long)]
+        template: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AddOrder {
+    NewestFirst,
+    OldestFirst,
+    NameAsc,
+    NameDesc,
+}
+
+impl AddOrder {
+    fn order(self) -> mainsave_manager::AddOrder {
+        match self {
+            AddOrder::NewestFirst => mainsave_manager::AddOrder::NewestFirst,
+            AddOrder::OldestFirst => mainsave_manager::AddOrder::OldestFirst,
+            AddOrder::NameAsc => mainsave_manager::AddOrder::NameAsc,
+            AddOrder::NameDesc => mainsave_manager::AddOrder::NameDesc,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BulkOp {
+    /// Move to the trash
+    Delete,
+    /// Delete permanently
+    Purge,
+    Restore,
+    Toggle,
+}
+
+impl BulkOp {
+    fn action(self) -> save_bulk::BulkAction {
+        match self {
+            BulkOp::Delete => save_bulk::BulkAction::SoftDelete,
+            BulkOp::Purge => save_bulk::BulkAction::Delete,
+            BulkOp::Restore => save_bulk::BulkAction::Restore,
+            BulkOp::Toggle => save_bulk::BulkAction::ToggleVisibility,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+}
+
+/// One PlayerData entry
+#[derive(Debug, Clone, Serialize)]
+struct PlayerInfo {
+    id: String,
+    sanity: f64,
+    inventory: Vec<String>,
+}
+
+/// `inspect` output
+#[derive(Serialize)]
+struct SaveInspection {
+    #[serde(flatten)]
+    info: save_utils::SaveFileInfo,
+    players: Vec<PlayerInfo>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let level = match cli.verbose {
+        0 => tracing::Level::WARN,
+        1 => tracing::Level::INFO,
+        _ => tracing::Level::DEBUG,
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env().add_directive(level.into()),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let json_output = cli.json;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if json_output {
+                eprintln!("{}", json!({ "success": false, "error": e }));
+            } else {
+                eprintln!("error: {}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn resolve_paths(cli: &Cli) -> AppResult<SavePaths> {
+    match (&cli.save_dir, &cli.library) {
+        (Some(dir), _) => Ok(SavePaths::new(
+            dir,
+            path_resolver::resolve_app_config_dir()?,
+        )),
+        (None, Some(name)) => SavePaths::for_library(name),
+        (None, None) => SavePaths::resolve(),
+    }
+}
+
+fn run(cli: Cli) -> AppResult<()> {
+    let paths = resolve_paths(&cli)?;
+    let json_output = cli.json;
+    let dry_run = cli.dry_run;
+
+    match cli.command {
+        Command::List => {
+            let (saves, mainsave_error) = save_loader::collect_saves(&paths)?;
+            warn_mainsave_unreadable(mainsave_error);
+            if json_output {
+                return print_json(&saves);
+            }
+            for save in &saves {
+                println!(
+                    "{} {:<32} {:<9} {:<9} {:<20} {}",
+                    if save.is_visible == Some(true) {
+                        "*"
+                    } else {
+                        " "
+                    },
+                    save.name,
+                    save.difficulty,
+                    save.actual_difficulty,
+                    save.current_level,
+                    save.path
+                );
+            }
+            println!("{} saves (* = listed in game)", saves.len());
+            Ok(())
+        }
+        Command::Query {
+            query,
+            offset,
+            limit,
+        } => {
+            let parsed = save_query::SaveQuery::parse(&query)?;
+            let (page, mainsave_error) = save_query::run_query(&paths, &parsed, offset, limit)?;
+            warn_mainsave_unreadable(mainsave_error);
+            if json_output {
+                return print_json(&page);
+            }
+            for hit in &page.items {
+                let (level, difficulty) = hit.details.as_ref().map_or(("?", "?"), |d| {
+                    (d.current_level.as_str(), d.actual_difficulty.as_str())
+                });
+                println!(
+                    "{} {:<32} {:<9} {:<20} {}",
+                    if hit.meta.is_visible == Some(true) {
+                        "*"
+                    } else {
+                        " "
+                    },
+                    hit.meta.name,
+                    difficulty,
+                    level,
+                    hit.meta.path
+                );
+            }
+            println!(
+                "{}-{} of {} match(es)",
+                (offset as usize + 1).min(page.total as usize),
+                offset as usize + page.items.len(),
+                page.total
+            );
+            Ok(())
+        }
+        Command::Inspect { save, raw } => {
+            let path = resolve_save_file(&paths, &save)?;
+            let parsed = cli_handlers::parse_sav_file(&path)?;
+            if raw {
+                // Serialize straight to a string: uesave 0.7 JSON needs header → schemas →
+                // root order, which a round trip through serde_json::Value would sort away
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(parsed.as_save())
+                        .map_err(|e| format!("JSON formatting failed: {}", e))?
+                );
+                return Ok(());
+            }
+            let inspection = SaveInspection {
+                info: save_info(&paths, &path, &parsed)?,
+                players: players_of(&parsed),
+            };
+            if json_output {
+                return print_json(&inspection);
+            }
+            let info = &inspection.info;
+            println!("Name:        {}", info.name);
+            println!("Path:        {}", info.path);
+            println!("Listed:      {}", info.is_visible == Some(true));
+            println!("Level:       {}", info.current_level);
+            println!(
+                "Difficulty:  {} (in game: {})",
+                info.difficulty, info.actual_difficulty
+            );
+            println!("Players:     {}", inspection.players.len());
+            for player in &inspection.players {
+                let items: Vec<&str> = player
+                    .inventory
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|item| *item != "None")
+                    .collect();
+                println!(
+                    "  {}  sanity {:.0}  [{}]",
+                    player.id,
+                    player.sanity,
+                    items.join(", ")
+                );
+            }
+            Ok(())
+        }
+        Command::Edit {
+            save,
+            name,
+            level,
+            difficulty,
+            actual_difficulty,
+            remove_player,
+            players,
+        } => {
+            let path = resolve_save_file(&paths, &save)?;
+            let parsed = cli_handlers::parse_sav_file(&path)?;
+            let info = save_info(&paths, &path, &parsed)?;
+
+            let mut roster = players_of(&parsed);
+            for id in &remove_player {
+                let idx = find_player(&roster, id)
+                    .ok_or_else(|| AppError::Validation(format!("Player not in save: {}", id)))?;
+                roster.remove(idx);
+            }
+            apply_player_args(&paths, &mut roster, &players)?;
+
+            let current_level = match level {
+                Some(level) => canonical_level(&level)?.to_string(),
+                None => info.current_level.clone(),
+            };
+            let file_difficulty = difficulty
+                .map(|d| d.label().to_string())
+                .unwrap_or_else(|| info.difficulty.clone());
+            let actual = actual_difficulty
+                .or(difficulty)
+                .map(|d| d.label().to_string())
+                .unwrap_or_else(|| info.actual_difficulty.clone());
+
+            let mut inventories = serde_json::Map::new();
+            let mut sanities = serde_json::Map::new();
+            for player in &roster {
+                let slots: Vec<Value> = player
+                    .inventory
+                    .iter()
+                    .map(|item| {
+                        let id = save_shared::map_item_name_to_id(item).unwrap_or(-1);
+                        json!({ "item": { "id": id } })
+                    })
+                    .collect();
+                inventories.insert(player.id.clone(), Value::Array(slots));
+                sanities.insert(player.id.clone(), json!(player.sanity));
+            }
+
+            let json_data = json!({
+                "path": info.path,
+                "name": name.unwrap_or(info.name),
+                "mode": info.mode,
+                "currentLevel": current_level,
+                "actualDifficulty": actual,
+                "difficulty": file_difficulty,
+                "playerInventory": inventories,
+                "playerSanity": sanities,
+            });
+            let output_dir = path.parent().ok_or("Invalid file path")?;
+            let output_dir = output_dir
+                .to_str()
+                .ok_or("Path contains invalid characters")?;
+            if dry_run {
+                let preview = save_editor::preview_edit_save(&paths, &json_data, output_dir)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let new_path = save_editor::edit_save_file(&paths, &json_data, output_dir)?;
+            report(
+                json_output,
+                json!({ "success": true, "path": new_path }),
+                || format!("Saved {}", new_path),
+            )
+        }
+        Command::Create {
+            name,
+            level,
+            difficulty,
+            actual_difficulty,
+            side_story,
+            meg_unlocked,
+            template,
+            players,
+        } => {
+            let basic_archive: Value = match template {
+                Some(file) => serde_json::from_str(
+                    &std::fs::read_to_string(&file)
+                        .map_err(|e| format!("Failed to read template: {}", e))?,
+                )?,
+                None => serde_json::from_str(BASIC_ARCHIVE)?,
+            };
+
+            let mut roster = Vec::new();
+            apply_player_args(&paths, &mut roster, &players)?;
+            let players = roster
+                .into_iter()
+                .map(|p| new_save::PlayerData {
+                    inventory: p
+                        .inventory
+                        .iter()
+                        .map(|item| save_shared::map_item_name_to_id(item).unwrap_or(-1))
+                        .collect(),
+                    sanity: p.sanity as f32,
+                    steam_id: p.id,
+                })
+                .collect();
+
+            let save_data = new_save::SaveData {
+                archive_name: name,
+                level: canonical_level(&level)?.to_string(),
+                game_mode: "Multiplayer".to_string(),
+                difficulty: difficulty.label().to_string(),
+                actual_difficulty: actual_difficulty.unwrap_or(difficulty).label().to_string(),
+                players,
+                basic_archive,
+                main_ending: side_story,
+                meg_unlocked,
+            };
+            if dry_run {
+                let preview = new_save::preview_new_save(&paths, &save_data)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let path = new_save::create_new_save(&paths, save_data)?;
+            let path = path.to_string_lossy().into_owned();
+            report(
+                json_output,
+                json!({ "success": true, "path": path }),
+                || format!("Created {}", path),
+            )
+        }
+        Command::UnlockDoors { save } => {
+            let path = resolve_save_file(&paths, &save)?;
+            let path = path.to_str().ok_or("Path contains invalid characters")?;
+            if dry_run {
+                let preview = save_editor::preview_unlock_hub_doors(&paths, path)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let message = save_editor::unlock_all_hub_doors(&paths, path)?;
+            report(
+                json_output,
+                json!({ "success": true, "path": path }),
+                || message,
+            )
+        }
+        Command::Delete { save } => {
+            let path = resolve_save_file(&paths, &save)?;
+            if dry_run {
+                let preview = save_deleter::preview_soft_delete(&paths, &path)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let deleted = save_deleter::soft_delete(&paths, &path)?;
+            let trash = deleted.trash_path.unwrap_or_default();
+            report(
+                json_output,
+                json!({ "success": true, "path": trash, "mainsaveError": deleted.mainsave_error }),
+                || match &deleted.mainsave_error {
+                    Some(e) => format!("Moved to trash: {} (MAINSAVE not updated: {})", trash, e),
+                    None => format!("Moved to trash: {}", trash),
+                },
+            )
+        }
+        Command::Restore { save, new_name } => {
+            // A save that took the name meanwhile is kept; the trashed one gets `(n)`
+            let trash_path = resolve_trashed_file(&paths, &save)?.with_extension("sav.trash");
+            if dry_run {
+                let preview =
+                    save_trash::preview_restore_trash(&paths, &trash_path, new_name.as_deref())?;
+                return print_dry_run(json_output, &preview);
+            }
+            let restored = save_trash::restore_trash(&paths, &trash_path, new_name.as_deref())?;
+            let path = &restored.path;
+            report(
+                json_output,
+                json!({ "success": true, "path": path, "mainsaveError": restored.mainsave_error }),
+                || match &restored.mainsave_error {
+                    Some(e) => format!("Restored as {} (MAINSAVE not updated: {})", path, e),
+                    None => format!("Restored as {}", path),
+                },
+            )
+        }
+        Command::Bulk { action, saves } => {
+            let files = saves
+                .iter()
+                .map(|save| match action {
+                    BulkOp::Restore => resolve_trashed_file(&paths, save),
+                    _ => resolve_save_file(&paths, save),
+                })
+                .map(|path| path.map(|p| p.to_string_lossy().into_owned()))
+                .collect::<AppResult<Vec<String>>>()?;
+            if dry_run {
+                let preview = save_bulk::preview_bulk(&paths, action.action(), &files)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let result = save_bulk::run_bulk(&paths, action.action(), &files)?;
+            if json_output {
+                return print_json(&result);
+            }
+            for item in &result.items {
+                println!(
+                    "{:<11} {}{}",
+                    format!("{:?}", item.status),
+                    item.path,
+                    item.error
+                        .as_deref()
+                        .map(|e| format!("  ({})", e))
+                        .unwrap_or_default()
+                );
+            }
+            if result.committed {
+                println!("Committed {} item(s)", result.items.len());
+                Ok(())
+            } else {
+                Err(AppError::General(
+                    result
+                        .error
+                        .unwrap_or_else(|| "Bulk operation rolled back".into()),
+                ))
+            }
+        }
+        Command::Mainsave { action } => run_mainsave(&paths, action, json_output, dry_run),
+        Command::Trashed => {
+            let entries = save_trash::list_trash(&paths)?;
+            if json_output {
+                return print_json(&entries);
+            }
+            for entry in &entries {
+                println!(
+                    "{:<32} {:<9} {:<20} {}{}",
+                    entry.name,
+                    entry.actual_difficulty.as_deref().unwrap_or("?"),
+                    entry.current_level.as_deref().unwrap_or("?"),
+                    entry.trashed_at,
+                    if entry.name_taken {
+                        "  (name taken)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            println!("{} trashed save(s)", entries.len());
+            Ok(())
+        }
+        Command::EmptyTrash => {
+            if dry_run {
+                return print_dry_run(json_output, &save_trash::preview_empty_trash(&paths));
+            }
+            let result = save_trash::empty_trash(&paths)?;
+            report(
+                json_output,
+                json!({ "success": true, "removed": result.removed, "freedBytes": result.freed_bytes }),
+                || {
+                    format!(
+                        "Deleted {} trashed save(s), {} bytes",
+                        result.removed, result.freed_bytes
+                    )
+                },
+            )
+        }
+        Command::Toggle { save } => {
+            let path = resolve_save_file(&paths, &save)?;
+            if dry_run {
+                let preview = save_deleter::preview_toggle_visibility(&paths, &path)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let visible = save_deleter::toggle_visibility(&paths, &path)?;
+            report(
+                json_output,
+                json!({ "success": true, "visible": visible }),
+                || {
+                    if visible {
+                        "Save is now listed in game".to_string()
+                    } else {
+                        "Save is now hidden".to_string()
+                    }
+                },
+            )
+        }
+        Command::Verify { save } => {
+            let path = resolve_save_file(&paths, &save)?;
+            let report = save_verify::verify_file(&path)?;
+            if json_output {
+                print_json(&report)?;
+            }
+            if !report.is_ok() {
+                return Err(AppError::Verification(report.summary()));
+            }
+            if !json_output {
+                println!("{}", report.summary());
+            }
+            Ok(())
+        }
+        Command::Backups { save: None } => {
+            let summaries = save_backup::list_backup_summaries(&paths)?;
+            if json_output {
+                return print_json(&summaries);
+            }
+            for summary in &summaries {
+                println!(
+                    "{:<40} {:>3} backup(s)  {:>9} bytes  latest {}",
+                    summary.archive_name, summary.count, summary.total_size, summary.latest
+                );
+            }
+            Ok(())
+        }
+        Command::Backups { save: Some(save) } => {
+            let archive_name = save_backup::archive_name_of(&paths, Path::new(&save))?;
+            let backups = save_backup::list_backups(&paths, &archive_name)?;
+            if json_output {
+                return print_json(&backups);
+            }
+            for backup in &backups {
+                println!(
+                    "{}  {:<14} {:>9} bytes  {}",
+                    backup.id, backup.reason, backup.size, backup.original_path
+                );
+            }
+            println!("{} backup(s) of {}", backups.len(), archive_name);
+            Ok(())
+        }
+        Command::RestoreBackup { save, id } => {
+            let archive_name = save_backup::archive_name_of(&paths, Path::new(&save))?;
+            if dry_run {
+                let preview = save_backup::preview_restore_backup(&paths, &archive_name, &id)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let path = save_backup::restore_backup(&paths, &archive_name, &id)?;
+            let path = path.to_string_lossy().into_owned();
+            report(
+                json_output,
+                json!({ "success": true, "path": path }),
+                || format!("Restored {} from backup {}", path, id),
+            )
+        }
+        Command::Snapshot { save, reason } => {
+            let path = resolve_save_file(&paths, &save)?;
+            if dry_run {
+                let preview = snapshot_store::preview_take_snapshot(&paths, &path)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let entry = snapshot_store::take_snapshot(
+                &paths,
+                &path,
+                &reason,
+                snapshot_store::SOURCE_MANUAL,
+            )?;
+            if json_output {
+                return print_json(&entry);
+            }
+            println!(
+                "Snapshot {} of {} ({})",
+                entry.id, entry.save_name, entry.hash
+            );
+            Ok(())
+        }
+        Command::Snapshots { save } => {
+            let save_name = save
+                .map(|save| save_backup::archive_name_of(&paths, Path::new(&save)))
+                .transpose()?;
+            let snapshots = snapshot_store::list_snapshots(&paths, save_name.as_deref())?;
+            if json_output {
+                return print_json(&snapshots);
+            }
+            for entry in &snapshots {
+                println!(
+                    "{}  {:<32} {:<14} {:>9} bytes  {}",
+                    entry.id, entry.save_name, entry.reason, entry.size, entry.created_at
+                );
+            }
+            println!("{} snapshot(s)", snapshots.len());
+            Ok(())
+        }
+        Command::RestoreSnapshot { id } => {
+            if dry_run {
+                let preview = snapshot_store::preview_restore_snapshot(&paths, &id)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let restored = snapshot_store::restore_snapshot(&paths, &id)?;
+            let path = &restored.path;
+            report(
+                json_output,
+                json!({ "success": true, "path": path, "mainsaveError": restored.mainsave_error }),
+                || match &restored.mainsave_error {
+                    Some(e) => format!(
+                        "Restored {} from snapshot {} (MAINSAVE not updated: {})",
+                        path, id, e
+                    ),
+                    None => format!("Restored {} from snapshot {}", path, id),
+                },
+            )
+        }
+        Command::GcSnapshots => {
+            if dry_run {
+                let preview = snapshot_store::preview_gc(&paths)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let gc = snapshot_store::gc(&paths)?;
+            if json_output {
+                return print_json(&gc);
+            }
+            println!(
+                "Removed {} blob(s), freed {} bytes",
+                gc.removed_blobs, gc.freed_bytes
+            );
+            Ok(())
+        }
+        Command::Diff { a, b } => {
+            let a = resolve_save_file(&paths, &a)?;
+            let b = resolve_save_file(&paths, &b)?;
+            let diff = save_diff::diff_files(&a, &b)?;
+            if json_output {
+                return print_json(&diff);
+            }
+            println!("{}", diff.to_text());
+            Ok(())
+        }
+        Command::Get { save, path } => {
+            let file = resolve_save_file(&paths, &save)?;
+            let result = property_path::get_file_value(&file, &path)?;
+            if json_output {
+                print_json(&result)
+            } else {
+                print_json(&result.value)
+            }
+        }
+        Command::Set {
+            save,
+            path,
+            value,
+            value_type,
+        } => {
+            let file = resolve_save_file(&paths, &save)?;
+            let value = property_path::TypedValue::parse(&value_type, &value)?;
+            if dry_run {
+                let preview = property_path::preview_set_file_value(&file, &path, &value)?;
+                return print_dry_run(json_output, &preview);
+            }
+            let result = property_path::set_file_value(&paths, &file, &path, &value)?;
+            report(
+                json_output,
+                json!({ "success": true, "result": result }),
+                || {
+                    if result.created.is_empty() {
+                        format!("Set {}", result.path)
+                    } else {
+                        format!(
+                            "Set {} (created {})",
+                            result.path,
+                            result.created.join(", ")
+                        )
+                    }
+                },
+            )
+        }
+        Command::Lint { save, fix } => {
+            let path = resolve_save_file(&paths, &save)?;
+            let report = if fix {
+                let fixes: Vec<_> = save_lint::lint_file(&path)?
+                    .issues
+                    .into_iter()
+                    .filter_map(|issue| issue.fix)
+                    .collect();
+                if dry_run {
+                    let preview = save_lint::preview_fix_file(&path, &fixes)?;
+                    return print_dry_run(json_output, &preview);
+                }
+                save_lint::fix_file(&paths, &path, &fixes)?
+            } else {
+                save_lint::lint_file(&path)?
+            };
+            if json_output {
+                return print_json(&report);
+            }
+            if report.applied > 0 {
+                println!("Applied {} fix(es)", report.applied);
+            }
+            if report.issues.is_empty() {
+                println!("No issues found");
+            }
+            for issue in &report.issues {
+                println!(
+                    "{:<8} {}{}",
+                    format!("{:?}", issue.severity).to_lowercase(),
+                    issue.description,
+                    if issue.fix.is_some() && !fix {
+                        " (fixable)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_mainsave(
+    paths: &SavePaths,
+    action: MainsaveCommand,
+    json_output: bool,
+    dry_run: bool,
+) -> AppResult<()> {
+    let outcome = match action {
+        MainsaveCommand::Status => {
+            let status = mainsave_manager::mainsave_status(paths)?;
+            if json_output {
+                return print_json(&status);
+            }
+            for entry in &status.entries {
+                println!(
+                    "{:>3}  {:<10} {}",
+                    entry.position,
+                    format!("{:?}", entry.status),
+                    entry.archive_name
+                );
+            }
+            for name in &status.unlisted {
+                println!("  -  {:<10} {}", "Unlisted", name);
+            }
+            return Ok(());
+        }
+        MainsaveCommand::Repair { template } => {
+            return match mainsave_manager::repair(paths, template.as_deref(), dry_run)? {
+                save_diff::Outcome::DryRun(preview) => print_dry_run(json_output, &preview),
+                save_diff::Outcome::Applied(result) => report(json_output, json!(result), || {
+                    let mut text = format!(
+                        "MAINSAVE rebuilt from {} with {} saves",
+                        result.template,
+                        result.listed.len()
+                    );
+                    if let Some(quarantined) = &result.quarantined {
+                        text.push_str(&format!("\nbroken file moved to {}", quarantined));
+                    }
+                    text
+                }),
+            };
+        }
+        MainsaveCommand::Reorder { archive_names } => {
+            mainsave_manager::reorder(paths, &archive_names, dry_run)?.map(|list| json!(list))
+        }
+        MainsaveCommand::DropOrphans => mainsave_manager::drop_orphans(paths, dry_run)?
+            .map(|dropped| json!(dropped.iter().map(|e| &e.archive_name).collect::<Vec<_>>())),
+        MainsaveCommand::AddAll { order, append } => {
+            mainsave_manager::add_all(paths, order.order(), append, dry_run)?
+                .map(|added| json!(added))
+        }
+    };
+    match outcome {
+        save_diff::Outcome::DryRun(preview) => print_dry_run(json_output, &preview),
+        save_diff::Outcome::Applied(names) => report(json_output, names.clone(), || {
+            let names: Vec<&str> = names
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            format!("MAINSAVE updated: {}", names.join(", "))
+        }),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> AppResult<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value)
+            .map_err(|e| format!("JSON formatting failed: {}", e))?
+    );
+    Ok(())
+}
+
+/// Note on stderr that a listing treated every save as hidden for lack of MAINSAVE
+fn warn_mainsave_unreadable(error: Option<AppError>) {
+    if let Some(e) = error {
+        eprintln!(
+            "warning: {}; every save is listed as hidden (`etbsave mainsave repair` rebuilds it)",
+            e
+        );
+    }
+}
+
+/// Print a dry-run preview as JSON or text
+fn print_dry_run(json_output: bool, preview: &DryRunReport) -> AppResult<()> {
+    if json_output {
+        print_json(preview)
+    } else {
+        println!("{}", preview.to_text());
+        Ok(())
+    }
+}
+
+/// Print the JSON result or the human-readable message
+fn report(json_output: bool, result: Value, message: impl FnOnce() -> String) -> AppResult<()> {
+    if json_output {
+        print_json(&result)
+    } else {
+        println!("{}", message());
+        Ok(())
+    }
+}
+
+/// Accept a full path, a file name inside SaveGames, or a bare archive name. The
+/// result must lie inside SaveGames.
+fn resolve_save_file(paths: &SavePaths, arg: &str) -> AppResult<PathBuf> {
+    let path = find_save_file(paths, arg)?;
+    paths.validate_save_games_path(&path)?;
+    Ok(path)
+}
+
+fn find_save_file(paths: &SavePaths, arg: &str) -> AppResult<PathBuf> {
+    let direct = PathBuf::from(arg);
+    if direct.is_file() {
+        return Ok(direct);
+    }
+    let in_root = paths.save_games_dir().join(arg);
+    if in_root.is_file() {
+        return Ok(in_root);
+    }
+
+    let matches: Vec<PathBuf> = get_file_path::list_save_paths(paths)?
+        .into_iter()
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| common::extract_archive_name(n).eq_ignore_ascii_case(arg))
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(AppError::Validation(format!("Save not found: {}", arg))),
+        1 => Ok(matches.into_iter().next().unwrap_or_default()),
+        n => Err(AppError::Validation(format!(
+            "'{}' matches {} saves; pass the file path instead",
+            arg, n
+        ))),
+    }
+}
+
+/// Original .sav path of a trashed save (the .sav.trash file itself is also
+/// accepted); it must lie inside SaveGames
+fn resolve_trashed_file(paths: &SavePaths, arg: &str) -> AppResult<PathBuf> {
+    let arg = arg.strip_suffix(".trash").unwrap_or(arg);
+    let direct = PathBuf::from(arg);
+    let path = if direct.with_extension("sav.trash").is_file() {
+        direct
+    } else {
+        paths.save_games_dir().join(arg)
+    };
+    paths.validate_save_games_path(&path)?;
+    Ok(path)
+}
+
+/// Filename-derived info plus parsed level / difficulty
+fn save_info(
+    paths: &SavePaths,
+    path: &Path,
+    save: &EtbSave,
+) -> AppResult<save_utils::SaveFileInfo> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid filename")?;
+    let (visible_saves, mainsave_error) = common::visible_saves_for_listing(paths);
+    warn_mainsave_unreadable(mainsave_error);
+    let visible = visible_saves.contains(common::extract_archive_name(file_name));
+    save_utils::build_save_file_info(
+        paths,
+        0,
+        path,
+        cli_handlers::get_modified_date(path).unwrap_or_default(),
+        Some(cli_handlers::extract_current_level(save)),
+        Some(cli_handlers::extract_difficulty_label(save).into_owned()),
+        visible,
+    )
+}
+
+fn players_of(save: &EtbSave) -> Vec<PlayerInfo> {
+    let (ids, sanities, inventories) = player_data::extract_player_data(save);
+    ids.into_iter()
+        .zip(sanities)
+        .zip(inventories)
+        .map(|((id, sanity), inventory)| PlayerInfo {
+            id,
+            sanity,
+            inventory,
+        })
+        .collect()
+}
+
+fn find_player(roster: &[PlayerInfo], id: &str) -> Option<usize> {
+    roster
+        .iter()
+        .position(|p| p.id == id)
+        .or_else(|| roster.iter().position(|p| pure_player_id(&p.id) == id))
+}
+
+/// Split an `ID=VALUE` option
+fn split_assignment<'a>(option: &str, arg: &'a str) -> AppResult<(&'a str, &'a str)> {
+    arg.split_once('=')
+        .map(|(id, value)| (id.trim(), value.trim()))
+        .filter(|(id, _)| !id.is_empty())
+        .ok_or_else(|| {
+            AppError::Validation(format!("--{} expects ID=VALUE, got '{}'", option, arg))
+        })
+}
+
+/// Add players and apply inventory / sanity changes. New players get the full
+/// `<steam id>_+_|<EOS PUID>` key when one is known; otherwise the game ignores them.
+fn apply_player_args(
+    paths: &SavePaths,
+    roster: &mut Vec<PlayerInfo>,
+    args: &PlayerArgs,
+) -> AppResult<()> {
+    let add = |id: &str, roster: &mut Vec<PlayerInfo>| -> usize {
+        if let Some(idx) = find_player(roster, id) {
+            return idx;
+        }
+        let key = save_batch::resolve_player_full_key(paths, id).unwrap_or_else(|| {
+            tracing::warn!(
+                "No EOS key known for {}; the game ignores bare-id player data",
+                id
+            );
+            id.to_string()
+        });
+        roster.push(PlayerInfo {
+            id: key,
+            sanity: 100.0,
+            inventory: Vec::new(),
+        });
+        roster.len() - 1
+    };
+
+    for id in &args.player {
+        add(id.trim(), roster);
+    }
+
+    for arg in &args.inventory {
+        let (id, items) = split_assignment("inventory", arg)?;
+        let inventory = items
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_item)
+            .collect::<AppResult<Vec<String>>>()?;
+        if inventory.len() > save_shared::INVENTORY_SLOTS {
+            return Err(AppError::Validation(format!(
+                "At most {} inventory slots",
+                save_shared::INVENTORY_SLOTS
+            )));
+        }
+        let idx = add(id, roster);
+        roster[idx].inventory = inventory;
+    }
+
+    for arg in &args.sanity {
+        let (id, value) = split_assignment("sanity", arg)?;
+        let sanity: f64 = value
+            .parse()
+            .map_err(|_| AppError::Validation(format!("Invalid sanity value: {}", value)))?;
+        let idx = add(id, roster);
+        roster[idx].sanity = sanity.clamp(0.0, 100.0);
+    }
+
+    Ok(())
+}
+
+/// Item by English name or numeric id; `None` keeps the slot empty
+fn parse_item(item: &str) -> AppResult<String> {
+    if item.eq_ignore_ascii_case("None") {
+        return Ok("None".to_string());
+    }
+    let name = match item.parse::<i32>() {
+        Ok(id) => Some(save_shared::map_item_id_to_name(id)).filter(|n| *n != "None"),
+        Err(_) => save_shared::map_item_name_to_id(item).map(save_shared::map_item_id_to_name),
+    };
+    name.map(str::to_string).ok_or_else(|| {
+        AppError::Validation(format!(
+            "Unknown item '{}'; known items: {}",
+            item,
+            save_shared::item_names().collect::<Vec<_>>().join(", ")
+        ))
+    })
+}
+
+/// Canonical level name (case-insensitive match against the known level list)
+fn canonical_level(level: &str) -> AppResult<&'static str> {
+    new_save::ALL_LEVELS
+        .iter()
+        .map(|(_, name)| *name)
+        .find(|name| name.eq_ignore_ascii_case(level))
+        .ok_or_else(|| AppError::Validation(format!("Unknown level: {}", level)))
+}
@@ -2,21 +2,22 @@
 //! Optimized version: Using Cow to reduce string allocations, optimized file read strategy
 
 use crate::error::AppResult;
+use crate::etb_save::EtbSave;
 use chrono::{DateTime, Local};
 use memmap2::Mmap;
 use std::borrow::Cow;
 use std::fs::File;
 use std::io::{Cursor, Read};
 use std::path::Path;
-use uesave::{Property, Save};
+use uesave::Save;
 
 /// Small file threshold (32KB), read directly below this
 const SMALL_FILE_THRESHOLD: u64 = 32768;
 /// Medium file threshold (512KB), use pre-allocated buffer between thresholds
 const MEDIUM_FILE_THRESHOLD: u64 = 524288;
 
-/// Parse .sav file into a Save object (three-tier file size strategy)
-pub fn parse_sav_file(path: &Path) -> AppResult<Save> {
+/// Parse .sav file into an EtbSave (three-tier file size strategy)
+pub fn parse_sav_file(path: &Path) -> AppResult<EtbSave> {
     let file = File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
     let file_size = file
         .metadata()
@@ -29,9 +30,7 @@ pub fn parse_sav_file(path: &Path) -> AppResult<Save> {
         let mut buffer = Vec::with_capacity(file_size as usize);
         file.read_to_end(&mut buffer)
             .map_err(|e| format!("读取文件内容失败: {}", e))?;
-        return Ok(
-            Save::read(&mut Cursor::new(&buffer)).map_err(|e| format!("解析存档失败: {:?}", e))?
-        );
+        return read_save(&buffer);
     }
 
     // Medium files: pre-allocate buffer of exact size
@@ -40,15 +39,20 @@ pub fn parse_sav_file(path: &Path) -> AppResult<Save> {
         let mut buffer = vec![0u8; file_size as usize];
         file.read_exact(&mut buffer)
             .map_err(|e| format!("读取文件内容失败: {}", e))?;
-        return Ok(
-            Save::read(&mut Cursor::new(&buffer)).map_err(|e| format!("解析存档失败: {:?}", e))?
-        );
+        return read_save(&buffer);
     }
 
     // Large files: use memory mapping
     // SAFETY: The file is not mutated during the mapping lifetime, and the returned Save owns a copy of the data via Cursor.
     let mmap = unsafe { Mmap::map(&file) }.map_err(|e| format!("内存映射失败: {}", e))?;
-    Ok(Save::read(&mut Cursor::new(&mmap[..])).map_err(|e| format!("解析存档失败: {:?}", e))?)
+    read_save(&mmap[..])
+}
+
+#[inline]
+fn read_save(bytes: &[u8]) -> AppResult<EtbSave> {
+    Ok(Save::read(&mut Cursor::new(bytes))
+        .map(EtbSave::from)
+        .map_err(|e| format!("解析存档失败: {:?}", e))?)
 }
 
 /// Get file last modified date in "YYYY-MM-DD" format
@@ -62,57 +66,15 @@ pub fn get_modified_date(path: &Path) -> AppResult<String> {
     Ok(datetime.format("%Y-%m-%d").to_string())
 }
 
-/// Helper function: find property by name (ignoring type ID)
-#[inline]
-fn get_property_by_name<'a>(save: &'a Save, name: &str) -> Option<&'a Property> {
-    save.root
-        .properties
-        .0
-        .iter()
-        .find(|(key, _)| key.1 == name)
-        .map(|(_, prop)| prop)
-}
-
 /// Extract CurrentLevel_0.Name field value, and determine Pipes1/Pipes2 based on UnlockedFun_0
 #[inline]
-pub fn extract_current_level(save: &Save) -> String {
-    let current_level = get_property_by_name(save, "CurrentLevel")
-        .and_then(|prop| match prop {
-            Property::Name(level) => Some(level.as_str()),
-            _ => None,
-        })
-        .unwrap_or("Level0");
-
-    if current_level == "Pipes" {
-        let is_unlocked = get_property_by_name(save, "UnlockedFun")
-            .and_then(|prop| match prop {
-                Property::Bool(value) => Some(*value),
-                _ => None,
-            })
-            .unwrap_or(false);
-        if is_unlocked {
-            "Pipes2".to_string()
-        } else {
-            "Pipes1".to_string()
-        }
-    } else {
-        current_level.to_string()
-    }
+pub fn extract_current_level(save: &EtbSave) -> String {
+    save.current_level()
 }
 
 /// Extract Difficulty_0.Byte.Label and map difficulty level
 /// Returns Cow to avoid allocation of static strings
 #[inline]
-pub fn extract_difficulty_label(save: &Save) -> Cow<'static, str> {
-    let difficulty_label = get_property_by_name(save, "Difficulty").and_then(|prop| match prop {
-        Property::Byte(uesave::Byte::Label(label)) => Some(label.as_str()),
-        _ => None,
-    });
-
-    match difficulty_label {
-        Some(s) if s.contains("NewEnumerator0") => Cow::Borrowed("Easy"),
-        Some(s) if s.contains("NewEnumerator1") => Cow::Borrowed("Hard"),
-        Some(s) if s.contains("NewEnumerator2") => Cow::Borrowed("Nightmare"),
-        _ => Cow::Borrowed("Normal"),
-    }
+pub fn extract_difficulty_label(save: &EtbSave) -> Cow<'static, str> {
+    Cow::Borrowed(save.difficulty().label())
 }
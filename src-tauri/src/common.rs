@@ -1,234 +1,289 @@
 //! Common utilities module - Shared tools and types across modules
 
 use crate::error::{AppError, AppResult};
+use crate::file_lock::{self, FileLock};
+use crate::save_backup::{backup_file, MAINSAVE_ARCHIVE};
+use crate::save_diff::{diff_roots, PropertyChange};
+use crate::save_paths::SavePaths;
+use crate::save_schema::{self, SINGLEPLAYER_SAVES};
+use crate::save_verify::verified_bytes;
 use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufReader, ErrorKind};
 use std::path::{Path, PathBuf};
-use std::sync::{Mutex, OnceLock};
-use uesave::{
-    Property, PropertyKey, PropertyTagDataPartial, PropertyTagPartial, PropertyType, Save, ValueVec,
-};
-
-/// Cache local app data directory path
-static LOCAL_APPDATA_DIR: OnceLock<AppResult<PathBuf>> = OnceLock::new();
-static SAVE_GAMES_DIR: OnceLock<AppResult<PathBuf>> = OnceLock::new();
-static APP_CONFIG_DIR: OnceLock<AppResult<PathBuf>> = OnceLock::new();
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use uesave::{Property, PropertyKey, Save, ValueVec};
 
 /// I/O buffer size (16KB is more efficient for small files)
 const IO_BUFFER_SIZE: usize = 16384;
 
-/// Get local app data directory (with caching, returns reference to avoid clones)
-#[inline]
-pub fn get_local_appdata_dir() -> AppResult<PathBuf> {
-    LOCAL_APPDATA_DIR
-        .get_or_init(|| {
-            #[cfg(target_os = "windows")]
-            {
-                std::env::var("LOCALAPPDATA")
-                    .map(PathBuf::from)
-                    .map_err(|e| AppError::General(format!("Failed to get LOCALAPPDATA: {}", e)))
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                Err(AppError::General("Only Windows is supported".to_string()))
-            }
-        })
-        .clone()
+/// Helper: run a blocking closure via tokio::task::spawn_blocking,
+/// mapping the join error into an AppResult.
+pub(crate) async fn run_blocking<F, T>(f: F) -> AppResult<T>
+where
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Task was cancelled: {}", e))?
 }
 
-/// Get save games directory path (with caching)
-#[inline]
-pub fn get_save_games_dir() -> AppResult<PathBuf> {
-    SAVE_GAMES_DIR
-        .get_or_init(|| {
-            get_local_appdata_dir().map(|p| p.join("EscapeTheBackrooms/Saved/SaveGames"))
-        })
-        .clone()
-}
+/// Read MAINSAVE.sav file (optimized buffer size). A missing or unparsable file is
+/// [`AppError::MainsaveCorrupt`].
+pub fn read_mainsave(paths: &SavePaths) -> AppResult<Save> {
+    let mainsave_path = paths.mainsave_path();
 
-/// Get MAINSAVE.sav file path (inline optimized)
-#[inline(always)]
-pub fn get_mainsave_path() -> AppResult<PathBuf> {
-    get_save_games_dir().map(|p| p.join("MAINSAVE.sav"))
-}
+    let file = File::open(&mainsave_path).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => AppError::MainsaveCorrupt("MAINSAVE.sav not found".to_string()),
+        _ => AppError::Io(format!("Failed to open MAINSAVE.sav: {}", e)),
+    })?;
+    let mut reader = BufReader::with_capacity(IO_BUFFER_SIZE, file);
 
-/// Get app config directory (with caching)
-#[inline]
-pub fn get_app_config_dir() -> AppResult<PathBuf> {
-    APP_CONFIG_DIR
-        .get_or_init(|| get_local_appdata_dir().map(|p| p.join("ETBSaveManager")))
-        .clone()
+    Save::read(&mut reader)
+        .map_err(|e| AppError::MainsaveCorrupt(format!("Failed to parse MAINSAVE.sav: {:?}", e)))
 }
 
-/// Read MAINSAVE.sav file (optimized buffer size)
-pub fn read_mainsave() -> AppResult<Save> {
-    let mainsave_path = get_mainsave_path()?;
+/// Write MAINSAVE.sav file (using temp file for atomicity)
+pub fn write_mainsave(paths: &SavePaths, save: &Save) -> AppResult<()> {
+    let mainsave_path = paths.mainsave_path();
 
-    let file =
-        File::open(&mainsave_path).map_err(|e| format!("Failed to open MAINSAVE.sav: {}", e))?;
-    let mut reader = BufReader::with_capacity(IO_BUFFER_SIZE, file);
+    // Write to temp file (only once the bytes re-parse to the same tree)
+    let bytes = verified_bytes(save, None, "MAINSAVE.sav")?;
+    backup_file(paths, &mainsave_path, "mainsave")?;
+    write_atomic(&mainsave_path, &bytes)
+}
 
-    Ok(Save::read(&mut reader).map_err(|e| format!("Failed to parse MAINSAVE.sav: {:?}", e))?)
+/// Temp file next to `target` that no other writer (thread or process) picks:
+/// `<name>.<pid>-<n>.tmp`, which doesn't end in `.sav` so listings skip it
+pub fn temp_path_for(target: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    target.with_file_name(format!(
+        "{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
 }
 
-/// Write MAINSAVE.sav file (using temp file for atomicity)
-pub fn write_mainsave(save: &Save) -> AppResult<()> {
-    let save_dir = get_save_games_dir()?;
-    let mainsave_path = save_dir.join("MAINSAVE.sav");
-    let temp_path = save_dir.join("MAINSAVE_temp.sav");
-
-    // Write to temp file
-    let file = File::create(&temp_path)
-        .map_err(|e| format!("Failed to create temp MAINSAVE file: {}", e))?;
-    let mut writer = BufWriter::with_capacity(IO_BUFFER_SIZE, file);
-
-    save.write(&mut writer)
-        .map_err(|e| format!("Failed to write MAINSAVE.sav: {:?}", e))?;
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush buffer: {}", e))?;
-
-    // Atomic replace
-    Ok(fs::rename(&temp_path, &mainsave_path)
-        .map_err(|e| format!("Failed to replace MAINSAVE.sav: {}", e))?)
+/// Replace `target` with `bytes` via a unique temp file and a rename
+pub fn write_atomic(target: &Path, bytes: &[u8]) -> AppResult<()> {
+    let temp_path = temp_path_for(target);
+    fs::write(&temp_path, bytes).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to write temp file: {}", e)
+    })?;
+    fs::rename(&temp_path, target).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        AppError::from(format!("Failed to replace {:?}: {}", target, e))
+    })
 }
 
-/// Get visible saves list from MAINSAVE (pre-allocated capacity)
-pub fn get_visible_saves_set() -> AppResult<HashSet<String>> {
-    let _lock = MAINSAVE_LOCK
-        .get_or_init(|| Mutex::new(()))
-        .lock()
-        .map_err(|e| format!("MAINSAVE lock poisoned: {}", e))?;
+/// Get visible saves list from MAINSAVE (pre-allocated capacity). Fails instead of
+/// reporting every save as hidden when MAINSAVE can't be read; library listings
+/// use [`visible_saves_for_listing`] instead.
+pub fn get_visible_saves_set(paths: &SavePaths) -> AppResult<HashSet<String>> {
+    let _lock = mainsave_lock(paths)?;
 
-    let mainsave = match read_mainsave() {
-        Ok(save) => save,
-        Err(_) => return Ok(HashSet::new()),
-    };
+    Ok(visible_saves_of(&read_mainsave(paths)?))
+}
+
+/// Visible saves for listing a library. An unreadable MAINSAVE doesn't block the
+/// listing: every save reads as not visible and the error is handed back for the
+/// caller to report next to the list.
+pub fn visible_saves_for_listing(paths: &SavePaths) -> (HashSet<String>, Option<AppError>) {
+    match get_visible_saves_set(paths) {
+        Ok(visible) => (visible, None),
+        Err(e) => {
+            tracing::warn!("Listing saves without MAINSAVE: {}", e);
+            (HashSet::new(), Some(e))
+        }
+    }
+}
 
-    let key = PropertyKey(0, "SingleplayerSaves".to_string());
+/// Archive names a parsed MAINSAVE lists in `SingleplayerSaves`
+pub fn visible_saves_of(mainsave: &Save) -> HashSet<String> {
+    let key = PropertyKey(0, SINGLEPLAYER_SAVES.to_string());
 
     if let Some(Property::Array(ValueVec::Str(ref saves))) = mainsave.root.properties.0.get(&key) {
         let mut set = HashSet::with_capacity(saves.len());
         set.extend(saves.iter().cloned());
-        return Ok(set);
+        return set;
+    }
+
+    HashSet::new()
+}
+
+/// `SingleplayerSaves` of a parsed MAINSAVE in stored order (duplicates kept)
+pub fn listed_saves(mainsave: &Save) -> Vec<String> {
+    let key = PropertyKey(0, SINGLEPLAYER_SAVES.to_string());
+    match mainsave.root.properties.0.get(&key) {
+        Some(Property::Array(ValueVec::Str(saves))) => saves.clone(),
+        _ => Vec::new(),
     }
+}
 
-    Ok(HashSet::new())
+/// Replace `SingleplayerSaves` wholesale; returns whether it changed
+pub fn set_listed_saves(mainsave: &mut Save, saves: Vec<String>) -> bool {
+    if listed_saves(mainsave) == saves {
+        return false;
+    }
+    let key = PropertyKey(0, SINGLEPLAYER_SAVES.to_string());
+    if !mainsave.root.properties.0.contains_key(&key) {
+        save_schema::record(mainsave, SINGLEPLAYER_SAVES);
+    }
+    mainsave
+        .root
+        .properties
+        .0
+        .insert(key, Property::Array(ValueVec::Str(saves)));
+    true
 }
 
 /// Serialize MAINSAVE operations across threads to prevent race conditions
 static MAINSAVE_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
-/// Add save name to MAINSAVE's save list
-pub fn add_save_to_mainsave(archive_name: &str) -> AppResult<()> {
-    let _lock = MAINSAVE_LOCK
+/// Held MAINSAVE lock: the in-process mutex plus the OS lock other processes see
+pub struct MainsaveGuard {
+    _file: FileLock,
+    _guard: MutexGuard<'static, ()>,
+}
+
+/// Hold this while reading-modifying-writing MAINSAVE. Fails with
+/// [`AppError::Locked`] when another process keeps it too long.
+pub fn mainsave_lock(paths: &SavePaths) -> AppResult<MainsaveGuard> {
+    let guard = MAINSAVE_LOCK
         .get_or_init(|| Mutex::new(()))
         .lock()
         .map_err(|e| format!("MAINSAVE lock poisoned: {}", e))?;
+    Ok(MainsaveGuard {
+        _file: file_lock::lock(paths, MAINSAVE_ARCHIVE)?,
+        _guard: guard,
+    })
+}
 
-    // During a batch create the game itself may be rewriting MAINSAVE concurrently,
-    // which makes a single read fail transiently. Retry a couple of times before
-    // giving up so newly created saves actually register as visible instead of
-    // silently staying hidden.
-    let mut mainsave = match read_mainsave() {
-        Ok(save) => save,
-        Err(_) => {
-            let mut result = None;
-            let mut last_err = String::new();
-            for _ in 0..3 {
-                std::thread::sleep(std::time::Duration::from_millis(60));
-                match read_mainsave() {
-                    Ok(save) => {
-                        result = Some(save);
-                        break;
-                    }
-                    Err(e) => last_err = e.to_string(),
-                }
-            }
-            match result {
-                Some(save) => save,
-                None => {
-                    tracing::error!(
-                        "Failed to read MAINSAVE after retries: {}",
-                        last_err
-                    );
-                    return Ok(()); // Silently skip (missing/corrupt — game rebuilds it on next launch)
-                }
-            }
+/// One edit to MAINSAVE's `SingleplayerSaves` list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MainsaveOp {
+    /// Insert at the front, or move there if already listed
+    Add(String),
+    Remove(String),
+    Rename {
+        old: String,
+        new: String,
+    },
+}
+
+/// Apply `op` to a parsed MAINSAVE; returns whether the list changed
+pub fn apply_mainsave_op(mainsave: &mut Save, op: &MainsaveOp) -> bool {
+    let key = PropertyKey(0, SINGLEPLAYER_SAVES.to_string());
+
+    let Some(Property::Array(ValueVec::Str(saves))) = mainsave.root.properties.0.get_mut(&key)
+    else {
+        if let MainsaveOp::Add(archive_name) = op {
+            // New field: record schema (0.7 requires schemas for writing) + insert property
+            save_schema::record(mainsave, SINGLEPLAYER_SAVES);
+            let new_prop = Property::Array(ValueVec::Str(vec![archive_name.clone()]));
+            mainsave.root.properties.0.insert(key, new_prop);
+            return true;
         }
+        return false;
     };
 
-    let key = PropertyKey(0, "SingleplayerSaves".to_string());
-
-    if let Some(Property::Array(ValueVec::Str(ref mut saves))) =
-        mainsave.root.properties.0.get_mut(&key)
-    {
-        if saves.iter().any(|s| s == archive_name) {
-            // Already exists — move to front (e.g. after editing)
+    match op {
+        MainsaveOp::Add(archive_name) => {
+            if saves.first() == Some(archive_name) {
+                return false;
+            }
+            // Already listed — move to front (e.g. after editing)
             saves.retain(|s| s != archive_name);
-            saves.insert(0, archive_name.to_string());
-            return write_mainsave(&mainsave);
+            saves.insert(0, archive_name.clone());
+            true
         }
-        saves.insert(0, archive_name.to_string());
-    } else {
-        // New field: record schema (0.7 requires schemas for writing) + insert property
-        mainsave.schemas.record(
-            "SingleplayerSaves".to_string(),
-            PropertyTagPartial {
-                id: None,
-                data: PropertyTagDataPartial::Array(Box::new(PropertyTagDataPartial::Other(
-                    PropertyType::StrProperty,
-                ))),
-            },
-        );
-        let new_prop = Property::Array(ValueVec::Str(vec![archive_name.to_string()]));
-        mainsave.root.properties.0.insert(key, new_prop);
+        MainsaveOp::Remove(archive_name) => {
+            let original_len = saves.len();
+            saves.retain(|s| s != archive_name);
+            saves.len() < original_len
+        }
+        MainsaveOp::Rename { old, new } => match saves.iter_mut().find(|s| *s == old) {
+            Some(save) => {
+                *save = new.clone();
+                true
+            }
+            None => false,
+        },
     }
-
-    write_mainsave(&mainsave)
 }
 
-/// Remove save name from MAINSAVE's save list
-pub fn remove_save_from_mainsave(archive_name: &str) -> AppResult<bool> {
-    let _lock = MAINSAVE_LOCK
-        .get_or_init(|| Mutex::new(()))
-        .lock()
-        .map_err(|e| format!("MAINSAVE lock poisoned: {}", e))?;
+/// MAINSAVE changes `ops` would make, without writing. An unreadable MAINSAVE
+/// fails the preview like it fails adding and renaming; removals preview as no
+/// change, since deletes still go ahead and report the MAINSAVE error.
+pub fn preview_mainsave(paths: &SavePaths, ops: &[MainsaveOp]) -> AppResult<Vec<PropertyChange>> {
+    let _lock = mainsave_lock(paths)?;
 
-    let mut mainsave = match read_mainsave() {
-        Ok(save) => save,
-        Err(e) => {
-            tracing::error!("Failed to read MAINSAVE: {}", e);
-            return Ok(false);
-        }
+    let before = match read_mainsave(paths) {
+        Ok(before) => before,
+        Err(e) if ops.iter().any(|op| !matches!(op, MainsaveOp::Remove(_))) => return Err(e),
+        Err(_) => return Ok(Vec::new()),
     };
+    let mut after = before.clone();
+    for op in ops {
+        apply_mainsave_op(&mut after, op);
+    }
+    Ok(diff_roots(&before, &after))
+}
 
-    let key = PropertyKey(0, "SingleplayerSaves".to_string());
+/// Add save name to MAINSAVE's save list
+pub fn add_save_to_mainsave(paths: &SavePaths, archive_name: &str) -> AppResult<()> {
+    let _lock = mainsave_lock(paths)?;
 
-    if let Some(Property::Array(ValueVec::Str(ref mut saves))) =
-        mainsave.root.properties.0.get_mut(&key)
-    {
-        let original_len = saves.len();
-        saves.retain(|s| s != archive_name);
-        if saves.len() < original_len {
-            write_mainsave(&mainsave)?;
-            return Ok(true);
+    // During a batch create the game itself may be rewriting MAINSAVE concurrently,
+    // which makes a single read fail transiently. Retry a couple of times; a MAINSAVE
+    // that still won't read is reported (`repair_mainsave` rebuilds it) rather than
+    // leaving the new save silently hidden.
+    let mut mainsave = read_mainsave(paths);
+    for _ in 0..3 {
+        if mainsave.is_ok() {
+            break;
         }
+        std::thread::sleep(std::time::Duration::from_millis(60));
+        mainsave = read_mainsave(paths);
+    }
+    let mut mainsave = mainsave
+        .inspect_err(|e| tracing::error!("Failed to read MAINSAVE after retries: {}", e))?;
+
+    if apply_mainsave_op(&mut mainsave, &MainsaveOp::Add(archive_name.to_string())) {
+        write_mainsave(paths, &mainsave)?;
     }
+    Ok(())
+}
+
+/// Remove save name from MAINSAVE's save list; returns whether it was listed
+pub fn remove_save_from_mainsave(paths: &SavePaths, archive_name: &str) -> AppResult<bool> {
+    let _lock = mainsave_lock(paths)?;
+
+    let mut mainsave =
+        read_mainsave(paths).inspect_err(|e| tracing::error!("Failed to read MAINSAVE: {}", e))?;
 
-    Ok(false)
+    let removed = apply_mainsave_op(&mut mainsave, &MainsaveOp::Remove(archive_name.to_string()));
+    if removed {
+        write_mainsave(paths, &mainsave)?;
+    }
+    Ok(removed)
 }
 
 /// Update archive name in MAINSAVE's SingleplayerSaves list after conversion.
 /// Used when renaming SINGLEPLAYER_ archives to MULTIPLAYER_ prefix.
-pub fn update_mainsave_archive_name(old_name: &str, new_name: &str) -> AppResult<()> {
-    let _lock = MAINSAVE_LOCK
-        .get_or_init(|| Mutex::new(()))
-        .lock()
-        .map_err(|e| format!("MAINSAVE lock poisoned: {}", e))?;
-
-    let mut mainsave = match read_mainsave() {
+pub fn update_mainsave_archive_name(
+    paths: &SavePaths,
+    old_name: &str,
+    new_name: &str,
+) -> AppResult<()> {
+    let _lock = mainsave_lock(paths)?;
+
+    let mut mainsave = match read_mainsave(paths) {
         Ok(save) => save,
         Err(e) => {
             tracing::error!("Failed to read MAINSAVE: {}", e);
@@ -236,19 +291,12 @@ pub fn update_mainsave_archive_name(old_name: &str, new_name: &str) -> AppResult
         }
     };
 
-    let key = PropertyKey(0, "SingleplayerSaves".to_string());
-
-    if let Some(Property::Array(ValueVec::Str(ref mut saves))) =
-        mainsave.root.properties.0.get_mut(&key)
-    {
-        // Find and replace the old archive name with the new one
-        for save in saves.iter_mut() {
-            if save == old_name {
-                *save = new_name.to_string();
-                break;
-            }
-        }
-        write_mainsave(&mainsave)?;
+    let op = MainsaveOp::Rename {
+        old: old_name.to_string(),
+        new: new_name.to_string(),
+    };
+    if apply_mainsave_op(&mut mainsave, &op) {
+        write_mainsave(paths, &mainsave)?;
     }
 
     Ok(())
@@ -312,21 +360,10 @@ pub fn validate_path_under_base(path: &Path, allowed_base: &Path) -> AppResult<(
 
 /// Safely open a file, using O_NOFOLLOW to prevent symlink attacks
 #[allow(dead_code)]
-pub fn safe_file_open(path: &Path) -> AppResult<File> {
+pub fn safe_file_open(paths: &SavePaths, path: &Path) -> AppResult<File> {
     // Validate path before opening the file
-    validate_save_games_path(path)?;
+    paths.validate_save_games_path(path)?;
 
     // Open file, reject symlinks (guaranteed by canonicalize on Windows)
     Ok(File::open(path).map_err(|e| format!("Failed to open file: {}", e))?)
 }
-
-pub fn validate_save_games_path(path: &Path) -> AppResult<()> {
-    let base = get_save_games_dir()?;
-    validate_path_under_base(path, &base)
-}
-
-#[allow(dead_code)]
-pub fn validate_app_config_path(path: &Path) -> AppResult<()> {
-    let base = get_app_config_dir()?;
-    validate_path_under_base(path, &base)
-}
@@ -13,6 +13,17 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     #[allow(dead_code)]
     Validation(String),
+    #[error("Verification error: {0}")]
+    Verification(String),
+    /// A pagination cursor that expired or was invalidated by a library change
+    #[error("Cursor expired: {0}")]
+    CursorExpired(String),
+    /// MAINSAVE.sav is missing or can't be parsed; `repair_mainsave` rebuilds it
+    #[error("MAINSAVE unreadable: {0}")]
+    MainsaveCorrupt(String),
+    /// Another process holds the lock on MAINSAVE or a save; retry later
+    #[error("Locked: {0}")]
+    Locked(String),
     #[error("{0}")]
     General(String),
 }
@@ -29,6 +40,10 @@ impl Serialize for AppError {
             AppError::Io(msg) => ("io", msg),
             AppError::Parse(msg) => ("parse", msg),
             AppError::Validation(msg) => ("validation", msg),
+            AppError::Verification(msg) => ("verification", msg),
+            AppError::CursorExpired(msg) => ("cursorExpired", msg),
+            AppError::MainsaveCorrupt(msg) => ("mainsaveCorrupt", msg),
+            AppError::Locked(msg) => ("locked", msg),
             AppError::General(msg) => ("general", msg),
         };
         let mut state = serializer.serialize_struct("AppError", 2)?;
@@ -0,0 +1,806 @@
+//! ETB save module - Typed view over an Escape the Backrooms `uesave::Save`
+//! Single place that knows the game's property names and encodings. Every setter
+//! records the uesave 0.7 schemas it needs (from `save_schema`), so callers can't
+//! forget them.
+
+use crate::error::AppResult;
+use crate::save_schema::{self, SchemaKind};
+use crate::save_shared;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use uesave::{
+    MapEntry, Properties, Property, PropertyKey, PropertyType, Save, StructValue, ValueVec,
+};
+
+/// Root property names
+pub const CURRENT_LEVEL: &str = "CurrentLevel";
+pub const UNLOCKED_FUN: &str = "UnlockedFun";
+pub const DIFFICULTY: &str = "Difficulty";
+pub const HAS_COMPLETED_MAIN_ENDING: &str = "HasCompletedMainEnding";
+pub const IS_MEG_UNLOCKED: &str = "IsMEGUnlocked";
+pub const IS_MEG_POWER_ON: &str = "IsMEGPowerOn";
+pub const IS_MEG_SECURITY_UNLOCKED: &str = "IsMEGSecurityUnlocked";
+pub const LEVELS_COMPLETED: &str = "LevelsCompleted";
+pub const PLAYER_DATA: &str = "PlayerData";
+
+/// Level name the game stores for both halves of Pipe Dreams
+pub const PIPES: &str = "Pipes";
+/// Name of the second Pipe Dreams visit in LevelsCompleted and the frontend
+pub const PIPES2: &str = "Pipes2";
+/// Placeholder key the game writes for a player it couldn't identify
+pub const BAD_PLAYER_KEY: &str = "ERROR, BAD UNIQUE NET ID";
+/// Separator between the steam id and the EOS PUID in a PlayerData key
+const EOS_SEPARATOR: &str = "_+_|";
+/// All-zeros EOS id an earlier app version wrote; the game ignores such keys
+const PLACEHOLDER_EOS_ID: &str = "00000000000000000000000000000000";
+
+/// Whether the key carries a real EOS account id suffix (32 hex, not the all-zeros
+/// placeholder an earlier app version wrote).
+pub fn is_real_eos_key(key: &str) -> bool {
+    match key.find(EOS_SEPARATOR) {
+        Some(idx) => {
+            let suffix = &key[idx + EOS_SEPARATOR.len()..];
+            suffix.len() == 32 && suffix != PLACEHOLDER_EOS_ID
+        }
+        None => false,
+    }
+}
+
+/// Whether the key carries the all-zeros placeholder EOS id
+pub fn is_placeholder_eos_key(key: &str) -> bool {
+    key.find(EOS_SEPARATOR)
+        .is_some_and(|idx| &key[idx + EOS_SEPARATOR.len()..] == PLACEHOLDER_EOS_ID)
+}
+
+/// Steam id part of a PlayerData key: strips the `_+_|<EOS PUID>` (online) or
+/// `-<suffix>` (offline) part.
+pub fn pure_player_id(key: &str) -> &str {
+    if let Some(idx) = key.find(EOS_SEPARATOR) {
+        &key[..idx]
+    } else if let Some(idx) = key.find('-') {
+        &key[..idx]
+    } else {
+        key
+    }
+}
+
+/// Current level as the frontend names it: `Level0` when absent, and
+/// `Pipes1` / `Pipes2` depending on UnlockedFun.
+pub fn display_level(raw_current_level: Option<&str>, unlocked_fun: bool) -> String {
+    match raw_current_level.unwrap_or("Level0") {
+        PIPES if unlocked_fun => PIPES2.to_string(),
+        PIPES => "Pipes1".to_string(),
+        level => level.to_string(),
+    }
+}
+
+/// In-game difficulty, ordered easiest first. Normal is encoded by the absence
+/// of a Difficulty field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    /// Display label used in file names and by the frontend
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    /// Parse a display label (case-insensitive)
+    pub fn from_label(label: &str) -> Option<Self> {
+        [
+            Difficulty::Easy,
+            Difficulty::Normal,
+            Difficulty::Hard,
+            Difficulty::Nightmare,
+        ]
+        .into_iter()
+        .find(|d| d.label().eq_ignore_ascii_case(label))
+    }
+
+    /// `E_Difficulty` enumerator stored in the save (`None` for Normal)
+    fn enumerator(self) -> Option<&'static str> {
+        match self {
+            Difficulty::Easy => Some("E_Difficulty::NewEnumerator0"),
+            Difficulty::Normal => None,
+            Difficulty::Hard => Some("E_Difficulty::NewEnumerator1"),
+            Difficulty::Nightmare => Some("E_Difficulty::NewEnumerator2"),
+        }
+    }
+
+    pub(crate) fn from_enumerator(label: &str) -> Self {
+        if label.contains("NewEnumerator0") {
+            Difficulty::Easy
+        } else if label.contains("NewEnumerator1") {
+            Difficulty::Hard
+        } else if label.contains("NewEnumerator2") {
+            Difficulty::Nightmare
+        } else {
+            Difficulty::Normal
+        }
+    }
+}
+
+/// The three M.E.G. base flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MegFlags {
+    pub unlocked: bool,
+    pub power_on: bool,
+    pub security_unlocked: bool,
+}
+
+/// One LevelsCompleted element
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelCompletion {
+    pub display_name: String,
+    pub level_name: String,
+    pub has_completed: bool,
+    pub has_unlocked_hub: bool,
+    pub time: f32,
+}
+
+impl LevelCompletion {
+    /// A record with no recorded time, unlocked and completed together
+    pub fn new(display_name: &str, level_name: &str, completed: bool) -> Self {
+        Self {
+            display_name: display_name.to_string(),
+            level_name: level_name.to_string(),
+            has_completed: completed,
+            has_unlocked_hub: completed,
+            time: -1.0,
+        }
+    }
+}
+
+/// One PlayerData entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerEntry {
+    /// Map key: `<steam id>_+_|<EOS PUID>`, or a bare id the game ignores
+    pub key: String,
+    /// `None` when the entry has no sanity field
+    pub sanity: Option<f32>,
+    /// Item names, `None` for an empty slot; empty when the entry has no inventory field
+    pub inventory: Vec<String>,
+}
+
+impl PlayerEntry {
+    /// Filled (non-`None`) inventory slots
+    pub fn item_count(&self) -> usize {
+        self.inventory
+            .iter()
+            .filter(|i| i.as_str() != "None" && !i.is_empty())
+            .count()
+    }
+
+    /// Carries real data: sanity other than the default 100, or any filled slot
+    pub fn has_real_data(&self) -> bool {
+        self.sanity.is_some_and(|s| (s - 100.0).abs() > 0.001) || self.item_count() > 0
+    }
+}
+
+/// Typed wrapper over a parsed save. Dereferences to the raw [`Save`] for reads;
+/// writes go through the typed setters (or the explicit [`EtbSave::as_save_mut`]).
+pub struct EtbSave(Save);
+
+impl From<Save> for EtbSave {
+    fn from(save: Save) -> Self {
+        Self(save)
+    }
+}
+
+impl Deref for EtbSave {
+    type Target = Save;
+
+    fn deref(&self) -> &Save {
+        &self.0
+    }
+}
+
+impl EtbSave {
+    /// Parse a GVAS stream
+    pub fn read<R: Read>(reader: &mut R) -> AppResult<Self> {
+        Save::read(reader)
+            .map(Self)
+            .map_err(|e| format!("Failed to parse save: {:?}", e).into())
+    }
+
+    /// Serialize as GVAS
+    pub fn write<W: Write>(&self, writer: &mut W) -> AppResult<()> {
+        self.0
+            .write(writer)
+            .map_err(|e| format!("Failed to write save: {:?}", e).into())
+    }
+
+    #[inline]
+    pub fn as_save(&self) -> &Save {
+        &self.0
+    }
+
+    /// Raw access for edits the typed API does not cover; schemas are the caller's job
+    #[inline]
+    pub fn as_save_mut(&mut self) -> &mut Save {
+        &mut self.0
+    }
+
+    #[inline]
+    pub fn into_save(self) -> Save {
+        self.0
+    }
+
+    /// Root property by name (ignoring the key index)
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.0
+            .root
+            .properties
+            .0
+            .iter()
+            .find(|(key, _)| key.1 == name)
+            .map(|(_, prop)| prop)
+    }
+
+    fn property_mut(&mut self, name: &str) -> Option<&mut Property> {
+        self.0
+            .root
+            .properties
+            .0
+            .iter_mut()
+            .find(|(key, _)| key.1 == name)
+            .map(|(_, prop)| prop)
+    }
+
+    /// Insert or replace a root property, recording its registry schemas first
+    fn insert_property(&mut self, name: &str, value: Property) {
+        save_schema::record_tree(&mut self.0, name);
+        self.0
+            .root
+            .properties
+            .0
+            .insert(PropertyKey(0, name.to_string()), value);
+    }
+
+    /// Remove a root property; returns whether it existed
+    fn remove_property(&mut self, name: &str) -> bool {
+        self.0
+            .root
+            .properties
+            .0
+            .shift_remove(&PropertyKey(0, name.to_string()))
+            .is_some()
+    }
+
+    // ---- CurrentLevel ----
+
+    /// The stored CurrentLevel name (`Pipes` for both Pipe Dreams halves), `None` when absent
+    pub fn raw_current_level(&self) -> Option<&str> {
+        match self.property(CURRENT_LEVEL) {
+            Some(Property::Name(level)) => Some(level.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Current level as the frontend names it: `Level0` when absent, and
+    /// `Pipes1` / `Pipes2` depending on UnlockedFun.
+    pub fn current_level(&self) -> String {
+        display_level(self.raw_current_level(), self.unlocked_fun())
+    }
+
+    /// Set the current level. `Pipes1` / `Pipes2` are stored as `Pipes` plus the
+    /// matching UnlockedFun flag. Returns false (and leaves the save untouched)
+    /// when an existing CurrentLevel has an unexpected type.
+    pub fn set_current_level(&mut self, level: &str) -> bool {
+        if let Some(other) = self
+            .property(CURRENT_LEVEL)
+            .filter(|p| !matches!(p, Property::Name(_)))
+        {
+            tracing::error!("CurrentLevel_0 type error, expected Name, got {:?}", other);
+            return false;
+        }
+
+        let stored = match level {
+            "Pipes1" => {
+                self.set_unlocked_fun(false);
+                PIPES
+            }
+            PIPES2 => {
+                self.set_unlocked_fun(true);
+                PIPES
+            }
+            other => other,
+        };
+
+        match self.property_mut(CURRENT_LEVEL) {
+            Some(Property::Name(name)) => {
+                *name = stored.to_string();
+                tracing::info!("CurrentLevel_0 modified to: {}", stored);
+                true
+            }
+            // Checked above
+            Some(_) => false,
+            None => {
+                tracing::info!("Created CurrentLevel_0 field with value: {}", stored);
+                self.insert_property(CURRENT_LEVEL, Property::Name(stored.to_string()));
+                true
+            }
+        }
+    }
+
+    /// Drop CurrentLevel entirely (the game then starts in Level 0)
+    pub fn clear_current_level(&mut self) -> bool {
+        let removed = self.remove_property(CURRENT_LEVEL);
+        if removed {
+            tracing::info!("CurrentLevel_0 has been completely removed");
+        }
+        removed
+    }
+
+    /// UnlockedFun distinguishes the second Pipe Dreams visit
+    pub fn unlocked_fun(&self) -> bool {
+        self.bool_flag(UNLOCKED_FUN)
+    }
+
+    /// UnlockedFun is only ever stored as `true`; clearing removes the field
+    pub fn set_unlocked_fun(&mut self, unlocked: bool) {
+        if unlocked {
+            self.set_bool_flag(UNLOCKED_FUN, true);
+        } else if self.remove_property(UNLOCKED_FUN) {
+            tracing::info!("Deleted UnlockedFun_0 field");
+        }
+    }
+
+    // ---- Difficulty ----
+
+    /// In-game difficulty; Normal when no Difficulty field exists
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty_label()
+            .map_or(Difficulty::Normal, Difficulty::from_enumerator)
+    }
+
+    /// The stored `E_Difficulty` enumerator label, `None` when absent
+    pub fn difficulty_label(&self) -> Option<&str> {
+        self.0
+            .root
+            .properties
+            .0
+            .iter()
+            .filter(|(key, _)| key.1.starts_with(DIFFICULTY))
+            .find_map(|(_, prop)| match prop {
+                Property::Byte(uesave::Byte::Label(label)) => Some(label.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Replace every Difficulty field; Normal leaves none
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        tracing::info!("Processing difficulty settings: {}", difficulty.label());
+        self.0
+            .root
+            .properties
+            .0
+            .retain(|key, _| !key.1.starts_with(DIFFICULTY));
+
+        if let Some(enumerator) = difficulty.enumerator() {
+            self.insert_property(
+                DIFFICULTY,
+                Property::Byte(uesave::Byte::Label(enumerator.to_string())),
+            );
+            tracing::info!("Created difficulty field: {}", enumerator);
+        }
+    }
+
+    // ---- Flags ----
+
+    /// A root BoolProperty; absent counts as false
+    pub fn bool_flag(&self, name: &str) -> bool {
+        matches!(self.property(name), Some(Property::Bool(true)))
+    }
+
+    /// Set a root BoolProperty (created when missing)
+    pub fn set_bool_flag(&mut self, name: &str, value: bool) {
+        save_schema::record_or(
+            &mut self.0,
+            name,
+            SchemaKind::Scalar(PropertyType::BoolProperty),
+        );
+        self.insert_property(name, Property::Bool(value));
+        tracing::info!("Set {} field to {}", name, value);
+    }
+
+    pub fn has_completed_main_ending(&self) -> bool {
+        self.bool_flag(HAS_COMPLETED_MAIN_ENDING)
+    }
+
+    pub fn set_has_completed_main_ending(&mut self, value: bool) {
+        self.set_bool_flag(HAS_COMPLETED_MAIN_ENDING, value);
+    }
+
+    pub fn meg_flags(&self) -> MegFlags {
+        MegFlags {
+            unlocked: self.bool_flag(IS_MEG_UNLOCKED),
+            power_on: self.bool_flag(IS_MEG_POWER_ON),
+            security_unlocked: self.bool_flag(IS_MEG_SECURITY_UNLOCKED),
+        }
+    }
+
+    pub fn set_meg_flags(&mut self, flags: MegFlags) {
+        self.set_bool_flag(IS_MEG_UNLOCKED, flags.unlocked);
+        self.set_bool_flag(IS_MEG_POWER_ON, flags.power_on);
+        self.set_bool_flag(IS_MEG_SECURITY_UNLOCKED, flags.security_unlocked);
+    }
+
+    /// Unlock (or lock) the M.E.G. base: all three flags move together
+    pub fn set_meg_unlocked(&mut self, unlocked: bool) {
+        self.set_meg_flags(MegFlags {
+            unlocked,
+            power_on: unlocked,
+            security_unlocked: unlocked,
+        });
+    }
+
+    // ---- LevelsCompleted ----
+
+    /// Every LevelsCompleted element that has the expected struct layout
+    pub fn levels_completed(&self) -> Vec<LevelCompletion> {
+        let Some(Property::Array(ValueVec::Struct(levels))) = self.property(LEVELS_COMPLETED)
+        else {
+            return Vec::new();
+        };
+        levels
+            .iter()
+            .filter_map(|level| match level {
+                StructValue::Struct(props) => Some(level_from_props(props)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Replace LevelsCompleted. Each element gets a default World (no dropped items,
+    /// sanity 100). The struct type already recorded for the save is kept.
+    pub fn set_levels_completed(&mut self, levels: &[LevelCompletion]) {
+        let values = levels.iter().map(level_value).collect();
+
+        self.insert_property(LEVELS_COMPLETED, Property::Array(ValueVec::Struct(values)));
+        tracing::info!("LevelsCompleted_0 updated with {} levels", levels.len());
+    }
+
+    /// Mark every recorded level completed and unlocked, then append any of `levels`
+    /// (display name, level name) that are missing. A missing or malformed
+    /// LevelsCompleted is rebuilt first. Returns how many levels were added.
+    pub fn unlock_hub_doors(&mut self, levels: &[(&str, &str)]) -> usize {
+        let Some(values) = self.levels_completed_mut() else {
+            return 0;
+        };
+
+        let mut existing = std::collections::HashSet::new();
+        for value in values.iter_mut() {
+            let StructValue::Struct(props) = value else {
+                continue;
+            };
+            if let Some(Property::Name(name)) = props
+                .0
+                .iter()
+                .find(|(k, _)| k.1.starts_with("LevelName"))
+                .map(|(_, p)| p)
+            {
+                existing.insert(name.clone());
+            }
+            for (_, prop) in props.0.iter_mut() {
+                if let Property::Bool(b) = prop {
+                    *b = true;
+                }
+            }
+        }
+
+        let mut added = 0;
+        for (display_name, level_name) in levels {
+            if existing.contains(*level_name) {
+                continue;
+            }
+            tracing::info!("Adding missing level: {} ({})", display_name, level_name);
+            values.push(level_value(&LevelCompletion::new(
+                display_name,
+                level_name,
+                true,
+            )));
+            added += 1;
+        }
+        added
+    }
+
+    /// Append one LevelsCompleted record, leaving existing records (and their
+    /// World data) untouched. Returns false when a record for the level exists.
+    pub fn add_level_completed(&mut self, level: &LevelCompletion) -> bool {
+        if self
+            .levels_completed()
+            .iter()
+            .any(|l| l.level_name.eq_ignore_ascii_case(&level.level_name))
+        {
+            return false;
+        }
+        let Some(values) = self.levels_completed_mut() else {
+            return false;
+        };
+        tracing::info!(
+            "Adding level record: {} ({})",
+            level.display_name,
+            level.level_name
+        );
+        values.push(level_value(level));
+        true
+    }
+
+    /// LevelsCompleted elements for in-place edits; a missing or malformed array is
+    /// rebuilt empty first
+    fn levels_completed_mut(&mut self) -> Option<&mut Vec<StructValue>> {
+        if !matches!(
+            self.property(LEVELS_COMPLETED),
+            Some(Property::Array(ValueVec::Struct(_)))
+        ) {
+            tracing::warn!("LevelsCompleted_0 missing or malformed, creating default structure");
+            self.set_levels_completed(&[]);
+        }
+        save_schema::record_tree(&mut self.0, LEVELS_COMPLETED);
+
+        match self.property_mut(LEVELS_COMPLETED) {
+            Some(Property::Array(ValueVec::Struct(values))) => Some(values),
+            _ => None,
+        }
+    }
+
+    // ---- PlayerData ----
+
+    /// Every PlayerData entry, in map order (the game's bad-id placeholder is skipped)
+    pub fn players(&self) -> Vec<PlayerEntry> {
+        let Some(Property::Map(entries)) = self.property(PLAYER_DATA) else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let Property::Str(key) = &entry.key else {
+                    return None;
+                };
+                if key == BAD_PLAYER_KEY {
+                    return None;
+                }
+                let Property::Struct(StructValue::Struct(props)) = &entry.value else {
+                    return None;
+                };
+                Some(player_from_props(key, props))
+            })
+            .collect()
+    }
+
+    /// Every PlayerData key as stored, including the game's bad-id placeholder
+    pub fn player_keys(&self) -> Vec<&str> {
+        let Some(Property::Map(entries)) = self.property(PLAYER_DATA) else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|entry| match &entry.key {
+                Property::Str(key) => Some(key.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Remove every entry stored under exactly `key`; returns whether any existed
+    pub fn remove_player(&mut self, key: &str) -> bool {
+        let mut removed = false;
+        self.retain_players(|player| {
+            let keep = player.key != key;
+            removed |= !keep;
+            keep
+        });
+        removed
+    }
+
+    /// Update the entry whose key matches (ignoring surrounding whitespace) or append
+    /// a new one. Inventory is padded / cut to 12 slots and sanity clamped to 0–100;
+    /// a new entry without sanity starts at 100. Other fields of an existing entry
+    /// are left alone.
+    pub fn set_player(&mut self, player: &PlayerEntry) {
+        save_schema::record_tree(&mut self.0, PLAYER_DATA);
+        if self.property(PLAYER_DATA).is_none() {
+            self.0.root.properties.0.insert(
+                PropertyKey(0, PLAYER_DATA.to_string()),
+                Property::Map(vec![]),
+            );
+        }
+        let Some(Property::Map(entries)) = self.property_mut(PLAYER_DATA) else {
+            tracing::error!("PlayerData_0 type error, expected Map");
+            return;
+        };
+
+        let existing = entries
+            .iter_mut()
+            .find(|entry| matches!(&entry.key, Property::Str(k) if k.trim() == player.key.trim()));
+        match existing {
+            Some(MapEntry {
+                value: Property::Struct(StructValue::Struct(props)),
+                ..
+            }) => write_player_props(props, player),
+            Some(_) => tracing::error!("PlayerData entry '{}' is not a struct", player.key),
+            None => {
+                tracing::info!("Creating new player data: {}", player.key);
+                let mut props = Properties::default();
+                write_player_props(
+                    &mut props,
+                    &PlayerEntry {
+                        sanity: Some(player.sanity.unwrap_or(100.0)),
+                        ..player.clone()
+                    },
+                );
+                entries.push(MapEntry {
+                    key: Property::Str(player.key.clone()),
+                    value: Property::Struct(StructValue::Struct(props)),
+                });
+            }
+        }
+    }
+
+    /// Replace PlayerData with exactly `players` (keeping the field's position);
+    /// an empty list removes the field
+    pub fn set_players(&mut self, players: &[PlayerEntry]) {
+        if players.is_empty() {
+            self.remove_property(PLAYER_DATA);
+            return;
+        }
+        match self.property_mut(PLAYER_DATA) {
+            Some(Property::Map(entries)) => entries.clear(),
+            _ => {
+                self.remove_property(PLAYER_DATA);
+            }
+        }
+        for player in players {
+            self.set_player(player);
+        }
+    }
+
+    /// Keep only entries for which `keep` returns true, visiting entries in map
+    /// order. Removes the whole field when no entry is left.
+    pub fn retain_players<F: FnMut(&PlayerEntry) -> bool>(&mut self, mut keep: F) {
+        let Some(Property::Map(entries)) = self.property_mut(PLAYER_DATA) else {
+            return;
+        };
+        entries.retain(|entry| match (&entry.key, &entry.value) {
+            (Property::Str(key), Property::Struct(StructValue::Struct(props))) => {
+                keep(&player_from_props(key, props))
+            }
+            _ => true,
+        });
+        if entries.is_empty() {
+            tracing::info!("All players removed, deleting PlayerData_0 field");
+            self.remove_property(PLAYER_DATA);
+        }
+    }
+}
+
+fn find_field<'a>(props: &'a Properties, name: &str) -> Option<&'a Property> {
+    props
+        .0
+        .iter()
+        .find(|(key, _)| key.1 == name)
+        .map(|(_, prop)| prop)
+}
+
+fn level_from_props(props: &Properties) -> LevelCompletion {
+    let bool_field = |name| matches!(find_field(props, name), Some(Property::Bool(true)));
+    LevelCompletion {
+        display_name: match find_field(props, save_shared::DISPLAY_NAME_FIELD) {
+            Some(Property::Str(s)) => s.clone(),
+            _ => String::new(),
+        },
+        level_name: match find_field(props, save_shared::LEVEL_NAME_FIELD) {
+            Some(Property::Name(s)) => s.clone(),
+            _ => String::new(),
+        },
+        has_completed: bool_field(save_shared::HAS_COMPLETED_FIELD),
+        has_unlocked_hub: bool_field(save_shared::HAS_UNLOCKED_HUB_FIELD),
+        time: match find_field(props, save_shared::TIME_FIELD) {
+            Some(Property::Float(f)) => f.0,
+            _ => -1.0,
+        },
+    }
+}
+
+/// A complete LevelsCompleted element with a default World (no dropped items, sanity 100)
+fn level_value(level: &LevelCompletion) -> StructValue {
+    let mut props = Properties::default();
+    write_level_props(&mut props, level);
+    props.0.insert(
+        PropertyKey(0, save_shared::WORLD_FIELD.to_string()),
+        save_shared::create_default_world_property(),
+    );
+    StructValue::Struct(props)
+}
+
+/// Write the scalar fields of a level record (World is left to the caller)
+fn write_level_props(props: &mut Properties, level: &LevelCompletion) {
+    for (name, value) in [
+        (
+            save_shared::DISPLAY_NAME_FIELD,
+            Property::Str(level.display_name.clone()),
+        ),
+        (
+            save_shared::HAS_COMPLETED_FIELD,
+            Property::Bool(level.has_completed),
+        ),
+        (
+            save_shared::HAS_UNLOCKED_HUB_FIELD,
+            Property::Bool(level.has_unlocked_hub),
+        ),
+        (
+            save_shared::LEVEL_NAME_FIELD,
+            Property::Name(level.level_name.clone()),
+        ),
+        (
+            save_shared::TIME_FIELD,
+            Property::Float(uesave::Float(level.time)),
+        ),
+    ] {
+        props.0.insert(PropertyKey(0, name.to_string()), value);
+    }
+}
+
+fn player_from_props(key: &str, props: &Properties) -> PlayerEntry {
+    PlayerEntry {
+        key: key.to_string(),
+        sanity: match find_field(props, save_shared::SANITY_PROP_NAME) {
+            Some(Property::Float(v)) => Some(v.0),
+            _ => None,
+        },
+        inventory: match find_field(props, save_shared::INVENTORY_PROP_NAME) {
+            Some(Property::Array(ValueVec::Name(names))) => names
+                .iter()
+                .take(save_shared::INVENTORY_SLOTS)
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        },
+    }
+}
+
+/// Write inventory (12 slots) and sanity (when given) into a player struct
+fn write_player_props(props: &mut Properties, player: &PlayerEntry) {
+    let mut inventory: Vec<String> = player
+        .inventory
+        .iter()
+        .take(save_shared::INVENTORY_SLOTS)
+        .cloned()
+        .collect();
+    inventory.resize(save_shared::INVENTORY_SLOTS, "None".to_string());
+
+    set_field(
+        props,
+        save_shared::INVENTORY_PROP_NAME,
+        save_shared::create_inventory_property(inventory),
+    );
+    if let Some(sanity) = player.sanity {
+        set_field(
+            props,
+            save_shared::SANITY_PROP_NAME,
+            save_shared::create_sanity_property(sanity),
+        );
+    }
+}
+
+/// Replace a struct field in place (whatever its key index), or append it
+fn set_field(props: &mut Properties, name: &str, value: Property) {
+    match props.0.iter_mut().find(|(key, _)| key.1 == name) {
+        Some((_, prop)) => *prop = value,
+        None => {
+            props.0.insert(PropertyKey(0, name.to_string()), value);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! File lock module - Advisory OS locks shared with other processes
+//! The in-process `mainsave_lock` mutex can't see a second app instance or a
+//! scripted `etbsave` run. Every MAINSAVE read-modify-write and every save
+//! mutation also takes an exclusive lock on a file under
+//! `SaveGames/.etbsave-locks/`, one per archive name, so any process pointed at
+//! the same library waits for the others. The lock is released when the
+//! [`FileLock`] is dropped.
+
+use crate::error::{AppError, AppResult};
+use crate::save_backup::archive_name_of;
+use crate::save_paths::SavePaths;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Lock files live here, inside SaveGames
+pub const LOCK_DIR: &str = ".etbsave-locks";
+/// How long to wait for another process before giving up with `Locked`
+const LOCK_WAIT: Duration = Duration::from_secs(3);
+const RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+/// An exclusive lock on one lock file; unlocked on drop
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+}
+
+/// Lock `name` in the library `paths` points at, waiting up to [`LOCK_WAIT`]
+pub fn lock(paths: &SavePaths, name: &str) -> AppResult<FileLock> {
+    lock_in(&paths.save_games_dir().join(LOCK_DIR), name)
+}
+
+/// Lock `<dir>/<name>.lock`, for stores that live outside SaveGames
+pub fn lock_in(dir: &Path, name: &str) -> AppResult<FileLock> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create lock directory: {}", e))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(format!("{}.lock", name)))
+        .map_err(|e| format!("Failed to open lock file for {}: {}", name, e))?;
+
+    let deadline = Instant::now() + LOCK_WAIT;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(FileLock { _file: file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                std::thread::sleep(RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(AppError::Locked(format!(
+                    "{} is locked by another writer",
+                    name
+                )))
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(AppError::Io(format!("Failed to lock {}: {}", name, e)))
+            }
+        }
+    }
+}
+
+/// Lock the save at `path` (a trashed `X.sav.trash` shares the lock of `X.sav`)
+pub fn lock_save(paths: &SavePaths, path: &Path) -> AppResult<FileLock> {
+    lock(paths, &archive_name_of(paths, path)?)
+}
+
+/// Lock several saves, in name order so two processes can't each hold half
+pub fn lock_saves<'a>(
+    paths: &SavePaths,
+    files: impl IntoIterator<Item = &'a Path>,
+) -> AppResult<Vec<FileLock>> {
+    let mut names = files
+        .into_iter()
+        .map(|path| archive_name_of(paths, path))
+        .collect::<AppResult<Vec<String>>>()?;
+    names.sort();
+    names.dedup();
+    names.iter().map(|name| lock(paths, name)).collect()
+}
@@ -1,8 +1,8 @@
 //! File path module - Save file scanning
 //! Optimized version: Reduce regex overhead, use fast path checking
 
-use crate::common::get_save_games_dir;
 use crate::error::AppResult;
+use crate::save_paths::SavePaths;
 use rayon::prelude::*;
 use std::ffi::OsStr;
 use std::path::PathBuf;
@@ -70,8 +70,8 @@ fn validate_save_filename(name: &str) -> bool {
 }
 
 /// List all save file paths (optimized version)
-pub fn list_save_paths() -> AppResult<Vec<PathBuf>> {
-    let base_dir = get_save_games_dir()?;
+pub fn list_save_paths(paths: &SavePaths) -> AppResult<Vec<PathBuf>> {
+    let base_dir = paths.save_games_dir();
 
     if !base_dir.exists() {
         return Err("Save directory not found".to_string().into());
@@ -81,7 +81,7 @@ pub fn list_save_paths() -> AppResult<Vec<PathBuf>> {
     let mut entries = Vec::with_capacity(100);
 
     // Single-threaded collection (WalkDir itself doesn't support parallel)
-    for entry in WalkDir::new(&base_dir)
+    for entry in WalkDir::new(base_dir)
         .max_depth(2)
         .into_iter()
         .filter_map(Result::ok)
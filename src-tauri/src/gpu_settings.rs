@@ -1,5 +1,5 @@
-use crate::common::get_app_config_dir;
 use crate::error::AppResult;
+use crate::path_resolver::resolve_app_config_dir;
 use serde_json::Value;
 use std::fs;
 
@@ -25,7 +25,7 @@ const GPU_DISABLED_ARGS: &[&str] = &["--disable-gpu", "--disable-software-raster
 /// Get current GPU acceleration setting status
 #[tauri::command]
 pub fn get_gpu_acceleration_status() -> AppResult<bool> {
-    let config_file = get_app_config_dir()?.join("gpu_config.json");
+    let config_file = resolve_app_config_dir()?.join("gpu_config.json");
 
     if !config_file.exists() {
         return Ok(false);
@@ -47,7 +47,7 @@ pub fn get_gpu_acceleration_status() -> AppResult<bool> {
 /// Returns whether app restart is required for changes to take effect
 #[tauri::command]
 pub fn set_gpu_acceleration(disabled: bool) -> AppResult<bool> {
-    let config_dir = get_app_config_dir()?;
+    let config_dir = resolve_app_config_dir()?;
     let config_file = config_dir.join("gpu_config.json");
 
     if !config_dir.exists() {
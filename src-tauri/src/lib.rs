@@ -1,18 +1,40 @@
 ﻿pub mod cli_handlers;
 pub mod common;
-mod error;
-mod get_file_path;
+pub mod error;
+pub mod etb_save;
+pub mod file_lock;
+pub mod get_file_path;
 mod gpu_settings;
+pub mod library_cursor;
+pub mod library_events;
+pub mod library_profiles;
+pub mod library_stream;
+pub mod mainsave_manager;
+pub mod metadata_index;
+pub mod path_resolver;
+pub mod property_path;
 pub mod new_save;
 pub mod player_data;
-mod save_batch;
+pub mod save_backup;
+pub mod save_batch;
+pub mod save_bulk;
 
 mod save_converter;
-mod save_deleter;
+pub mod save_deleter;
+pub mod save_diff;
 pub mod save_editor;
-mod save_loader;
+pub mod save_lint;
+pub mod save_loader;
+pub mod save_paths;
+pub mod save_query;
+pub mod save_scan;
+pub mod save_schema;
 pub mod save_shared;
-mod save_utils;
+pub mod save_trash;
+pub mod save_utils;
+pub mod save_verify;
+pub mod save_watcher;
+pub mod snapshot_store;
 mod system_commands;
 mod theme_commands;
 
@@ -38,21 +60,58 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(save_paths::SavePathsState::resolve())
+        .manage(save_watcher::SaveWatcherState::default())
+        .manage(library_cursor::LibraryCursors::default())
+        .manage(library_stream::LibraryStreams::default())
+        .setup(|app| {
+            save_watcher::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             save_loader::load_all_saves,
             save_loader::load_save_metadata,
             save_loader::load_save_metadata_page,
+            save_loader::release_library_cursor,
+            library_stream::stream_library,
+            library_stream::cancel_library_stream,
             save_loader::load_save_details_batch,
+            metadata_index::load_save_index_details,
+            metadata_index::clear_metadata_index,
+            save_query::query_saves,
             save_deleter::delete_file,
             save_deleter::soft_delete_file,
             save_deleter::restore_file,
             save_deleter::permanent_delete_file,
+            save_trash::list_trash_files,
+            save_trash::restore_trash_file,
+            save_trash::empty_trash_files,
+            save_trash::purge_expired_trash,
+            save_trash::get_trash_config,
+            save_trash::set_trash_config,
+            save_bulk::bulk_delete_files,
+            save_bulk::bulk_soft_delete_files,
+            save_bulk::bulk_restore_files,
+            save_bulk::bulk_toggle_visibility,
+            mainsave_manager::get_mainsave_status,
+            mainsave_manager::reorder_mainsave,
+            mainsave_manager::drop_mainsave_orphans,
+            mainsave_manager::add_all_saves_to_mainsave,
+            mainsave_manager::repair_mainsave,
             save_deleter::handle_file,
             save_batch::get_player_data,
             save_batch::get_player_unique_ids,
             save_batch::unlock_all_hub_doors,
             save_batch::handle_edit_save,
             system_commands::get_local_appdata,
+            system_commands::get_save_games_path,
+            path_resolver::get_save_root_status,
+            path_resolver::set_save_games_override,
+            library_profiles::list_library_profiles,
+            library_profiles::add_library_profile,
+            library_profiles::remove_library_profile,
+            library_profiles::switch_library_profile,
+            library_profiles::transfer_save_between_libraries,
             save_converter::ensure_dir_exists,
             save_converter::handle_new_save,
             save_deleter::open_save_games_folder,
@@ -62,6 +121,24 @@ pub fn run() {
             system_commands::restart_app,
             save_converter::convert_sav_to_json,
             save_converter::convert_json_to_sav,
+            save_verify::verify_save_roundtrip,
+            save_diff::diff_saves,
+            save_backup::list_save_backups,
+            save_backup::list_all_backups,
+            save_backup::restore_save_backup,
+            save_backup::get_backup_config,
+            save_backup::set_backup_config,
+            snapshot_store::create_snapshot,
+            snapshot_store::list_save_snapshots,
+            snapshot_store::restore_save_snapshot,
+            snapshot_store::delete_save_snapshot,
+            snapshot_store::gc_snapshots,
+            snapshot_store::get_snapshot_stats,
+            save_watcher::get_save_watcher_status,
+            save_lint::lint_save_file,
+            save_lint::apply_lint_fixes,
+            property_path::get_save_property,
+            property_path::set_save_property,
             system_commands::set_window_title,
             theme_commands::get_theme_config,
             theme_commands::set_active_theme
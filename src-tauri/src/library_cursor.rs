@@ -0,0 +1,130 @@
+//! Library cursor module - Snapshot-consistent pagination of save metadata
+//! The first page captures the whole library listing once and hands out a cursor;
+//! later pages slice that snapshot, so ids stay stable and nothing shifts or
+//! repeats while files change. Snapshots expire after a period of disuse or when
+//! the save watcher reports a change under their SaveGames root.
+
+use crate::common::visible_saves_for_listing;
+use crate::error::{AppError, AppResult};
+use crate::get_file_path;
+use crate::save_loader::save_meta;
+use crate::save_paths::SavePaths;
+use crate::save_utils::{SaveFileMeta, SaveFileMetaPage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Unused snapshots are dropped after this long
+const CURSOR_TTL: Duration = Duration::from_secs(300);
+/// Oldest snapshots are evicted beyond this many
+const MAX_CURSORS: usize = 16;
+
+struct LibrarySnapshot {
+    root: PathBuf,
+    items: Arc<Vec<SaveFileMeta>>,
+    last_used: Instant,
+}
+
+/// Tauri managed state holding the live snapshots by cursor id
+#[derive(Default)]
+pub struct LibraryCursors(Mutex<HashMap<String, LibrarySnapshot>>);
+
+impl LibraryCursors {
+    fn lock(&self) -> AppResult<MutexGuard<'_, HashMap<String, LibrarySnapshot>>> {
+        let mut cursors = self
+            .0
+            .lock()
+            .map_err(|e| AppError::General(format!("Cursor lock poisoned: {}", e)))?;
+        cursors.retain(|_, s| s.last_used.elapsed() < CURSOR_TTL);
+        Ok(cursors)
+    }
+
+    /// Scan the library once and register the snapshot. Returns the new cursor id,
+    /// plus the MAINSAVE error when every save was listed as hidden for lack of it.
+    pub fn capture(&self, paths: &SavePaths) -> AppResult<(String, Option<AppError>)> {
+        let (paths_result, (visible_saves, mainsave_error)) = rayon::join(
+            || get_file_path::list_save_paths(paths),
+            || visible_saves_for_listing(paths),
+        );
+        let files = paths_result?;
+
+        // Ids are positions in the snapshot, so they stay put across pages
+        let items: Vec<SaveFileMeta> = files
+            .iter()
+            .filter_map(|path| save_meta(paths, 0, path, &visible_saves))
+            .enumerate()
+            .map(|(i, mut meta)| {
+                meta.id = i as u32;
+                meta
+            })
+            .collect();
+
+        let cursor = uuid::Uuid::new_v4().to_string();
+        let mut cursors = self.lock()?;
+        while cursors.len() >= MAX_CURSORS {
+            let Some(oldest) = cursors
+                .iter()
+                .min_by_key(|(_, s)| s.last_used)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            cursors.remove(&oldest);
+        }
+        cursors.insert(
+            cursor.clone(),
+            LibrarySnapshot {
+                root: paths.save_games_dir().to_path_buf(),
+                items: Arc::new(items),
+                last_used: Instant::now(),
+            },
+        );
+        Ok((cursor, mainsave_error))
+    }
+
+    /// A page of the snapshot behind `cursor`
+    pub fn page(&self, cursor: &str, offset: u32, limit: u32) -> AppResult<SaveFileMetaPage> {
+        let items = {
+            let mut cursors = self.lock()?;
+            let snapshot = cursors.get_mut(cursor).ok_or_else(|| {
+                AppError::CursorExpired(format!(
+                    "Library cursor {} expired or was invalidated; reload from the first page",
+                    cursor
+                ))
+            })?;
+            snapshot.last_used = Instant::now();
+            snapshot.items.clone()
+        };
+
+        let total = items.len() as u32;
+        let start = offset.min(total) as usize;
+        let end = offset.saturating_add(limit).min(total) as usize;
+        Ok(SaveFileMetaPage {
+            items: items[start..end].to_vec(),
+            total,
+            offset,
+            has_more: end < total as usize,
+            cursor: cursor.to_string(),
+        })
+    }
+
+    pub fn release(&self, cursor: &str) -> AppResult<bool> {
+        Ok(self.lock()?.remove(cursor).is_some())
+    }
+
+    /// Drop every snapshot of `root` (called by the watcher on library changes)
+    pub fn invalidate_root(&self, root: &Path) {
+        if let Ok(mut cursors) = self.lock() {
+            let before = cursors.len();
+            cursors.retain(|_, s| s.root != root);
+            if cursors.len() < before {
+                tracing::debug!(
+                    "Invalidated {} library cursor(s) for {:?}",
+                    before - cursors.len(),
+                    root
+                );
+            }
+        }
+    }
+}
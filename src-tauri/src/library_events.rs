@@ -0,0 +1,191 @@
+//! Library events module - Incremental library changes for the card grid
+//! Keeps the last seen state of SaveGames (file stamps + MAINSAVE visibility) and
+//! turns a rescan into `save-added` / `save-removed` / `save-modified` /
+//! `visibility-changed` events. Driven by the save watcher; a rename shows up as
+//! a removal plus an addition.
+
+use crate::common::{extract_archive_name, get_visible_saves_set};
+use crate::error::AppError;
+use crate::get_file_path;
+use crate::save_loader::{save_detail, save_meta};
+use crate::save_paths::SavePaths;
+use crate::save_utils::{SaveFileDetail, SaveFileMeta};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter};
+
+pub const EVENT_SAVE_ADDED: &str = "save-added";
+pub const EVENT_SAVE_REMOVED: &str = "save-removed";
+pub const EVENT_SAVE_MODIFIED: &str = "save-modified";
+pub const EVENT_VISIBILITY_CHANGED: &str = "visibility-changed";
+/// A library listing went ahead without MAINSAVE; payload is the error
+pub const EVENT_MAINSAVE_UNREADABLE: &str = "mainsave-unreadable";
+
+/// Payload of `save-added`, `save-modified` and `visibility-changed`
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveChangedEvent {
+    pub meta: SaveFileMeta,
+    /// Parsed details; `None` for visibility-only changes or unparseable files
+    pub detail: Option<SaveFileDetail>,
+}
+
+/// Payload of `save-removed`
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveRemovedEvent {
+    pub path: String,
+    pub archive_name: String,
+}
+
+pub enum LibraryEvent {
+    Added(SaveChangedEvent),
+    Removed(SaveRemovedEvent),
+    Modified(SaveChangedEvent),
+    VisibilityChanged(SaveChangedEvent),
+}
+
+impl LibraryEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LibraryEvent::Added(_) => EVENT_SAVE_ADDED,
+            LibraryEvent::Removed(_) => EVENT_SAVE_REMOVED,
+            LibraryEvent::Modified(_) => EVENT_SAVE_MODIFIED,
+            LibraryEvent::VisibilityChanged(_) => EVENT_VISIBILITY_CHANGED,
+        }
+    }
+
+    pub fn emit(self, app: &AppHandle) {
+        let name = self.name();
+        let result = match self {
+            LibraryEvent::Removed(payload) => app.emit(name, payload),
+            LibraryEvent::Added(payload)
+            | LibraryEvent::Modified(payload)
+            | LibraryEvent::VisibilityChanged(payload) => app.emit(name, payload),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to emit {}: {}", name, e);
+        }
+    }
+}
+
+/// Report a listing that fell back to "nothing visible" because MAINSAVE
+/// couldn't be read (`repair_mainsave` rebuilds it)
+pub fn emit_mainsave_unreadable(app: &AppHandle, error: Option<AppError>) {
+    if let Some(error) = error {
+        if let Err(e) = app.emit(EVENT_MAINSAVE_UNREADABLE, error) {
+            tracing::warn!("Failed to emit {}: {}", EVENT_MAINSAVE_UNREADABLE, e);
+        }
+    }
+}
+
+/// Modification time and size; a change in either counts as a write
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    size: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Self {
+        let metadata = path.metadata().ok();
+        Self {
+            modified: metadata.as_ref().and_then(|m| m.modified().ok()),
+            size: metadata.map(|m| m.len()).unwrap_or(0),
+        }
+    }
+}
+
+fn is_visible(visible: &HashSet<String>, path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| visible.contains(extract_archive_name(n)))
+}
+
+/// Last seen library state
+#[derive(Default)]
+pub struct LibraryTracker {
+    files: HashMap<PathBuf, FileStamp>,
+    visible: HashSet<String>,
+}
+
+impl LibraryTracker {
+    /// Record the current state without reporting anything (startup, library switch)
+    pub fn reset(&mut self, paths: &SavePaths) {
+        let (files, visible) = Self::scan(paths, &self.visible);
+        self.files = files;
+        self.visible = visible;
+    }
+
+    fn scan(
+        paths: &SavePaths,
+        previous_visible: &HashSet<String>,
+    ) -> (HashMap<PathBuf, FileStamp>, HashSet<String>) {
+        // A missing directory reads as empty
+        let files = get_file_path::list_save_paths(paths)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| {
+                let stamp = FileStamp::of(&p);
+                (p, stamp)
+            })
+            .collect();
+        // MAINSAVE mid-rewrite, locked or corrupt: keep the last known visibility
+        // instead of reporting every save as hidden
+        let visible = get_visible_saves_set(paths).unwrap_or_else(|e| {
+            tracing::warn!("Keeping previous visibility, MAINSAVE unreadable: {}", e);
+            previous_visible.clone()
+        });
+        (files, visible)
+    }
+
+    /// Rescan and report what changed since the last call
+    pub fn refresh(&mut self, paths: &SavePaths) -> Vec<LibraryEvent> {
+        let (files, visible) = Self::scan(paths, &self.visible);
+
+        let mut sorted: Vec<&PathBuf> = files.keys().collect();
+        sorted.sort();
+        let changed = |index: usize, path: &Path, with_detail: bool| {
+            save_meta(paths, index as u32, path, &visible).map(|meta| SaveChangedEvent {
+                meta,
+                detail: if with_detail {
+                    save_detail(paths, path)
+                } else {
+                    None
+                },
+            })
+        };
+
+        let mut events = Vec::new();
+        for path in self.files.keys().filter(|p| !files.contains_key(*p)) {
+            let archive_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| extract_archive_name(n).to_string())
+                .unwrap_or_default();
+            events.push(LibraryEvent::Removed(SaveRemovedEvent {
+                path: path.to_string_lossy().into_owned(),
+                archive_name,
+            }));
+        }
+        for (index, path) in sorted.into_iter().enumerate() {
+            let event = match self.files.get(path) {
+                None => changed(index, path, true).map(LibraryEvent::Added),
+                Some(stamp) if *stamp != files[path] => {
+                    changed(index, path, true).map(LibraryEvent::Modified)
+                }
+                Some(_) if is_visible(&self.visible, path) != is_visible(&visible, path) => {
+                    changed(index, path, false).map(LibraryEvent::VisibilityChanged)
+                }
+                Some(_) => None,
+            };
+            events.extend(event);
+        }
+
+        self.files = files;
+        self.visible = visible;
+        events
+    }
+}
@@ -0,0 +1,537 @@
+//! Library profiles module - Named save libraries, each with its own SaveGames root
+//! and MAINSAVE. Handles switching the active library and copying / moving saves
+//! between libraries while keeping both MAINSAVE `SingleplayerSaves` lists correct.
+
+use crate::common::{
+    add_save_to_mainsave, extract_archive_name, get_visible_saves_set, preview_mainsave,
+    remove_save_from_mainsave, run_blocking, MainsaveOp,
+};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::lock_save;
+use crate::path_resolver;
+use crate::save_diff::{DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Profiles file, stored in the app config dir
+const LIBRARY_PROFILES_FILE: &str = "library_profiles.json";
+/// Reserved name addressing the default root (override / LOCALAPPDATA / Proton)
+pub const DEFAULT_LIBRARY: &str = "default";
+/// Maximum profile name length
+const MAX_NAME_LEN: usize = 64;
+
+/// A named save library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryProfile {
+    pub name: String,
+    pub save_games_dir: PathBuf,
+}
+
+/// Persisted profile list plus the active profile (`None` = default root)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryProfiles {
+    pub active: Option<String>,
+    pub profiles: Vec<LibraryProfile>,
+}
+
+impl LibraryProfiles {
+    fn find(&self, name: &str) -> Option<&LibraryProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// One library as reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryProfileInfo {
+    pub name: String,
+    pub save_games_dir: Option<String>,
+    pub exists: bool,
+    pub active: bool,
+    pub is_default: bool,
+}
+
+/// How `transfer_save` treats the source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// Outcome of a copy / move between libraries
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferResult {
+    pub path: String,
+    pub visible: bool,
+}
+
+/// Whether `name` addresses the default root
+#[inline]
+pub fn is_default_library(name: &str) -> bool {
+    name.trim().eq_ignore_ascii_case(DEFAULT_LIBRARY)
+}
+
+fn profiles_path() -> AppResult<PathBuf> {
+    Ok(path_resolver::resolve_app_config_dir()?.join(LIBRARY_PROFILES_FILE))
+}
+
+/// Load profiles. Missing or corrupt files degrade to an empty list.
+pub fn load_library_profiles() -> LibraryProfiles {
+    let Ok(path) = profiles_path() else {
+        return LibraryProfiles::default();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return LibraryProfiles::default();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+/// Persist profiles
+pub fn write_library_profiles(profiles: &LibraryProfiles) -> AppResult<()> {
+    let path = profiles_path()?;
+    if let Some(dir) = path.parent() {
+        if !dir.exists() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+    }
+    let json = serde_json::to_string_pretty(profiles)
+        .map_err(|e| format!("Failed to serialize library profiles: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write library profiles: {}", e))?;
+    Ok(())
+}
+
+/// The active profile, if one is selected and still configured
+pub fn active_profile() -> Option<LibraryProfile> {
+    let profiles = load_library_profiles();
+    let active = profiles.active.as_deref()?;
+    profiles.find(active).cloned()
+}
+
+/// Look up a profile by name (case-insensitive)
+pub fn find_profile(name: &str) -> AppResult<LibraryProfile> {
+    load_library_profiles()
+        .find(name.trim())
+        .cloned()
+        .ok_or_else(|| AppError::Validation(format!("Library profile not found: {}", name)))
+}
+
+fn validate_profile_name(name: &str) -> AppResult<()> {
+    if name.is_empty() {
+        return Err(AppError::Validation(
+            "Library name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::Validation(format!(
+            "Library name must be at most {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if is_default_library(name) {
+        return Err(AppError::Validation(format!(
+            "'{}' is reserved for the default library",
+            DEFAULT_LIBRARY
+        )));
+    }
+    Ok(())
+}
+
+/// Copy or move one save into another library.
+///
+/// The save keeps its position relative to the SaveGames root (subfolder = hidden).
+/// A save that is visible in the source MAINSAVE is added to the target MAINSAVE;
+/// a move also removes it from the source MAINSAVE. Existing target files are never
+/// overwritten, and a failed MAINSAVE update puts the file back where it was.
+pub fn transfer_save(
+    source: &SavePaths,
+    target: &SavePaths,
+    file_path: &Path,
+    mode: TransferMode,
+) -> AppResult<TransferResult> {
+    let (relative, dest) = transfer_plan(source, target, file_path)?;
+    if let Some(parent) = dest.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create target directory: {}", e))?;
+        }
+    }
+    target.validate_save_games_path(&dest)?;
+
+    let _source_lock = lock_save(source, file_path)?;
+    let _target_lock = lock_save(target, &dest)?;
+    if dest.exists() {
+        return Err(format!("Target library already contains {}", relative.display()).into());
+    }
+
+    let archive_name = transfer_archive_name(file_path);
+    let visible =
+        !source.is_hidden(file_path) && get_visible_saves_set(source)?.contains(archive_name);
+    // Both MAINSAVEs must read before the file moves; a fresh library without
+    // one fails here rather than after the save has left the source
+    let target_listed = visible && get_visible_saves_set(target)?.contains(archive_name);
+
+    let placement = place_file(file_path, &dest, mode)?;
+    if visible {
+        if let Err(e) = update_mainsaves(source, target, archive_name, mode, target_listed) {
+            undo_placement(file_path, &dest, placement);
+            return Err(e);
+        }
+    }
+
+    tracing::info!(
+        "{:?} {} -> {} (visible: {})",
+        mode,
+        file_path.display(),
+        dest.display(),
+        visible
+    );
+
+    Ok(TransferResult {
+        path: dest.to_str().unwrap_or_default().to_string(),
+        visible,
+    })
+}
+
+/// How the save reached the target library
+#[derive(Debug, Clone, Copy)]
+enum Placement {
+    Copied,
+    Renamed,
+    /// Moved across filesystems: copied, then the source removed
+    CopiedAndRemoved,
+}
+
+/// Copy or move the file; a move that can't remove its source undoes the copy
+fn place_file(file_path: &Path, dest: &Path, mode: TransferMode) -> AppResult<Placement> {
+    let copy = || fs::copy(file_path, dest).map_err(|e| format!("Failed to copy save: {}", e));
+    match mode {
+        TransferMode::Copy => {
+            copy()?;
+            Ok(Placement::Copied)
+        }
+        // rename fails across filesystems (e.g. SD card library) — fall back to copy + delete
+        TransferMode::Move if fs::rename(file_path, dest).is_ok() => Ok(Placement::Renamed),
+        TransferMode::Move => {
+            copy()?;
+            if let Err(e) = fs::remove_file(file_path) {
+                let _ = fs::remove_file(dest);
+                return Err(format!("Failed to remove source save: {}", e).into());
+            }
+            Ok(Placement::CopiedAndRemoved)
+        }
+    }
+}
+
+/// Put the file system back the way it was before [`place_file`]
+fn undo_placement(file_path: &Path, dest: &Path, placement: Placement) {
+    let result = match placement {
+        Placement::Copied => fs::remove_file(dest),
+        Placement::Renamed => fs::rename(dest, file_path),
+        Placement::CopiedAndRemoved => {
+            fs::copy(dest, file_path).and_then(|_| fs::remove_file(dest))
+        }
+    };
+    if let Err(e) = result {
+        tracing::error!(
+            "Rollback of transfer {:?} -> {:?} failed: {}",
+            file_path,
+            dest,
+            e
+        );
+    }
+}
+
+/// List the save in the target MAINSAVE and, for a move, drop it from the source.
+/// A failed source update takes the target entry back out (unless it was already
+/// listed before the transfer).
+fn update_mainsaves(
+    source: &SavePaths,
+    target: &SavePaths,
+    archive_name: &str,
+    mode: TransferMode,
+    target_listed: bool,
+) -> AppResult<()> {
+    add_save_to_mainsave(target, archive_name)?;
+    if mode == TransferMode::Move {
+        if let Err(e) = remove_save_from_mainsave(source, archive_name) {
+            if !target_listed {
+                if let Err(undo) = remove_save_from_mainsave(target, archive_name) {
+                    tracing::error!(
+                        "Failed to take {} back out of target MAINSAVE: {}",
+                        archive_name,
+                        undo
+                    );
+                }
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Dry run of [`transfer_save`]
+pub fn preview_transfer_save(
+    source: &SavePaths,
+    target: &SavePaths,
+    file_path: &Path,
+    mode: TransferMode,
+) -> AppResult<DryRunReport> {
+    let (relative, dest) = transfer_plan(source, target, file_path)?;
+    if dest.exists() {
+        return Err(format!("Target library already contains {}", relative.display()).into());
+    }
+
+    let archive_name = transfer_archive_name(file_path);
+    let visible =
+        !source.is_hidden(file_path) && get_visible_saves_set(source)?.contains(archive_name);
+
+    let dest_str = dest.to_string_lossy().into_owned();
+    let mut report = DryRunReport::default();
+    match mode {
+        TransferMode::Copy => report.files.push(FileOp::Create { path: dest_str }),
+        TransferMode::Move => {
+            report.files.push(FileOp::Rename {
+                from: file_path.to_string_lossy().into_owned(),
+                to: dest_str,
+            });
+            if visible {
+                report.push_diff(
+                    source.mainsave_path().to_string_lossy(),
+                    preview_mainsave(source, &[MainsaveOp::Remove(archive_name.to_string())])?,
+                );
+            }
+        }
+    }
+    if visible {
+        report.push_diff(
+            target.mainsave_path().to_string_lossy(),
+            preview_mainsave(target, &[MainsaveOp::Add(archive_name.to_string())])?,
+        );
+    } else {
+        report
+            .notes
+            .push("The save is hidden and stays out of the target MAINSAVE".to_string());
+    }
+    Ok(report)
+}
+
+fn transfer_archive_name(file_path: &Path) -> &str {
+    extract_archive_name(
+        file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default(),
+    )
+}
+
+/// Check a transfer and work out where the save lands: (path inside the
+/// library, destination path)
+fn transfer_plan(
+    source: &SavePaths,
+    target: &SavePaths,
+    file_path: &Path,
+) -> AppResult<(PathBuf, PathBuf)> {
+    source.validate_save_games_path(file_path)?;
+
+    let filename = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file path")?;
+    if !filename.to_lowercase().ends_with(".sav") || filename.eq_ignore_ascii_case("MAINSAVE.sav") {
+        return Err("Only .sav save files can be transferred".to_string().into());
+    }
+    if !file_path.is_file() {
+        return Err(format!("File does not exist: {}", file_path.display()).into());
+    }
+
+    let source_root = source
+        .save_games_dir()
+        .canonicalize()
+        .map_err(|e| format!("Path resolution failed: {}", e))?;
+    let target_root = target
+        .save_games_dir()
+        .canonicalize()
+        .map_err(|e| format!("Target library not found: {}", e))?;
+    if source_root == target_root {
+        return Err("Source and target library are the same".to_string().into());
+    }
+
+    let relative = file_path
+        .strip_prefix(source.save_games_dir())
+        .map_err(|_| "File is not inside the source library".to_string())?;
+    let dest = target.save_games_dir().join(relative);
+    Ok((relative.to_path_buf(), dest))
+}
+
+/// List the default library and every configured profile.
+#[tauri::command]
+pub async fn list_library_profiles() -> AppResult<Vec<LibraryProfileInfo>> {
+    run_blocking(move || {
+        let profiles = load_library_profiles();
+        let active = profiles
+            .active
+            .as_deref()
+            .and_then(|name| profiles.find(name))
+            .map(|p| p.name.clone());
+
+        let default_dir = path_resolver::resolve_default_save_games_dir()
+            .ok()
+            .map(|(dir, _)| dir);
+        let mut result = Vec::with_capacity(profiles.profiles.len() + 1);
+        result.push(LibraryProfileInfo {
+            name: DEFAULT_LIBRARY.to_string(),
+            exists: default_dir.as_ref().is_some_and(|d| d.is_dir()),
+            save_games_dir: default_dir.map(|d| d.to_string_lossy().into_owned()),
+            active: active.is_none(),
+            is_default: true,
+        });
+
+        for profile in &profiles.profiles {
+            result.push(LibraryProfileInfo {
+                name: profile.name.clone(),
+                save_games_dir: Some(profile.save_games_dir.to_string_lossy().into_owned()),
+                exists: profile.save_games_dir.is_dir(),
+                active: active.as_deref() == Some(profile.name.as_str()),
+                is_default: false,
+            });
+        }
+
+        Ok(result)
+    })
+    .await
+}
+
+/// Add a named library. The SaveGames directory must already exist.
+#[tauri::command]
+pub async fn add_library_profile(name: String, save_games_dir: String) -> AppResult<()> {
+    run_blocking(move || {
+        let name = name.trim().to_string();
+        validate_profile_name(&name)?;
+
+        let dir = PathBuf::from(save_games_dir.trim());
+        if !dir.is_dir() {
+            return Err(AppError::Validation(format!(
+                "SaveGames directory does not exist: {}",
+                dir.display()
+            )));
+        }
+
+        let mut profiles = load_library_profiles();
+        if profiles.find(&name).is_some() {
+            return Err(AppError::Validation(format!(
+                "Library profile already exists: {}",
+                name
+            )));
+        }
+
+        tracing::info!("Adding library profile '{}': {}", name, dir.display());
+        profiles.profiles.push(LibraryProfile {
+            name,
+            save_games_dir: dir,
+        });
+        write_library_profiles(&profiles)
+    })
+    .await
+}
+
+/// Remove a named library (its files are left untouched). Removing the active
+/// library switches back to the default root.
+#[tauri::command]
+pub async fn remove_library_profile(
+    state: tauri::State<'_, SavePathsState>,
+    name: String,
+) -> AppResult<()> {
+    let resolved = run_blocking(move || {
+        let mut profiles = load_library_profiles();
+        let profile = profiles
+            .find(&name)
+            .cloned()
+            .ok_or_else(|| AppError::Validation(format!("Library profile not found: {}", name)))?;
+
+        let was_active = profiles
+            .active
+            .as_deref()
+            .is_some_and(|a| a.eq_ignore_ascii_case(&profile.name));
+        profiles.profiles.retain(|p| p.name != profile.name);
+        if was_active {
+            profiles.active = None;
+        }
+        write_library_profiles(&profiles)?;
+        tracing::info!("Removed library profile '{}'", profile.name);
+
+        Ok(was_active.then(|| SavePaths::resolve().map(Arc::new)))
+    })
+    .await?;
+
+    if let Some(resolved) = resolved {
+        // The default root may legitimately be unresolvable; keep the error in state.
+        let _ = state.replace(resolved);
+    }
+    Ok(())
+}
+
+/// Switch the active library (`default` or `None` selects the default root).
+/// Takes effect immediately; returns the newly active SaveGames directory.
+#[tauri::command]
+pub async fn switch_library_profile(
+    state: tauri::State<'_, SavePathsState>,
+    name: Option<String>,
+) -> AppResult<String> {
+    let (active, resolved) = run_blocking(move || {
+        let mut profiles = load_library_profiles();
+        profiles.active = match name.as_deref().map(str::trim) {
+            Some(n) if !n.is_empty() && !is_default_library(n) => {
+                Some(profiles.find(n).map(|p| p.name.clone()).ok_or_else(|| {
+                    AppError::Validation(format!("Library profile not found: {}", n))
+                })?)
+            }
+            _ => None,
+        };
+        write_library_profiles(&profiles)?;
+        tracing::info!("Active library: {:?}", profiles.active);
+
+        Ok((profiles.active, SavePaths::resolve().map(Arc::new)))
+    })
+    .await?;
+
+    let paths = state.replace(resolved)?;
+    if paths.library() != active.as_deref() {
+        tracing::warn!(
+            "{} is set; it takes precedence over the selected library",
+            path_resolver::SAVE_GAMES_DIR_ENV
+        );
+    }
+    Ok(paths.save_games_dir().to_string_lossy().into_owned())
+}
+
+/// Copy or move a save between libraries (`None` = active library).
+#[tauri::command]
+pub async fn transfer_save_between_libraries(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    source_library: Option<String>,
+    target_library: String,
+    mode: TransferMode,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<TransferResult>> {
+    let source = state.for_library(source_library.as_deref())?;
+    let target = state.for_library(Some(&target_library))?;
+    run_blocking(move || {
+        let file_path = Path::new(&file_path);
+        if dry_run.unwrap_or(false) {
+            return preview_transfer_save(&source, &target, file_path, mode).map(Outcome::DryRun);
+        }
+        transfer_save(&source, &target, file_path, mode).map(Outcome::Applied)
+    })
+    .await
+}
@@ -0,0 +1,206 @@
+//! Library stream module - One-subscription library loading over a Tauri channel
+//! `stream_library` sends every save's metadata first, then parsed details as the
+//! rayon workers (or the metadata index) produce them, with progress counts in
+//! between. Replaces the `load_save_metadata` + `load_save_details_batch` round
+//! trips; a stream can be cancelled by id or by dropping the channel.
+
+use crate::common::{run_blocking, visible_saves_for_listing};
+use crate::error::{AppError, AppResult};
+use crate::get_file_path;
+use crate::library_events::emit_mainsave_unreadable;
+use crate::metadata_index::{self, DetailDepth};
+use crate::save_loader::{convert_singleplayer_archives, save_meta};
+use crate::save_paths::SavePathsState;
+use crate::save_utils::{SaveFileDetail, SaveFileMeta};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::ipc::Channel;
+use tauri::AppHandle;
+
+/// Metadata is sent in chunks of this many saves
+const META_CHUNK: usize = 200;
+
+/// Messages sent over the `stream_library` channel, in this order:
+/// `started`, `meta`*, (`detail` | `progress`)*, `finished`
+#[derive(Clone, Serialize)]
+#[serde(
+    tag = "event",
+    content = "data",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum LibraryStreamEvent {
+    /// First message; `stream_id` is what `cancel_library_stream` takes
+    Started {
+        stream_id: String,
+        total: u32,
+    },
+    Meta {
+        items: Vec<SaveFileMeta>,
+    },
+    /// `id` matches the `id` of the save's metadata
+    Detail {
+        id: u32,
+        detail: SaveFileDetail,
+    },
+    /// Files whose details are settled (parsed or failed) out of `total`
+    Progress {
+        done: u32,
+        total: u32,
+    },
+    Finished {
+        parsed: u32,
+        failed: u32,
+        cancelled: bool,
+    },
+}
+
+/// Tauri managed state: cancel flags of the running streams
+#[derive(Default)]
+pub struct LibraryStreams(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl LibraryStreams {
+    fn register(&self, stream_id: &str) -> AppResult<Arc<AtomicBool>> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.0
+            .lock()
+            .map_err(|e| AppError::General(format!("Stream registry lock poisoned: {}", e)))?
+            .insert(stream_id.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    fn unregister(&self, stream_id: &str) {
+        if let Ok(mut streams) = self.0.lock() {
+            streams.remove(stream_id);
+        }
+    }
+
+    pub fn cancel(&self, stream_id: &str) -> AppResult<bool> {
+        let streams = self
+            .0
+            .lock()
+            .map_err(|e| AppError::General(format!("Stream registry lock poisoned: {}", e)))?;
+        Ok(streams
+            .get(stream_id)
+            .map(|flag| flag.store(true, Ordering::Relaxed))
+            .is_some())
+    }
+}
+
+/// Stream the library's metadata, then details, over `on_event`. Resolves once
+/// the stream has finished or was cancelled. An unreadable MAINSAVE is reported
+/// with a `mainsave-unreadable` event and every save streams as hidden.
+#[tauri::command]
+pub async fn stream_library(
+    app: AppHandle,
+    state: tauri::State<'_, SavePathsState>,
+    streams: tauri::State<'_, LibraryStreams>,
+    library: Option<String>,
+    on_event: Channel<LibraryStreamEvent>,
+) -> AppResult<()> {
+    let start_time = Instant::now();
+    let save_paths = state.for_library(library.as_deref())?;
+    let stream_id = uuid::Uuid::new_v4().to_string();
+    let cancel = streams.register(&stream_id)?;
+
+    let id = stream_id.clone();
+    let result = run_blocking(move || {
+        // Phase 0 of `load_save_metadata`: SINGLEPLAYER_ archives become MULTIPLAYER_
+        let conversions = convert_singleplayer_archives(&save_paths);
+        if !conversions.is_empty() {
+            tracing::info!("Converted {} singleplayer archives", conversions.len());
+        }
+
+        // A failed send means the frontend dropped the channel: stop work too
+        let send = |event: LibraryStreamEvent| {
+            if on_event.send(event).is_err() {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+
+        let (paths_result, (visible_saves, mainsave_error)) = rayon::join(
+            || get_file_path::list_save_paths(&save_paths),
+            || visible_saves_for_listing(&save_paths),
+        );
+        emit_mainsave_unreadable(&app, mainsave_error);
+        let mut metas: Vec<(PathBuf, SaveFileMeta)> = paths_result?
+            .into_iter()
+            .filter_map(|path| {
+                let meta = save_meta(&save_paths, 0, &path, &visible_saves)?;
+                Some((path, meta))
+            })
+            .collect();
+        for (i, (_, meta)) in metas.iter_mut().enumerate() {
+            meta.id = i as u32;
+        }
+        let total = metas.len() as u32;
+
+        send(LibraryStreamEvent::Started {
+            stream_id: id,
+            total,
+        });
+        for chunk in metas.chunks(META_CHUNK) {
+            send(LibraryStreamEvent::Meta {
+                items: chunk.iter().map(|(_, meta)| meta.clone()).collect(),
+            });
+        }
+
+        // Visible saves first: they are the cards the user sees
+        let mut order: Vec<usize> = (0..metas.len()).collect();
+        order.sort_by_key(|&i| metas[i].1.is_visible != Some(true));
+        let files: Vec<PathBuf> = order.iter().map(|&i| metas[i].0.clone()).collect();
+
+        let done = AtomicU32::new(0);
+        let parsed = AtomicU32::new(0);
+        metadata_index::stream_details(
+            &save_paths,
+            &files,
+            DetailDepth::Basic,
+            Some(&cancel),
+            |i, details| {
+                if let Some(details) = details {
+                    parsed.fetch_add(1, Ordering::Relaxed);
+                    send(LibraryStreamEvent::Detail {
+                        id: order[i] as u32,
+                        detail: details.into(),
+                    });
+                }
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                send(LibraryStreamEvent::Progress { done, total });
+            },
+        );
+
+        let cancelled = cancel.load(Ordering::Relaxed);
+        let (done, parsed) = (done.into_inner(), parsed.into_inner());
+        send(LibraryStreamEvent::Finished {
+            parsed,
+            failed: done - parsed,
+            cancelled,
+        });
+        tracing::info!(
+            "stream_library: {} saves, {} parsed{}, took {:.2}ms",
+            total,
+            parsed,
+            if cancelled { " (cancelled)" } else { "" },
+            start_time.elapsed().as_secs_f64() * 1000.0
+        );
+        Ok(())
+    })
+    .await;
+
+    streams.unregister(&stream_id);
+    result
+}
+
+/// Ask a running `stream_library` to stop. Returns whether the stream was running.
+#[tauri::command]
+pub fn cancel_library_stream(
+    streams: tauri::State<'_, LibraryStreams>,
+    stream_id: String,
+) -> AppResult<bool> {
+    streams.cancel(&stream_id)
+}
@@ -0,0 +1,474 @@
+//! Mainsave manager module - Inspect, reorder and repair MAINSAVE's save list
+//! `SingleplayerSaves` decides which saves the game offers and in what order.
+//! Entries are checked against the files in SaveGames, and the list can be
+//! reordered, cleared of orphans or filled with every on-disk save. Each edit
+//! holds `mainsave_lock` for the whole read-modify-write and goes through
+//! `write_mainsave`. A MAINSAVE that is missing or won't parse is rebuilt from a
+//! known-good template, after the broken file is moved aside.
+
+use crate::common::{
+    extract_archive_name, listed_saves, mainsave_lock, read_mainsave, run_blocking,
+    set_listed_saves, write_mainsave,
+};
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{LEVELS_COMPLETED, PLAYER_DATA};
+use crate::get_file_path;
+use crate::save_backup::{self, MAINSAVE_ARCHIVE};
+use crate::save_diff::{diff_roots, DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_trash;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use uesave::{PropertyKey, Save};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryStatus {
+    /// `<name>.sav` is in SaveGames
+    Present,
+    /// Only in a hidden subfolder, where the game doesn't look
+    Hidden,
+    /// Only the `.sav.trash` is left
+    Trashed,
+    /// No file at all
+    Missing,
+    /// Listed earlier already
+    Duplicate,
+}
+
+/// One `SingleplayerSaves` entry
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MainsaveEntry {
+    pub position: usize,
+    pub archive_name: String,
+    pub status: EntryStatus,
+    /// The file the entry resolves to, if any
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MainsaveStatus {
+    pub entries: Vec<MainsaveEntry>,
+    /// Saves in SaveGames the list leaves out
+    pub unlisted: Vec<String>,
+}
+
+/// Order `add_all` lists the unlisted saves in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    NameAsc,
+    NameDesc,
+}
+
+/// What `repair` did (or would do)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// Where the broken MAINSAVE was moved; `None` when there was none
+    pub quarantined: Option<String>,
+    /// File the header and schemas were taken from
+    pub template: String,
+    /// The rebuilt `SingleplayerSaves`
+    pub listed: Vec<String>,
+}
+
+/// Save files by archive name
+struct DiskSaves {
+    top_level: HashMap<String, PathBuf>,
+    hidden: HashMap<String, PathBuf>,
+    trashed: HashMap<String, PathBuf>,
+}
+
+fn archive_of(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let file_name = file_name.strip_suffix(".trash").unwrap_or(file_name);
+    Some(extract_archive_name(file_name).to_string())
+}
+
+fn scan_disk(paths: &SavePaths) -> AppResult<DiskSaves> {
+    let mut disk = DiskSaves {
+        top_level: HashMap::new(),
+        hidden: HashMap::new(),
+        trashed: HashMap::new(),
+    };
+    for path in get_file_path::list_save_paths(paths)? {
+        let Some(archive_name) = archive_of(&path) else {
+            continue;
+        };
+        if paths.is_hidden(&path) {
+            disk.hidden.insert(archive_name, path);
+        } else {
+            disk.top_level.insert(archive_name, path);
+        }
+    }
+    for path in save_trash::trash_files(paths) {
+        if let Some(archive_name) = archive_of(&path) {
+            disk.trashed.insert(archive_name, path);
+        }
+    }
+    Ok(disk)
+}
+
+fn entries_of(listed: &[String], disk: &DiskSaves) -> Vec<MainsaveEntry> {
+    let mut seen = HashSet::new();
+    listed
+        .iter()
+        .enumerate()
+        .map(|(position, archive_name)| {
+            let (status, path) = if !seen.insert(archive_name.as_str()) {
+                (EntryStatus::Duplicate, disk.top_level.get(archive_name))
+            } else if let Some(path) = disk.top_level.get(archive_name) {
+                (EntryStatus::Present, Some(path))
+            } else if let Some(path) = disk.hidden.get(archive_name) {
+                (EntryStatus::Hidden, Some(path))
+            } else if let Some(path) = disk.trashed.get(archive_name) {
+                (EntryStatus::Trashed, Some(path))
+            } else {
+                (EntryStatus::Missing, None)
+            };
+            MainsaveEntry {
+                position,
+                archive_name: archive_name.clone(),
+                status,
+                path: path.map(|p| p.to_string_lossy().into_owned()),
+            }
+        })
+        .collect()
+}
+
+/// Every entry with its status, plus the on-disk saves the list leaves out
+pub fn mainsave_status(paths: &SavePaths) -> AppResult<MainsaveStatus> {
+    let disk = scan_disk(paths)?;
+    let listed = {
+        let _lock = mainsave_lock(paths)?;
+        listed_saves(&read_mainsave(paths)?)
+    };
+    let listed_set: HashSet<&String> = listed.iter().collect();
+    let mut unlisted: Vec<String> = disk
+        .top_level
+        .keys()
+        .filter(|name| !listed_set.contains(name))
+        .cloned()
+        .collect();
+    unlisted.sort();
+    Ok(MainsaveStatus {
+        entries: entries_of(&listed, &disk),
+        unlisted,
+    })
+}
+
+/// Read-modify-write of the list under the MAINSAVE lock. `edit` gets the current
+/// list and returns the new one plus the command's result; a dry run only diffs.
+fn edit_listed<T>(
+    paths: &SavePaths,
+    dry_run: bool,
+    edit: impl FnOnce(Vec<String>, &DiskSaves) -> AppResult<(Vec<String>, T)>,
+) -> AppResult<Outcome<T>> {
+    let disk = scan_disk(paths)?;
+    let _lock = mainsave_lock(paths)?;
+    let mut mainsave = read_mainsave(paths)?;
+    let (listed, value) = edit(listed_saves(&mainsave), &disk)?;
+
+    if dry_run {
+        let before = mainsave.clone();
+        set_listed_saves(&mut mainsave, listed);
+        let mut report = DryRunReport::default();
+        report.push_diff(
+            paths.mainsave_path().to_string_lossy(),
+            diff_roots(&before, &mainsave),
+        );
+        return Ok(Outcome::DryRun(report));
+    }
+    if set_listed_saves(&mut mainsave, listed) {
+        write_mainsave(paths, &mainsave)?;
+    }
+    Ok(Outcome::Applied(value))
+}
+
+/// Move the named entries to the top, in the given order; the others follow in
+/// their current order. Returns the new list.
+pub fn reorder(
+    paths: &SavePaths,
+    order: &[String],
+    dry_run: bool,
+) -> AppResult<Outcome<Vec<String>>> {
+    edit_listed(paths, dry_run, |listed, _| {
+        let mut named = HashSet::new();
+        for archive_name in order {
+            if !listed.contains(archive_name) {
+                return Err(AppError::Validation(format!(
+                    "'{}' is not listed in MAINSAVE",
+                    archive_name
+                )));
+            }
+            if !named.insert(archive_name) {
+                return Err(AppError::Validation(format!(
+                    "'{}' appears twice in the new order",
+                    archive_name
+                )));
+            }
+        }
+        let mut reordered = order.to_vec();
+        reordered.extend(listed.into_iter().filter(|name| !named.contains(name)));
+        Ok((reordered.clone(), reordered))
+    })
+}
+
+/// Drop every entry that isn't [`EntryStatus::Present`]; returns the dropped ones
+pub fn drop_orphans(paths: &SavePaths, dry_run: bool) -> AppResult<Outcome<Vec<MainsaveEntry>>> {
+    edit_listed(paths, dry_run, |listed, disk| {
+        let (kept, dropped): (Vec<MainsaveEntry>, Vec<MainsaveEntry>) = entries_of(&listed, disk)
+            .into_iter()
+            .partition(|e| e.status == EntryStatus::Present);
+        tracing::info!("Dropping {} orphan MAINSAVE entries", dropped.len());
+        Ok((kept.into_iter().map(|e| e.archive_name).collect(), dropped))
+    })
+}
+
+/// Archive names of `saves` in `order`
+fn sorted_names<'a>(
+    saves: impl Iterator<Item = (&'a String, &'a PathBuf)>,
+    order: AddOrder,
+) -> Vec<String> {
+    let mut saves: Vec<(&String, Option<SystemTime>)> = saves
+        .map(|(name, path)| (name, path.metadata().and_then(|m| m.modified()).ok()))
+        .collect();
+    match order {
+        AddOrder::NewestFirst => saves.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0))),
+        AddOrder::OldestFirst => saves.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0))),
+        AddOrder::NameAsc => saves.sort_by(|a, b| a.0.cmp(b.0)),
+        AddOrder::NameDesc => saves.sort_by(|a, b| b.0.cmp(a.0)),
+    }
+    saves.into_iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Add every top-level save MAINSAVE leaves out to SingleplayerSaves, in `order`,
+/// before the current entries (after them with `append`). Returns the added names.
+pub fn add_all(
+    paths: &SavePaths,
+    order: AddOrder,
+    append: bool,
+    dry_run: bool,
+) -> AppResult<Outcome<Vec<String>>> {
+    edit_listed(paths, dry_run, |listed, disk| {
+        let listed_set: HashSet<&String> = listed.iter().collect();
+        let added = sorted_names(
+            disk.top_level
+                .iter()
+                .filter(|(name, _)| !listed_set.contains(name)),
+            order,
+        );
+        let new_list = if append {
+            listed.iter().chain(&added).cloned().collect()
+        } else {
+            added.iter().chain(&listed).cloned().collect()
+        };
+        Ok((new_list, added))
+    })
+}
+
+fn parse_save(path: &Path) -> AppResult<Save> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    Save::read(&mut BufReader::new(file))
+        .map_err(|e| AppError::Parse(format!("Failed to parse {:?}: {:?}", path, e)))
+}
+
+/// Reject game archives passed off as a MAINSAVE template
+fn check_template(save: &Save, path: &Path) -> AppResult<()> {
+    let properties = &save.root.properties.0;
+    if [PLAYER_DATA, LEVELS_COMPLETED]
+        .iter()
+        .any(|name| properties.contains_key(&PropertyKey(0, name.to_string())))
+    {
+        return Err(AppError::Validation(format!(
+            "{:?} is a game save, not a MAINSAVE",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// The MAINSAVE a rebuild starts from: `template` when given, otherwise the
+/// newest backup of MAINSAVE that still parses
+fn load_template(paths: &SavePaths, template: Option<&Path>) -> AppResult<(Save, PathBuf)> {
+    if let Some(path) = template {
+        let save = parse_save(path)?;
+        check_template(&save, path)?;
+        return Ok((save, path.to_path_buf()));
+    }
+    for entry in save_backup::list_backups(paths, MAINSAVE_ARCHIVE)? {
+        let path = save_backup::backup_path(paths, &entry)?;
+        match parse_save(&path) {
+            Ok(save) => return Ok((save, path)),
+            Err(e) => tracing::warn!("Skipping MAINSAVE backup {}: {}", entry.id, e),
+        }
+    }
+    Err(AppError::Validation(
+        "No readable MAINSAVE backup to rebuild from; pass a template MAINSAVE.sav or \
+         launch the game once to let it create one"
+            .to_string(),
+    ))
+}
+
+/// Rebuild a missing or unreadable MAINSAVE: the broken file is moved aside as
+/// `MAINSAVE-<timestamp>.sav.corrupt`, and the new one keeps the template's header,
+/// schemas and other properties with `SingleplayerSaves` listing every top-level
+/// save, newest first. Refuses to touch a MAINSAVE that reads fine.
+pub fn repair(
+    paths: &SavePaths,
+    template: Option<&Path>,
+    dry_run: bool,
+) -> AppResult<Outcome<RepairReport>> {
+    let disk = scan_disk(paths)?;
+    let listed = sorted_names(disk.top_level.iter(), AddOrder::NewestFirst);
+    let _lock = mainsave_lock(paths)?;
+
+    match read_mainsave(paths) {
+        Ok(_) => {
+            return Err(AppError::Validation(
+                "MAINSAVE.sav reads fine; nothing to repair".to_string(),
+            ))
+        }
+        Err(AppError::MainsaveCorrupt(reason)) => {
+            tracing::warn!("Repairing MAINSAVE: {}", reason)
+        }
+        Err(e) => return Err(e),
+    }
+    let (mut mainsave, template_path) = load_template(paths, template)?;
+    let mainsave_path = paths.mainsave_path();
+    let quarantine = mainsave_path.exists().then(|| {
+        paths.save_games_dir().join(format!(
+            "MAINSAVE-{}.sav.corrupt",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+    let result = RepairReport {
+        quarantined: quarantine
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned()),
+        template: template_path.to_string_lossy().into_owned(),
+        listed: listed.clone(),
+    };
+
+    if dry_run {
+        let before = parse_save(&template_path)?;
+        set_listed_saves(&mut mainsave, listed);
+        let mut report = DryRunReport::default();
+        if let Some(quarantine) = &result.quarantined {
+            report.files.push(FileOp::Rename {
+                from: mainsave_path.to_string_lossy().into_owned(),
+                to: quarantine.clone(),
+            });
+        }
+        report.files.push(FileOp::Create {
+            path: mainsave_path.to_string_lossy().into_owned(),
+        });
+        report.push_diff(
+            mainsave_path.to_string_lossy(),
+            diff_roots(&before, &mainsave),
+        );
+        report
+            .notes
+            .push(format!("Header and schemas from {}", result.template));
+        return Ok(Outcome::DryRun(report));
+    }
+
+    set_listed_saves(&mut mainsave, listed);
+    if let Some(quarantine) = &quarantine {
+        fs::rename(&mainsave_path, quarantine)
+            .map_err(|e| format!("Failed to move broken MAINSAVE aside: {}", e))?;
+    }
+    if let Err(e) = write_mainsave(paths, &mainsave) {
+        // Put the broken file back rather than leave no MAINSAVE at all
+        if let Some(quarantine) = &quarantine {
+            let _ = fs::rename(quarantine, &mainsave_path);
+        }
+        return Err(e);
+    }
+    tracing::info!(
+        "Rebuilt MAINSAVE from {:?} with {} saves",
+        template_path,
+        result.listed.len()
+    );
+    Ok(Outcome::Applied(result))
+}
+
+/// MAINSAVE entries with their file status, and the saves it doesn't list.
+#[tauri::command]
+pub async fn get_mainsave_status(
+    state: tauri::State<'_, SavePathsState>,
+) -> AppResult<MainsaveStatus> {
+    let paths = state.current()?;
+    run_blocking(move || mainsave_status(&paths)).await
+}
+
+/// Put the named saves first, in this order.
+#[tauri::command]
+pub async fn reorder_mainsave(
+    state: tauri::State<'_, SavePathsState>,
+    order: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<Vec<String>>> {
+    let paths = state.current()?;
+    run_blocking(move || reorder(&paths, &order, dry_run.unwrap_or(false))).await
+}
+
+/// Remove entries whose save isn't in SaveGames (missing, trashed, hidden, duplicate).
+#[tauri::command]
+pub async fn drop_mainsave_orphans(
+    state: tauri::State<'_, SavePathsState>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<Vec<MainsaveEntry>>> {
+    let paths = state.current()?;
+    run_blocking(move || drop_orphans(&paths, dry_run.unwrap_or(false))).await
+}
+
+/// Add every save MAINSAVE leaves out to SingleplayerSaves.
+#[tauri::command]
+pub async fn add_all_saves_to_mainsave(
+    state: tauri::State<'_, SavePathsState>,
+    order: Option<AddOrder>,
+    append: Option<bool>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<Vec<String>>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        add_all(
+            &paths,
+            order.unwrap_or_default(),
+            append.unwrap_or(false),
+            dry_run.unwrap_or(false),
+        )
+    })
+    .await
+}
+
+/// Rebuild a missing or unreadable MAINSAVE from a template (the newest readable
+/// MAINSAVE backup by default), listing every save in SaveGames.
+#[tauri::command]
+pub async fn repair_mainsave(
+    state: tauri::State<'_, SavePathsState>,
+    template: Option<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<RepairReport>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        repair(
+            &paths,
+            template.as_deref().map(Path::new),
+            dry_run.unwrap_or(false),
+        )
+    })
+    .await
+}
@@ -0,0 +1,432 @@
+//! Metadata index module - Persistent SQLite cache of parsed save details
+//! Rows are keyed by absolute path and only trusted while the file's mtime and
+//! size still match, so a library opens without re-parsing unchanged `.sav`s.
+//! Rows from the partial scan hold only the card fields; extended details are
+//! filled in the first time a caller asks for them.
+//! The index is a cache: when it can't be opened, callers just parse.
+
+use crate::cli_handlers;
+use crate::common::run_blocking;
+use crate::error::AppResult;
+use crate::etb_save::{EtbSave, PlayerEntry};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_scan::{self, ScanSummary};
+use crate::save_utils::SaveFileDetail;
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Index database, next to `player_id_map.json`
+const INDEX_FILE: &str = "metadata_index.sqlite3";
+/// Bump when the table layout or the extracted fields change; older indexes are rebuilt
+const INDEX_VERSION: i32 = 3;
+
+/// How much a caller needs: `Basic` is served by the partial scan, `Full`
+/// (extended details) needs the whole save parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailDepth {
+    Basic,
+    Full,
+}
+
+/// Everything the index caches for one save
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedDetails {
+    pub path: String,
+    pub current_level: String,
+    pub actual_difficulty: String,
+    /// Present once the save was fully parsed
+    #[serde(flatten)]
+    pub extended: Option<ExtendedDetails>,
+}
+
+/// Details that need the full parse
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedDetails {
+    /// PlayerData entries (the game's bad-id placeholder excluded)
+    pub players: Vec<IndexedPlayer>,
+    pub levels_completed: u32,
+    pub levels_total: u32,
+}
+
+/// One PlayerData entry as the index keeps it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedPlayer {
+    /// PlayerData key
+    pub key: String,
+    /// Filled inventory slots
+    pub items: Vec<String>,
+}
+
+impl From<PlayerEntry> for IndexedPlayer {
+    fn from(player: PlayerEntry) -> Self {
+        Self {
+            items: player
+                .inventory
+                .into_iter()
+                .filter(|item| item != "None" && !item.is_empty())
+                .collect(),
+            key: player.key,
+        }
+    }
+}
+
+impl IndexedDetails {
+    fn from_save(path: &Path, save: &EtbSave) -> Self {
+        let levels = save.levels_completed();
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            current_level: cli_handlers::extract_current_level(save),
+            actual_difficulty: cli_handlers::extract_difficulty_label(save).into_owned(),
+            extended: Some(ExtendedDetails {
+                players: save.players().into_iter().map(Into::into).collect(),
+                levels_completed: levels.iter().filter(|l| l.has_completed).count() as u32,
+                levels_total: levels.len() as u32,
+            }),
+        }
+    }
+
+    fn from_scan(path: &Path, scan: &ScanSummary) -> Self {
+        Self {
+            path: path.to_string_lossy().into_owned(),
+            current_level: scan.current_level(),
+            actual_difficulty: scan.difficulty().label().to_string(),
+            extended: None,
+        }
+    }
+
+    /// Read `path` as deep as `depth` asks
+    fn load(path: &Path, depth: DetailDepth) -> Option<Self> {
+        match depth {
+            DetailDepth::Basic => save_scan::scan_or_parse(path)
+                .ok()
+                .map(|scan| Self::from_scan(path, &scan)),
+            DetailDepth::Full => cli_handlers::parse_sav_file(path)
+                .ok()
+                .map(|save| Self::from_save(path, &save)),
+        }
+    }
+}
+
+impl From<IndexedDetails> for SaveFileDetail {
+    fn from(details: IndexedDetails) -> Self {
+        SaveFileDetail {
+            path: details.path,
+            current_level: details.current_level,
+            actual_difficulty: details.actual_difficulty,
+        }
+    }
+}
+
+/// Modification time (ns since the epoch) and size the cached row was built from
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    mtime: i64,
+    size: i64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = path.metadata().ok()?;
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as i64;
+        Some(Self {
+            mtime,
+            size: metadata.len() as i64,
+        })
+    }
+}
+
+pub struct MetadataIndex {
+    conn: Connection,
+}
+
+impl MetadataIndex {
+    pub fn open(paths: &SavePaths) -> AppResult<Self> {
+        let db_path = paths.app_config_dir().join(INDEX_FILE);
+        if let Some(dir) = db_path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let conn = Connection::open(&db_path)
+            .map_err(|e| format!("Failed to open metadata index: {}", e))?;
+        // Commands and the watcher may open the index concurrently
+        conn.busy_timeout(Duration::from_secs(5))
+            .map_err(|e| format!("Failed to configure metadata index: {}", e))?;
+        let index = Self { conn };
+        index.migrate()?;
+        Ok(index)
+    }
+
+    fn migrate(&self) -> AppResult<()> {
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read metadata index version: {}", e))?;
+        if version == INDEX_VERSION {
+            return Ok(());
+        }
+        tracing::info!(
+            "Rebuilding metadata index (version {} -> {})",
+            version,
+            INDEX_VERSION
+        );
+        self.conn
+            .execute_batch(&format!(
+                "DROP TABLE IF EXISTS save_index;
+                 CREATE TABLE save_index (
+                     path TEXT PRIMARY KEY,
+                     mtime INTEGER NOT NULL,
+                     size INTEGER NOT NULL,
+                     current_level TEXT NOT NULL,
+                     actual_difficulty TEXT NOT NULL,
+                     players TEXT,
+                     levels_completed INTEGER,
+                     levels_total INTEGER
+                 );
+                 PRAGMA user_version = {};",
+                INDEX_VERSION
+            ))
+            .map_err(|e| format!("Failed to create metadata index: {}", e))?;
+        Ok(())
+    }
+
+    /// Cached details for `path`, if the row matches `stamp` and is deep enough
+    fn lookup(
+        &self,
+        path: &str,
+        stamp: FileStamp,
+        depth: DetailDepth,
+    ) -> AppResult<Option<IndexedDetails>> {
+        let row = self
+            .conn
+            .prepare_cached(
+                "SELECT current_level, actual_difficulty, players, levels_completed, levels_total
+                 FROM save_index WHERE path = ?1 AND mtime = ?2 AND size = ?3",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_row(params![path, stamp.mtime, stamp.size], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<u32>>(3)?,
+                        row.get::<_, Option<u32>>(4)?,
+                    ))
+                })
+                .optional()
+            })
+            .map_err(|e| format!("Failed to query metadata index: {}", e))?;
+
+        let Some((current_level, actual_difficulty, players, completed, total)) = row else {
+            return Ok(None);
+        };
+        let extended = match (players, completed, total) {
+            (Some(players), Some(levels_completed), Some(levels_total)) => Some(ExtendedDetails {
+                players: serde_json::from_str(&players).unwrap_or_default(),
+                levels_completed,
+                levels_total,
+            }),
+            _ => None,
+        };
+        if depth == DetailDepth::Full && extended.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(IndexedDetails {
+            path: path.to_string(),
+            current_level,
+            actual_difficulty,
+            extended,
+        }))
+    }
+
+    fn store(&mut self, rows: &[(FileStamp, IndexedDetails)]) -> AppResult<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| format!("Failed to start index transaction: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO save_index
+                     (path, mtime, size, current_level, actual_difficulty, players,
+                      levels_completed, levels_total)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )
+                .map_err(|e| format!("Failed to prepare index update: {}", e))?;
+            for (stamp, details) in rows {
+                let extended = details.extended.as_ref();
+                let players =
+                    extended.map(|e| serde_json::to_string(&e.players).unwrap_or_default());
+                stmt.execute(params![
+                    details.path,
+                    stamp.mtime,
+                    stamp.size,
+                    details.current_level,
+                    details.actual_difficulty,
+                    players,
+                    extended.map(|e| e.levels_completed),
+                    extended.map(|e| e.levels_total),
+                ])
+                .map_err(|e| format!("Failed to update metadata index: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit metadata index: {}", e))?;
+        Ok(())
+    }
+
+    /// Drop rows under this library's SaveGames that aren't in `live`. Returns the count.
+    pub fn prune(&self, paths: &SavePaths, live: &[PathBuf]) -> AppResult<usize> {
+        let root = paths.save_games_dir().to_string_lossy().into_owned();
+        let live: HashSet<String> = live
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        let stale: Vec<String> = self
+            .conn
+            .prepare("SELECT path FROM save_index")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| format!("Failed to query metadata index: {}", e))?
+            .into_iter()
+            .filter(|p| Path::new(p).starts_with(&root) && !live.contains(p))
+            .collect();
+        for path in &stale {
+            self.conn
+                .execute("DELETE FROM save_index WHERE path = ?1", params![path])
+                .map_err(|e| format!("Failed to prune metadata index: {}", e))?;
+        }
+        Ok(stale.len())
+    }
+
+    pub fn clear(&self) -> AppResult<usize> {
+        self.conn
+            .execute("DELETE FROM save_index", [])
+            .map_err(|e| format!("Failed to clear metadata index: {}", e).into())
+    }
+}
+
+/// Details for each of `files` (same order), reading only files the index has no
+/// current row of that depth for; `None` for files that can't be read.
+pub fn details_for(
+    paths: &SavePaths,
+    files: &[PathBuf],
+    depth: DetailDepth,
+) -> Vec<Option<IndexedDetails>> {
+    let results = Mutex::new(vec![None; files.len()]);
+    stream_details(paths, files, depth, None, |i, details| {
+        if let Ok(mut results) = results.lock() {
+            results[i] = details;
+        }
+    });
+    results.into_inner().unwrap_or_default()
+}
+
+/// Streaming form of [`details_for`]: `on_detail(i, details)` runs once per file as
+/// soon as its details are known (index hits first, then parses as rayon workers
+/// finish them). Once `cancel` is set, files not yet started are skipped.
+pub fn stream_details<F>(
+    paths: &SavePaths,
+    files: &[PathBuf],
+    depth: DetailDepth,
+    cancel: Option<&AtomicBool>,
+    on_detail: F,
+) where
+    F: Fn(usize, Option<IndexedDetails>) + Sync,
+{
+    let mut index = match MetadataIndex::open(paths) {
+        Ok(index) => Some(index),
+        Err(e) => {
+            tracing::warn!("Metadata index unavailable, parsing every save: {}", e);
+            None
+        }
+    };
+    let cancelled = || cancel.is_some_and(|c| c.load(Ordering::Relaxed));
+
+    let stamps: Vec<Option<FileStamp>> = files.iter().map(|p| FileStamp::of(p)).collect();
+    let mut misses = Vec::new();
+    for (i, (path, stamp)) in files.iter().zip(&stamps).enumerate() {
+        let hit = index.as_ref().zip(*stamp).and_then(|(index, stamp)| {
+            index
+                .lookup(&path.to_string_lossy(), stamp, depth)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("{}", e);
+                    None
+                })
+        });
+        match hit {
+            Some(details) => on_detail(i, Some(details)),
+            None => misses.push(i),
+        }
+    }
+
+    let parsed: Vec<(usize, IndexedDetails)> = misses
+        .par_iter()
+        .filter_map(|&i| {
+            if cancelled() {
+                return None;
+            }
+            let details = IndexedDetails::load(&files[i], depth);
+            on_detail(i, details.clone());
+            Some((i, details?))
+        })
+        .collect();
+
+    let fresh: Vec<(FileStamp, IndexedDetails)> = parsed
+        .into_iter()
+        .filter_map(|(i, details)| Some((stamps[i]?, details)))
+        .collect();
+    if let (Some(index), false) = (index.as_mut(), fresh.is_empty()) {
+        if let Err(e) = index.store(&fresh) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    tracing::debug!(
+        "metadata index: {} hit(s), {} parsed",
+        files.len() - misses.len(),
+        fresh.len()
+    );
+}
+
+/// Indexed details (players and inventories, completion counts, ...) for the given saves.
+#[tauri::command]
+pub async fn load_save_index_details(
+    state: tauri::State<'_, SavePathsState>,
+    paths: Vec<String>,
+) -> AppResult<Vec<IndexedDetails>> {
+    let save_paths = state.current()?;
+    run_blocking(move || {
+        let files: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        for file in &files {
+            save_paths.validate_save_games_path(file)?;
+        }
+        Ok(details_for(&save_paths, &files, DetailDepth::Full)
+            .into_iter()
+            .flatten()
+            .collect())
+    })
+    .await
+}
+
+/// Forget every cached row; the next loads re-parse. Returns the number of rows dropped.
+#[tauri::command]
+pub async fn clear_metadata_index(state: tauri::State<'_, SavePathsState>) -> AppResult<usize> {
+    let paths = state.current()?;
+    run_blocking(move || MetadataIndex::open(&paths)?.clear()).await
+}
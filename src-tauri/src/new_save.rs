@@ -1,14 +1,19 @@
-use crate::common::{add_save_to_mainsave, extract_archive_name, get_local_appdata_dir};
-use crate::error::AppResult;
+use crate::common::{
+    add_save_to_mainsave, extract_archive_name, preview_mainsave, write_atomic, MainsaveOp,
+};
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{Difficulty, EtbSave, LevelCompletion, PlayerEntry};
+use crate::file_lock::lock_save;
+use crate::save_backup::backup_file;
+use crate::save_diff::{diff_properties, DryRunReport, FileOp};
+use crate::save_paths::SavePaths;
 use crate::save_shared;
+use crate::save_verify::verified_bytes;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::fs;
-use std::io::{BufWriter, Write};
-use uesave::{
-    FGuid, Properties, Property, PropertyKey, PropertyTagDataPartial, PropertyTagPartial,
-    PropertyType, Save, StructType, StructValue, ValueVec,
-};
+use std::path::PathBuf;
+use uesave::{Properties, Save};
 
 /// Main storyline level data: (DisplayName, LevelName)
 /// Arranged by game progress order - first 17 of endingLevelsData[0]
@@ -99,7 +104,8 @@ pub struct PlayerData {
     pub sanity: f32,
 }
 
-pub fn create_new_save(save_data: SaveData) -> AppResult<()> {
+/// Build the new save in memory; returns the target path, file name and save
+fn plan_new_save(paths: &SavePaths, save_data: &SaveData) -> AppResult<(PathBuf, String, EtbSave)> {
     tracing::info!("Received new save request:");
     tracing::info!("  Archive name: {}", save_data.archive_name);
     tracing::info!("  Level: {}", save_data.level);
@@ -139,21 +145,8 @@ pub fn create_new_save(save_data: SaveData) -> AppResult<()> {
         );
     }
 
-    // Process level mapping
-    let processed_level = match save_data.level.as_str() {
-        "Pipes1" | "Pipes2" => "Pipes".to_string(),
-        _ => save_data.level.clone(),
-    };
-
     // Build target path
-    let app_data_dir = get_local_appdata_dir()?;
-    let save_dir = app_data_dir.join("EscapeTheBackrooms/Saved/SaveGames");
-
-    if !save_dir.exists() {
-        fs::create_dir_all(&save_dir)
-            .map_err(|e| format!("Failed to create save directory: {}", e))?;
-    }
-
+    let save_dir = paths.save_games_dir();
     let file_name = format!(
         "MULTIPLAYER_{}_{}.sav",
         save_data.archive_name, sanitized_difficulty
@@ -164,197 +157,105 @@ pub fn create_new_save(save_data: SaveData) -> AppResult<()> {
 
     // Construct Save object from BasicArchive.json
     // NOTE (uesave 0.7): JSON must contain "schemas" before "root" (hand-written Deserialize)
-    let mut save: Save = serde_json::from_value(save_data.basic_archive.clone()).map_err(|e| {
+    let save: Save = serde_json::from_value(save_data.basic_archive.clone()).map_err(|e| {
         format!(
             "Failed to convert JSON to Save: {:?}, JSON content: {}",
             e, save_data.basic_archive
         )
     })?;
+    let mut save = EtbSave::from(save);
 
-    // Modify CurrentLevel field
-    if processed_level == "Level0" {
-        remove_current_level(&mut save);
+    // Modify CurrentLevel field (Level0 = no field; Pipes1 / Pipes2 also set UnlockedFun)
+    if save_data.level == "Level0" {
+        save.clear_current_level();
     } else {
-        save_shared::modify_current_level(&mut save, processed_level.clone());
+        save.set_current_level(&save_data.level);
     }
 
-    // Handle Pipes UnlockedFun_0 field
-    handle_pipes_unlocked_fun(&mut save, &save_data.level);
-
     // Update difficulty settings
-    save_shared::update_difficulty(&mut save, &save_data.actual_difficulty);
+    let actual_difficulty =
+        Difficulty::from_label(&save_data.actual_difficulty).ok_or_else(|| {
+            AppError::Validation(format!(
+                "Invalid actual difficulty: {}",
+                save_data.actual_difficulty
+            ))
+        })?;
+    save.set_difficulty(actual_difficulty);
 
     // Handle MainEnding parameter
-    update_bool_property(&mut save, "HasCompletedMainEnding", save_data.main_ending)?;
+    save.set_has_completed_main_ending(save_data.main_ending);
 
     // Handle MEG status
-    update_meg_status(&mut save, save_data.meg_unlocked)?;
+    save.set_meg_unlocked(save_data.meg_unlocked);
 
     // Generate LevelsCompleted_0 data
     // main_ending being true means side story (non-main ending) is selected
-    generate_levels_completed(&mut save, &save_data.level, save_data.main_ending)?;
+    generate_levels_completed(&mut save, &save_data.level, save_data.main_ending);
 
     // Update player data
     if !save_data.players.is_empty() {
-        update_player_data(&mut save, &save_data.players)?;
+        update_player_data(paths, &mut save, &save_data.players);
     } else {
         // No players provided: remove template PlayerData to avoid ghost player records
-        let player_data_key = PropertyKey(0, "PlayerData".to_string());
-        if save
-            .root
-            .properties
-            .0
-            .shift_remove(&player_data_key)
-            .is_some()
-        {
+        if save.property(crate::etb_save::PLAYER_DATA).is_some() {
+            save.set_players(&[]);
             tracing::info!("Removed template PlayerData (no players provided)");
         }
     }
 
-    // Write as .sav file
-    let file =
-        fs::File::create(&save_path).map_err(|e| format!("Failed to create output file: {}", e))?;
-    let mut writer = BufWriter::new(file);
-    save.write(&mut writer)
-        .map_err(|e| format!("Failed to write save: {:?}", e))?;
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush buffer: {}", e))?;
-
-    tracing::info!("Save successfully saved to: {:?}", save_path);
-
-    // Update MAINSAVE.sav file
-    let archive_name = extract_archive_name(&file_name);
-    add_save_to_mainsave(archive_name)?;
-
-    Ok(())
+    Ok((save_path, file_name, save))
 }
 
-/// Delete the entire CurrentLevel_0 field
-pub fn remove_current_level(save: &mut Save) -> bool {
-    let key = PropertyKey(0, "CurrentLevel".to_string());
+/// Write the new save and list it in MAINSAVE. Returns the written path.
+pub fn create_new_save(paths: &SavePaths, save_data: SaveData) -> AppResult<PathBuf> {
+    let (save_path, file_name, save) = plan_new_save(paths, &save_data)?;
 
-    if save.root.properties.0.shift_remove(&key).is_some() {
-        tracing::info!("CurrentLevel_0 has been completely removed");
-        true
-    } else {
-        tracing::warn!("CurrentLevel_0 field not found");
-        false
-    }
-}
-
-/// Handle Pipes UnlockedFun_0 field
-fn handle_pipes_unlocked_fun(save: &mut Save, level: &str) {
-    let unlocked_fun_key = PropertyKey(0, "UnlockedFun".to_string());
-
-    match level {
-        "Pipes1" => {
-            if save
-                .root
-                .properties
-                .0
-                .shift_remove(&unlocked_fun_key)
-                .is_some()
-            {
-                tracing::info!("Deleted UnlockedFun_0 field (Pipes1)");
-            }
-        }
-        "Pipes2" => {
-            save_shared::record_root_schema(
-                save,
-                "UnlockedFun",
-                PropertyTagPartial {
-                    id: None,
-                    data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-                },
-            );
-            save.root
-                .properties
-                .0
-                .insert(unlocked_fun_key, Property::Bool(true));
-            tracing::info!("Created UnlockedFun_0 field with value true (Pipes2)");
-        }
-        _ => {}
+    let save_dir = paths.save_games_dir();
+    if !save_dir.exists() {
+        fs::create_dir_all(save_dir)
+            .map_err(|e| format!("Failed to create save directory: {}", e))?;
     }
-}
 
-/// Update boolean property
-fn update_bool_property(save: &mut Save, name: &str, value: bool) -> AppResult<()> {
-    save_shared::record_root_schema(
-        save,
-        name,
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-        },
-    );
-    save.root
-        .properties
-        .0
-        .insert(PropertyKey(0, name.to_string()), Property::Bool(value));
+    // Write as .sav file (template was edited, so only the structure is verified)
+    let bytes = verified_bytes(&save, None, &file_name)?;
+    let _lock = lock_save(paths, &save_path)?;
+    backup_file(paths, &save_path, "create")?;
+    write_atomic(&save_path, &bytes)?;
 
-    tracing::info!("Set {} field to {}", name, value);
-    Ok(())
-}
+    tracing::info!("Save successfully saved to: {:?}", save_path);
 
-/// Update MEG status field
-fn update_meg_status(save: &mut Save, meg_unlocked: bool) -> AppResult<()> {
-    let meg_fields = ["IsMEGUnlocked", "IsMEGPowerOn", "IsMEGSecurityUnlocked"];
+    // Update MAINSAVE.sav file
+    let archive_name = extract_archive_name(&file_name);
+    add_save_to_mainsave(paths, archive_name)?;
 
-    for field in &meg_fields {
-        update_bool_property(save, field, meg_unlocked)?;
-    }
+    Ok(save_path)
+}
 
-    if meg_unlocked {
-        tracing::info!("MEG related fields set to true (MEG unlocked)");
+/// Dry run of [`create_new_save`]: every property of the new file shows as added
+pub fn preview_new_save(paths: &SavePaths, save_data: &SaveData) -> AppResult<DryRunReport> {
+    let (save_path, file_name, save) = plan_new_save(paths, save_data)?;
+    verified_bytes(&save, None, &file_name)?;
+
+    let path = save_path.to_string_lossy().into_owned();
+    let mut report = DryRunReport::default();
+    if save_path.exists() {
+        report
+            .notes
+            .push(format!("{} already exists and will be replaced", path));
+        report.files.push(FileOp::Write { path: path.clone() });
     } else {
-        tracing::info!("MEG related fields set to false (MEG locked)");
+        report.files.push(FileOp::Create { path: path.clone() });
     }
-
-    Ok(())
-}
-
-/// Record schemas for all fields inside a LevelsCompleted struct element.
-/// In uesave 0.7 the schema path is "LevelsCompleted.<field>".
-fn record_level_struct_schemas(save: &mut Save) {
-    let parent = "LevelsCompleted";
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::DISPLAY_NAME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::StrProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::HAS_COMPLETED_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::HAS_UNLOCKED_HUB_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::LEVEL_NAME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::NameProperty),
-        },
+    report.push_diff(
+        path,
+        diff_properties(&Properties::default(), &save.root.properties),
     );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::TIME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::FloatProperty),
-        },
+    let archive_name = extract_archive_name(&file_name).to_string();
+    report.push_diff(
+        paths.mainsave_path().to_string_lossy(),
+        preview_mainsave(paths, &[MainsaveOp::Add(archive_name)])?,
     );
-    // World struct + nested Items/SanityLevel schemas
-    save_shared::create_default_world_property(save, parent);
+    Ok(report)
 }
 
 /// Generate LevelsCompleted_0 data based on selected level
@@ -364,11 +265,7 @@ fn record_level_struct_schemas(save: &mut Save) {
 ///   - 1st to (n-1)th: HasCompleted=true, HasUnlockedHub=true
 ///   - nth (current level): HasCompleted=false, HasUnlockedHub=false
 /// - If is_side_storyline is true (side ending), generate all levels, all set as completed
-fn generate_levels_completed(
-    save: &mut Save,
-    level: &str,
-    is_side_storyline: bool,
-) -> AppResult<()> {
+fn generate_levels_completed(save: &mut EtbSave, level: &str, is_side_storyline: bool) {
     tracing::info!(
         "Generating LevelsCompleted_0 data, target level: {}, side storyline: {}",
         level,
@@ -431,193 +328,47 @@ fn generate_levels_completed(
 
     tracing::info!("Will generate {} level records", levels_to_generate.len());
 
-    // Get existing LevelsCompleted_0 as template
-    let levels_completed_key = PropertyKey(0, "LevelsCompleted".to_string());
-
-    // Get template structure info from existing schemas (0.7: struct_type/id live in schemas)
-    let template_struct = save
-        .schemas
-        .get("LevelsCompleted")
-        .and_then(|tag| match &tag.data {
-            PropertyTagDataPartial::Array(inner) => match &**inner {
-                PropertyTagDataPartial::Struct { struct_type, id } => {
-                    Some((struct_type.clone(), *id))
-                }
-                _ => None,
-            },
-            _ => None,
-        });
-
-    let (struct_type, struct_id) =
-        template_struct.ok_or("Failed to get LevelsCompleted template structure")?;
-
-    // Generate new level records
-    let mut new_values: Vec<StructValue> = Vec::new();
-
-    // Record schemas for the struct fields (used both for new save creation and write-back)
-    record_level_struct_schemas(save);
-
-    for (display_name, level_name, is_completed) in levels_to_generate {
-        let mut level_props = Properties::default();
-
-        // DisplayName
-        level_props.0.insert(
-            PropertyKey(0, save_shared::DISPLAY_NAME_FIELD.to_string()),
-            Property::Str(display_name.to_string()),
-        );
-
-        // HasCompleted
-        level_props.0.insert(
-            PropertyKey(0, save_shared::HAS_COMPLETED_FIELD.to_string()),
-            Property::Bool(is_completed),
-        );
-
-        // HasUnlockedHub
-        level_props.0.insert(
-            PropertyKey(0, save_shared::HAS_UNLOCKED_HUB_FIELD.to_string()),
-            Property::Bool(is_completed),
-        );
-
-        // LevelName
-        level_props.0.insert(
-            PropertyKey(0, save_shared::LEVEL_NAME_FIELD.to_string()),
-            Property::Name(level_name.to_string()),
-        );
-
-        // Time
-        level_props.0.insert(
-            PropertyKey(0, save_shared::TIME_FIELD.to_string()),
-            Property::Float(uesave::Float(-1.0)),
-        );
-
-        // World - Create default World structure
-        let world_prop = save_shared::create_default_world_property(save, "LevelsCompleted");
-        level_props.0.insert(
-            PropertyKey(0, save_shared::WORLD_FIELD.to_string()),
-            world_prop,
-        );
-
-        new_values.push(StructValue::Struct(level_props));
-        tracing::info!(
-            "Added level: {} ({}) - Completed: {}",
-            display_name,
-            level_name,
-            is_completed
-        );
-    }
-
-    // Create new LevelsCompleted_0 property
-    // 0.7: struct id is FGuid (not Option<Uuid>)
-    save.schemas.record(
-        "LevelsCompleted".to_string(),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Array(Box::new(PropertyTagDataPartial::Struct {
-                struct_type: struct_type.clone(),
-                id: struct_id,
-            })),
-        },
-    );
-    let new_levels_completed = Property::Array(ValueVec::Struct(new_values));
-
-    // Replace the original LevelsCompleted_0
-    save.root
-        .properties
-        .0
-        .insert(levels_completed_key, new_levels_completed);
+    let levels: Vec<LevelCompletion> = levels_to_generate
+        .into_iter()
+        .map(|(display_name, level_name, is_completed)| {
+            tracing::info!(
+                "Added level: {} ({}) - Completed: {}",
+                display_name,
+                level_name,
+                is_completed
+            );
+            LevelCompletion::new(display_name, level_name, is_completed)
+        })
+        .collect();
 
-    tracing::info!("LevelsCompleted_0 has been updated");
-    Ok(())
+    // Replace the original LevelsCompleted_0 (keeps the template's struct type)
+    save.set_levels_completed(&levels);
 }
 
 /// Update player data
-fn update_player_data(save: &mut Save, players: &[PlayerData]) -> AppResult<()> {
-    if players.is_empty() {
-        return Ok(());
-    }
-
+fn update_player_data(paths: &SavePaths, save: &mut EtbSave, players: &[PlayerData]) {
     tracing::info!("Processing player data...");
 
-    let map_entries: Vec<_> = players
+    let entries: Vec<PlayerEntry> = players
         .iter()
-        .map(|player| {
-            // Create inventory items list
-            let mut inventory_items: Vec<String> = player
-                .inventory
-                .iter()
-                .take(save_shared::INVENTORY_SLOTS)
-                .map(|&id| save_shared::map_item_id_to_name(id).to_string())
-                .collect();
-
-            inventory_items.resize(save_shared::INVENTORY_SLOTS, "None".to_string());
-
-            // Create player struct properties
-            let mut player_struct_properties = Properties::default();
-
-            // Sanity property
-            let sanity_prop = Property::Float(uesave::Float(player.sanity.clamp(0.0, 100.0)));
-
-            // Inventory property
-            let inventory_prop = Property::Array(ValueVec::Name(inventory_items));
-
-            player_struct_properties.0.insert(
-                PropertyKey(0, save_shared::SANITY_PROP_NAME.to_string()),
-                sanity_prop,
-            );
-            player_struct_properties.0.insert(
-                PropertyKey(0, save_shared::INVENTORY_PROP_NAME.to_string()),
-                inventory_prop,
-            );
-
+        .map(|player| PlayerEntry {
             // PlayerData map key: the game only binds player data to
             // `<steam id>_+_|<EOS PUID>` keys. A bare steam id yields an entry the
             // game ignores (empty backpack, sanity reset to 100), so resolve the real
             // EOS-suffixed key from the cache or an existing save; keep the raw id
             // only when no reusable PUID exists anywhere.
-            let resolved_key = crate::save_batch::resolve_player_full_key(&player.steam_id)
-                .unwrap_or_else(|| player.steam_id.clone());
-
-            uesave::MapEntry {
-                key: Property::Str(resolved_key),
-                value: Property::Struct(StructValue::Struct(player_struct_properties)),
-            }
+            key: crate::save_batch::resolve_player_full_key(paths, &player.steam_id)
+                .unwrap_or_else(|| player.steam_id.clone()),
+            sanity: Some(player.sanity),
+            inventory: player
+                .inventory
+                .iter()
+                .take(save_shared::INVENTORY_SLOTS)
+                .map(|&id| save_shared::map_item_id_to_name(id).to_string())
+                .collect(),
         })
         .collect();
 
-    // Record schemas for PlayerData map and its nested struct fields
-    save.schemas.record(
-        "PlayerData".to_string(),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Map {
-                key_type: Box::new(PropertyTagDataPartial::Other(PropertyType::StrProperty)),
-                value_type: Box::new(PropertyTagDataPartial::Struct {
-                    struct_type: StructType::Struct(None),
-                    id: FGuid::nil(),
-                }),
-            },
-        },
-    );
-    save.schemas.record(
-        format!("PlayerData.{}", save_shared::SANITY_PROP_NAME),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::FloatProperty),
-        },
-    );
-    save.schemas.record(
-        format!("PlayerData.{}", save_shared::INVENTORY_PROP_NAME),
-        save_shared::array_scalar_tag(PropertyTagDataPartial::Other(PropertyType::NameProperty)),
-    );
-
-    // Create PlayerData_0 property
-    let player_data_prop = Property::Map(map_entries);
-
-    save.root
-        .properties
-        .0
-        .insert(PropertyKey(0, "PlayerData".to_string()), player_data_prop);
+    save.set_players(&entries);
     tracing::info!("PlayerData_0 Map created");
-
-    Ok(())
 }
@@ -0,0 +1,320 @@
+//! Path resolver module - Locates the game's SaveGames directory
+//! Windows reads LOCALAPPDATA; Linux / Steam Deck discovers the Proton prefix
+//! inside every known Steam library. A user-configured override always wins.
+
+use crate::common::run_blocking;
+use crate::error::{AppError, AppResult};
+use crate::library_profiles;
+use crate::save_paths::{SavePaths, SavePathsState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Steam app id of Escape the Backrooms (name of its `compatdata` folder)
+pub const ETB_STEAM_APP_ID: &str = "1943950";
+/// SaveGames location relative to the (real or Proton) LocalAppData directory
+pub const SAVE_GAMES_SUBDIR: &str = "EscapeTheBackrooms/Saved/SaveGames";
+/// LocalAppData location inside a Proton compatdata folder.
+/// Proton always runs games as the `steamuser` Windows account.
+const PROTON_LOCAL_APPDATA: &str = "pfx/drive_c/users/steamuser/AppData/Local";
+/// Override settings file, stored in the app config dir
+const SAVE_ROOT_CONFIG_FILE: &str = "save_root.json";
+/// Environment variable that overrides the SaveGames directory (takes precedence over settings)
+pub const SAVE_GAMES_DIR_ENV: &str = "ETB_SAVE_GAMES_DIR";
+
+/// Cached libraryfolders.vdf `"path"` regex
+static LIBRARY_PATH_REGEX: OnceLock<Regex> = OnceLock::new();
+
+#[inline]
+fn get_library_path_regex() -> &'static Regex {
+    LIBRARY_PATH_REGEX.get_or_init(|| {
+        Regex::new(r#""path"\s+"((?:[^"\\]|\\.)*)""#).expect("Regex compilation failed")
+    })
+}
+
+/// Persisted save-root settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveRootConfig {
+    /// User-chosen SaveGames directory; `None` means auto-detect
+    pub save_games_override: Option<PathBuf>,
+}
+
+/// Where the active SaveGames directory came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SaveRootSource {
+    Environment,
+    Library,
+    Override,
+    LocalAppData,
+    Proton,
+}
+
+/// Snapshot of the save-root resolution, returned to the settings page
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveRootStatus {
+    pub save_games_dir: Option<String>,
+    pub source: Option<SaveRootSource>,
+    pub save_games_override: Option<String>,
+    pub discovered: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// App config directory (`ETBSaveManager`), independent of where the game lives.
+///
+/// Windows keeps using `%LOCALAPPDATA%\ETBSaveManager` (build.rs reads gpu_config.json
+/// from there); other platforms use the XDG data dir, so the override settings can be
+/// read before the game's save directory is known.
+pub fn resolve_app_config_dir() -> AppResult<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var("LOCALAPPDATA")
+            .map(|p| PathBuf::from(p).join("ETBSaveManager"))
+            .map_err(|e| AppError::General(format!("Failed to get LOCALAPPDATA: {}", e)))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs_next::data_local_dir()
+            .map(|p| p.join("ETBSaveManager"))
+            .ok_or_else(|| AppError::General("Failed to locate local data directory".to_string()))
+    }
+}
+
+/// Load the override settings. Missing or corrupt files degrade to defaults.
+pub fn load_save_root_config() -> SaveRootConfig {
+    let Ok(dir) = resolve_app_config_dir() else {
+        return SaveRootConfig::default();
+    };
+    let Ok(content) = fs::read_to_string(dir.join(SAVE_ROOT_CONFIG_FILE)) else {
+        return SaveRootConfig::default();
+    };
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+/// Persist the override settings
+pub fn write_save_root_config(config: &SaveRootConfig) -> AppResult<()> {
+    let dir = resolve_app_config_dir()?;
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize save root config: {}", e))?;
+    fs::write(dir.join(SAVE_ROOT_CONFIG_FILE), json)
+        .map_err(|e| format!("Failed to write save root config: {}", e))?;
+    Ok(())
+}
+
+/// Standard Steam install locations on Linux (native, flatpak and snap packages)
+fn steam_root_candidates() -> Vec<PathBuf> {
+    let Some(home) = dirs_next::home_dir() else {
+        return Vec::new();
+    };
+    [
+        ".steam/steam",
+        ".steam/root",
+        ".local/share/Steam",
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        "snap/steam/common/.local/share/Steam",
+    ]
+    .iter()
+    .map(|rel| home.join(rel))
+    .collect()
+}
+
+/// Extract library paths from the contents of a `libraryfolders.vdf` file
+pub fn parse_library_folders(content: &str) -> Vec<PathBuf> {
+    get_library_path_regex()
+        .captures_iter(content)
+        .filter_map(|caps| caps.get(1))
+        .map(|m| PathBuf::from(m.as_str().replace("\\\\", "\\")))
+        .collect()
+}
+
+/// All Steam library roots: the standard installs plus every extra library
+/// listed in their `libraryfolders.vdf`. Canonicalized and de-duplicated
+/// (`~/.steam/steam` is usually a symlink to `~/.local/share/Steam`).
+pub fn steam_library_paths() -> Vec<PathBuf> {
+    let mut libraries: Vec<PathBuf> = Vec::new();
+    let mut push_unique = |path: PathBuf| {
+        let Ok(canon) = path.canonicalize() else {
+            return;
+        };
+        if !libraries.contains(&canon) {
+            libraries.push(canon);
+        }
+    };
+
+    for root in steam_root_candidates() {
+        if !root.is_dir() {
+            continue;
+        }
+        push_unique(root.clone());
+
+        for vdf in [
+            root.join("steamapps/libraryfolders.vdf"),
+            root.join("config/libraryfolders.vdf"),
+        ] {
+            if let Ok(content) = fs::read_to_string(&vdf) {
+                for library in parse_library_folders(&content) {
+                    push_unique(library);
+                }
+            }
+        }
+    }
+
+    libraries
+}
+
+/// Proton LocalAppData directory of the game inside a Steam library, if its prefix exists
+fn proton_local_appdata(library: &Path) -> Option<PathBuf> {
+    let dir = library
+        .join("steamapps/compatdata")
+        .join(ETB_STEAM_APP_ID)
+        .join(PROTON_LOCAL_APPDATA);
+    dir.is_dir().then_some(dir)
+}
+
+/// Every Proton SaveGames directory found across all Steam libraries
+pub fn discover_proton_save_games_dirs() -> Vec<PathBuf> {
+    steam_library_paths()
+        .iter()
+        .filter_map(|library| proton_local_appdata(library))
+        .map(|local| local.join(SAVE_GAMES_SUBDIR))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Proton LocalAppData directory of the game. Prefers a prefix that already holds
+/// saves; falls back to any existing prefix (game installed but never saved).
+pub fn discover_proton_local_appdata() -> Option<PathBuf> {
+    let prefixes: Vec<PathBuf> = steam_library_paths()
+        .iter()
+        .filter_map(|library| proton_local_appdata(library))
+        .collect();
+
+    prefixes
+        .iter()
+        .find(|local| local.join(SAVE_GAMES_SUBDIR).is_dir())
+        .or_else(|| prefixes.first())
+        .cloned()
+}
+
+/// The game's LocalAppData directory for this platform
+pub fn resolve_local_appdata_dir() -> AppResult<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var("LOCALAPPDATA")
+            .map(PathBuf::from)
+            .map_err(|e| AppError::General(format!("Failed to get LOCALAPPDATA: {}", e)))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        discover_proton_local_appdata().ok_or_else(|| {
+            AppError::General(format!(
+                "Escape the Backrooms Proton prefix (compatdata/{}) not found in any Steam library; \
+                 set the SaveGames folder manually in settings",
+                ETB_STEAM_APP_ID
+            ))
+        })
+    }
+}
+
+/// Resolve the SaveGames directory and report where it came from.
+///
+/// Order: `ETB_SAVE_GAMES_DIR` env var → active library profile → the default root.
+pub fn resolve_save_games_dir() -> AppResult<(PathBuf, SaveRootSource)> {
+    if let Some(dir) = std::env::var_os(SAVE_GAMES_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok((PathBuf::from(dir), SaveRootSource::Environment));
+    }
+
+    if let Some(profile) = library_profiles::active_profile() {
+        return Ok((profile.save_games_dir, SaveRootSource::Library));
+    }
+
+    resolve_default_save_games_dir()
+}
+
+/// Resolve the default SaveGames root (the "Default" library): configured override →
+/// platform default (LOCALAPPDATA on Windows, discovered Proton prefix elsewhere).
+pub fn resolve_default_save_games_dir() -> AppResult<(PathBuf, SaveRootSource)> {
+    if let Some(dir) = load_save_root_config().save_games_override {
+        return Ok((dir, SaveRootSource::Override));
+    }
+
+    let source = if cfg!(target_os = "windows") {
+        SaveRootSource::LocalAppData
+    } else {
+        SaveRootSource::Proton
+    };
+    resolve_local_appdata_dir().map(|p| (p.join(SAVE_GAMES_SUBDIR), source))
+}
+
+/// Report the active save root, the configured override and every discovered Proton root.
+#[tauri::command]
+pub async fn get_save_root_status() -> AppResult<SaveRootStatus> {
+    run_blocking(|| {
+        let config = load_save_root_config();
+        let discovered = discover_proton_save_games_dirs()
+            .into_iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+
+        let (save_games_dir, source, error) = match resolve_save_games_dir() {
+            Ok((dir, source)) => (Some(dir.to_string_lossy().into_owned()), Some(source), None),
+            Err(e) => (None, None, Some(e.to_string())),
+        };
+
+        Ok(SaveRootStatus {
+            save_games_dir,
+            source,
+            save_games_override: config
+                .save_games_override
+                .map(|p| p.to_string_lossy().into_owned()),
+            discovered,
+            error,
+        })
+    })
+    .await
+}
+
+/// Set (or clear with `None`) the SaveGames override. The directory must exist.
+/// Applied immediately: the managed [`SavePathsState`] is re-resolved, and the
+/// newly active SaveGames directory is returned.
+#[tauri::command]
+pub async fn set_save_games_override(
+    state: tauri::State<'_, SavePathsState>,
+    path: Option<String>,
+) -> AppResult<String> {
+    // Re-resolving walks the Steam / Proton library folders: keep it off the main thread
+    let resolved = run_blocking(move || {
+        let save_games_override = match path.map(|p| p.trim().to_string()) {
+            Some(p) if !p.is_empty() => {
+                let dir = PathBuf::from(&p);
+                if !dir.is_dir() {
+                    return Err(AppError::Validation(format!(
+                        "SaveGames directory does not exist: {}",
+                        p
+                    )));
+                }
+                Some(dir)
+            }
+            _ => None,
+        };
+
+        tracing::info!("SaveGames override set to: {:?}", save_games_override);
+        write_save_root_config(&SaveRootConfig {
+            save_games_override,
+        })?;
+
+        Ok(SavePaths::resolve().map(Arc::new))
+    })
+    .await?;
+
+    let paths = state.replace(resolved)?;
+    Ok(paths.save_games_dir().to_string_lossy().into_owned())
+}
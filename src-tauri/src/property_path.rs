@@ -0,0 +1,727 @@
+//! Property path module - Get / set single save values by path
+//! Paths look like `LevelsCompleted[4].HasCompleted_4_...` or
+//! `PlayerData["7656..."].Sanity_6_...`. Setting creates missing intermediate
+//! properties and records their schemas (registry first, inferred otherwise).
+
+use crate::common::{run_blocking, write_atomic};
+use crate::error::{AppError, AppResult};
+use crate::etb_save::EtbSave;
+use crate::file_lock::lock_save;
+use crate::save_backup::backup_file;
+use crate::save_diff::{diff_roots, DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_schema::{self, SchemaKind};
+use crate::save_verify::verified_bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use uesave::{
+    MapEntry, Properties, Property, PropertyKey, PropertyTagDataPartial, PropertyType, Save,
+    StructValue, ValueVec,
+};
+
+/// One step of a property path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Property / struct field name (the `_0` key index may be included)
+    Field(String),
+    /// Array element
+    Index(usize),
+    /// Map entry by string key
+    Key(String),
+}
+
+/// A value with its property type. Composite values are returned as `raw` JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum TypedValue {
+    Bool(bool),
+    Int(i32),
+    Int64(i64),
+    Float(f32),
+    Double(f64),
+    Str(String),
+    Name(String),
+    Byte(u8),
+    /// ByteProperty holding an enum label (e.g. `E_Difficulty::NewEnumerator1`)
+    ByteLabel(String),
+    Enum(String),
+    /// Read-only view of a struct / array / map
+    Raw(JsonValue),
+}
+
+impl TypedValue {
+    /// Parse `raw` as the type named `kind` (the serialized type names)
+    pub fn parse(kind: &str, raw: &str) -> AppResult<Self> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            AppError::Validation(format!("Invalid {} value '{}': {}", kind, raw, e))
+        };
+        Ok(match kind {
+            "bool" => TypedValue::Bool(raw.parse().map_err(|e| invalid(&e))?),
+            "int" => TypedValue::Int(raw.parse().map_err(|e| invalid(&e))?),
+            "int64" => TypedValue::Int64(raw.parse().map_err(|e| invalid(&e))?),
+            "float" => TypedValue::Float(raw.parse().map_err(|e| invalid(&e))?),
+            "double" => TypedValue::Double(raw.parse().map_err(|e| invalid(&e))?),
+            "byte" => TypedValue::Byte(raw.parse().map_err(|e| invalid(&e))?),
+            "str" => TypedValue::Str(raw.to_string()),
+            "name" => TypedValue::Name(raw.to_string()),
+            "byteLabel" => TypedValue::ByteLabel(raw.to_string()),
+            "enum" => TypedValue::Enum(raw.to_string()),
+            other => {
+                return Err(AppError::Validation(format!(
+                    "Unknown value type '{}'",
+                    other
+                )))
+            }
+        })
+    }
+
+    /// Scalar view of a property (composites as `Raw` JSON)
+    pub fn from_property(prop: &Property) -> Self {
+        match prop {
+            Property::Bool(v) => TypedValue::Bool(*v),
+            Property::Int(v) => TypedValue::Int(*v),
+            Property::Int64(v) => TypedValue::Int64(*v),
+            Property::Float(v) => TypedValue::Float(v.0),
+            Property::Double(v) => TypedValue::Double(v.0),
+            Property::Str(v) => TypedValue::Str(v.clone()),
+            Property::Name(v) => TypedValue::Name(v.clone()),
+            Property::Byte(uesave::Byte::Byte(v)) => TypedValue::Byte(*v),
+            Property::Byte(uesave::Byte::Label(v)) => TypedValue::ByteLabel(v.clone()),
+            Property::Enum(v) => TypedValue::Enum(v.clone()),
+            other => TypedValue::Raw(serde_json::to_value(other).unwrap_or(JsonValue::Null)),
+        }
+    }
+
+    fn to_property(&self) -> AppResult<Property> {
+        Ok(match self {
+            TypedValue::Bool(v) => Property::Bool(*v),
+            TypedValue::Int(v) => Property::Int(*v),
+            TypedValue::Int64(v) => Property::Int64(*v),
+            TypedValue::Float(v) => Property::Float(uesave::Float(*v)),
+            TypedValue::Double(v) => Property::Double(uesave::Double(*v)),
+            TypedValue::Str(v) => Property::Str(v.clone()),
+            TypedValue::Name(v) => Property::Name(v.clone()),
+            TypedValue::Byte(v) => Property::Byte(uesave::Byte::Byte(*v)),
+            TypedValue::ByteLabel(v) => Property::Byte(uesave::Byte::Label(v.clone())),
+            TypedValue::Enum(v) => Property::Enum(v.clone()),
+            TypedValue::Raw(_) => {
+                return Err(AppError::Validation(
+                    "Only scalar values can be set by path".to_string(),
+                ))
+            }
+        })
+    }
+
+    /// Property type for an inferred schema (`None` when the enum name is unknowable)
+    fn property_type(&self) -> Option<PropertyType> {
+        match self {
+            TypedValue::Bool(_) => Some(PropertyType::BoolProperty),
+            TypedValue::Int(_) => Some(PropertyType::IntProperty),
+            TypedValue::Int64(_) => Some(PropertyType::Int64Property),
+            TypedValue::Float(_) => Some(PropertyType::FloatProperty),
+            TypedValue::Double(_) => Some(PropertyType::DoubleProperty),
+            TypedValue::Str(_) => Some(PropertyType::StrProperty),
+            TypedValue::Name(_) => Some(PropertyType::NameProperty),
+            _ => None,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            TypedValue::Bool(_) => "bool",
+            TypedValue::Int(_) => "int",
+            TypedValue::Int64(_) => "int64",
+            TypedValue::Float(_) => "float",
+            TypedValue::Double(_) => "double",
+            TypedValue::Str(_) => "str",
+            TypedValue::Name(_) => "name",
+            TypedValue::Byte(_) => "byte",
+            TypedValue::ByteLabel(_) => "byteLabel",
+            TypedValue::Enum(_) => "enum",
+            TypedValue::Raw(_) => "raw",
+        }
+    }
+}
+
+/// Result of a get / set
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyValue {
+    pub path: String,
+    /// Dotted schema path (field names only, as uesave records schemas)
+    pub schema_path: String,
+    pub value: TypedValue,
+    /// Set only: properties / elements / entries that had to be created
+    pub created: Vec<String>,
+}
+
+/// Parse `Root.Field[3]["key"].Other` into segments
+pub fn parse_path(path: &str) -> AppResult<Vec<Segment>> {
+    let invalid = |msg: &str| AppError::Validation(format!("Invalid path '{}': {}", path, msg));
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+    let mut expect_field = true;
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '.' => {
+                chars.next();
+                if expect_field {
+                    return Err(invalid("empty field name"));
+                }
+                expect_field = true;
+            }
+            '[' => {
+                chars.next();
+                if expect_field {
+                    return Err(invalid("'[' must follow a field, index or key"));
+                }
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('\\') => {
+                                key.push(chars.next().ok_or_else(|| invalid("dangling escape"))?)
+                            }
+                            Some('"') => break,
+                            Some(c) => key.push(c),
+                            None => return Err(invalid("unterminated key")),
+                        }
+                    }
+                    segments.push(Segment::Key(key));
+                } else {
+                    let mut digits = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if c == ']' {
+                            break;
+                        }
+                        digits.push(c);
+                        chars.next();
+                    }
+                    let index = digits
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid("index must be a number or a quoted key"))?;
+                    segments.push(Segment::Index(index));
+                }
+                if chars.next() != Some(']') {
+                    return Err(invalid("expected ']'"));
+                }
+            }
+            _ => {
+                if !expect_field {
+                    return Err(invalid("expected '.' or '['"));
+                }
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                segments.push(Segment::Field(name));
+                expect_field = false;
+            }
+        }
+    }
+
+    if expect_field {
+        return Err(invalid(
+            "path must start with and end on a name, index or key",
+        ));
+    }
+    Ok(segments)
+}
+
+fn join_schema_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+/// Key matching a field segment: exact name, or `Name_<index>` as in the JSON view
+fn field_matches(key: &PropertyKey, field: &str) -> bool {
+    key.1 == field || format!("{}_{}", key.1, key.0) == field
+}
+
+/// Field name without a trailing `_<index>` when that is how it was written
+fn bare_field_name(props: &Properties, field: &str) -> String {
+    props
+        .0
+        .keys()
+        .find(|key| field_matches(key, field))
+        .map_or_else(|| new_field_key(field).1, |key| key.1.clone())
+}
+
+/// Key for a field that doesn't exist yet: the JSON-view spelling `Name_<index>`
+/// becomes `PropertyKey(index, Name)`, anything else gets index 0
+fn new_field_key(field: &str) -> PropertyKey {
+    field
+        .rsplit_once('_')
+        .filter(|(name, index)| !name.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|(name, index)| Some(PropertyKey(index.parse().ok()?, name.to_string())))
+        .unwrap_or_else(|| PropertyKey(0, field.to_string()))
+}
+
+fn map_key_matches(key: &Property, wanted: &str) -> bool {
+    matches!(key, Property::Str(k) | Property::Name(k) if k == wanted)
+}
+
+fn not_found(path: &str) -> AppError {
+    AppError::Validation(format!("No property at '{}'", path))
+}
+
+/// Read one value
+pub fn get_value(save: &Save, path: &str) -> AppResult<PropertyValue> {
+    let segments = parse_path(path)?;
+    let Segment::Field(root) = &segments[0] else {
+        return Err(not_found(path));
+    };
+
+    let mut schema_path = bare_field_name(&save.root.properties, root);
+    let mut current = save
+        .root
+        .properties
+        .0
+        .iter()
+        .find(|(key, _)| field_matches(key, root))
+        .map(|(_, prop)| prop)
+        .ok_or_else(|| not_found(path))?;
+
+    let mut rest = &segments[1..];
+    while let Some(segment) = rest.first() {
+        rest = &rest[1..];
+        current = match (segment, current) {
+            (Segment::Field(f), Property::Struct(StructValue::Struct(props))) => {
+                schema_path = join_schema_path(&schema_path, &bare_field_name(props, f));
+                props
+                    .0
+                    .iter()
+                    .find(|(key, _)| field_matches(key, f))
+                    .map(|(_, prop)| prop)
+                    .ok_or_else(|| not_found(path))?
+            }
+            (Segment::Index(i), Property::Array(ValueVec::Struct(values))) => {
+                let Some(StructValue::Struct(props)) = values.get(*i) else {
+                    return Err(not_found(path));
+                };
+                // An array element is addressed through its fields
+                let Some(Segment::Field(f)) = rest.first() else {
+                    return Ok(PropertyValue {
+                        path: path.to_string(),
+                        schema_path,
+                        value: TypedValue::Raw(
+                            serde_json::to_value(&values[*i]).unwrap_or(JsonValue::Null),
+                        ),
+                        created: Vec::new(),
+                    });
+                };
+                rest = &rest[1..];
+                schema_path = join_schema_path(&schema_path, &bare_field_name(props, f));
+                props
+                    .0
+                    .iter()
+                    .find(|(key, _)| field_matches(key, f))
+                    .map(|(_, prop)| prop)
+                    .ok_or_else(|| not_found(path))?
+            }
+            (Segment::Index(i), Property::Array(values)) if rest.is_empty() => {
+                return Ok(PropertyValue {
+                    path: path.to_string(),
+                    schema_path,
+                    value: scalar_element(values, *i).ok_or_else(|| not_found(path))?,
+                    created: Vec::new(),
+                });
+            }
+            (Segment::Key(k), Property::Map(entries)) => entries
+                .iter()
+                .find(|entry| map_key_matches(&entry.key, k))
+                .map(|entry| &entry.value)
+                .ok_or_else(|| not_found(path))?,
+            _ => return Err(not_found(path)),
+        };
+    }
+
+    Ok(PropertyValue {
+        path: path.to_string(),
+        schema_path,
+        value: TypedValue::from_property(current),
+        created: Vec::new(),
+    })
+}
+
+fn scalar_element(values: &ValueVec, index: usize) -> Option<TypedValue> {
+    Some(match values {
+        ValueVec::Bool(v) => TypedValue::Bool(*v.get(index)?),
+        ValueVec::Int(v) => TypedValue::Int(*v.get(index)?),
+        ValueVec::Int64(v) => TypedValue::Int64(*v.get(index)?),
+        ValueVec::Float(v) => TypedValue::Float(v.get(index)?.0),
+        ValueVec::Double(v) => TypedValue::Double(v.get(index)?.0),
+        ValueVec::Str(v) => TypedValue::Str(v.get(index)?.clone()),
+        ValueVec::Name(v) => TypedValue::Name(v.get(index)?.clone()),
+        ValueVec::Enum(v) => TypedValue::Enum(v.get(index)?.clone()),
+        _ => return None,
+    })
+}
+
+/// Write `value` into element `index` of a scalar array (`index == len` appends)
+fn set_scalar_element(values: &mut ValueVec, index: usize, value: &TypedValue) -> AppResult<bool> {
+    fn put<T>(v: &mut Vec<T>, index: usize, item: T) -> Option<bool> {
+        match index.cmp(&v.len()) {
+            std::cmp::Ordering::Less => {
+                v[index] = item;
+                Some(false)
+            }
+            std::cmp::Ordering::Equal => {
+                v.push(item);
+                Some(true)
+            }
+            std::cmp::Ordering::Greater => None,
+        }
+    }
+    let result = match (values, value) {
+        (ValueVec::Bool(v), TypedValue::Bool(x)) => put(v, index, *x),
+        (ValueVec::Int(v), TypedValue::Int(x)) => put(v, index, *x),
+        (ValueVec::Int64(v), TypedValue::Int64(x)) => put(v, index, *x),
+        (ValueVec::Float(v), TypedValue::Float(x)) => put(v, index, uesave::Float(*x)),
+        (ValueVec::Double(v), TypedValue::Double(x)) => put(v, index, uesave::Double(*x)),
+        (ValueVec::Str(v), TypedValue::Str(x)) => put(v, index, x.clone()),
+        (ValueVec::Name(v), TypedValue::Name(x)) => put(v, index, x.clone()),
+        (ValueVec::Enum(v), TypedValue::Enum(x)) => put(v, index, x.clone()),
+        _ => {
+            return Err(AppError::Validation(format!(
+                "Array elements are not of type {}",
+                value.type_name()
+            )))
+        }
+    };
+    result.ok_or_else(|| AppError::Validation(format!("Index {} is past the end", index)))
+}
+
+/// Empty scalar array of the value's type
+fn empty_scalar_array(value: &TypedValue) -> Option<ValueVec> {
+    Some(match value {
+        TypedValue::Bool(_) => ValueVec::Bool(Vec::new()),
+        TypedValue::Int(_) => ValueVec::Int(Vec::new()),
+        TypedValue::Int64(_) => ValueVec::Int64(Vec::new()),
+        TypedValue::Float(_) => ValueVec::Float(Vec::new()),
+        TypedValue::Double(_) => ValueVec::Double(Vec::new()),
+        TypedValue::Str(_) => ValueVec::Str(Vec::new()),
+        TypedValue::Name(_) => ValueVec::Name(Vec::new()),
+        _ => return None,
+    })
+}
+
+/// State threaded through a set: the value, the read-only schemas and what changed
+struct SetContext<'a> {
+    path: &'a str,
+    value: &'a TypedValue,
+    schemas: &'a uesave::PropertySchemas,
+    new_schemas: Vec<(String, SchemaKind)>,
+    created: Vec<String>,
+}
+
+impl SetContext<'_> {
+    fn cannot_infer(&self, schema_path: &str) -> AppError {
+        AppError::Validation(format!(
+            "Cannot create '{}' in '{}': its schema is unknown",
+            schema_path, self.path
+        ))
+    }
+
+    /// New property for a missing field, shaped by what follows it in the path
+    fn new_property(&mut self, schema_path: &str, rest: &[Segment]) -> AppResult<Property> {
+        let registered = save_schema::lookup(schema_path).map(|entry| entry.kind);
+        let (prop, kind) = match rest.first() {
+            None => {
+                let kind = match (registered, self.value.property_type()) {
+                    (Some(kind), _) => kind,
+                    (None, Some(ty)) => SchemaKind::Scalar(ty),
+                    (None, None) => return Err(self.cannot_infer(schema_path)),
+                };
+                (self.value.to_property()?, kind)
+            }
+            // uesave can't write a struct property without its struct type name
+            Some(Segment::Field(_)) => match registered {
+                Some(kind @ SchemaKind::Struct(Some(_))) => (
+                    Property::Struct(StructValue::Struct(Properties::default())),
+                    kind,
+                ),
+                _ => return Err(self.cannot_infer(schema_path)),
+            },
+            Some(Segment::Index(_)) => match registered {
+                Some(kind @ SchemaKind::StructArray(_)) => {
+                    (Property::Array(ValueVec::Struct(Vec::new())), kind)
+                }
+                _ if rest.len() == 1 => {
+                    let ty = self
+                        .value
+                        .property_type()
+                        .ok_or_else(|| self.cannot_infer(schema_path))?;
+                    let values = empty_scalar_array(self.value)
+                        .ok_or_else(|| self.cannot_infer(schema_path))?;
+                    (
+                        Property::Array(values),
+                        registered.unwrap_or(SchemaKind::ScalarArray(ty)),
+                    )
+                }
+                _ => return Err(self.cannot_infer(schema_path)),
+            },
+            Some(Segment::Key(_)) => {
+                let kind = match registered {
+                    Some(kind) => kind,
+                    None if rest.len() > 1 => SchemaKind::StrStructMap,
+                    None => SchemaKind::StrScalarMap(
+                        self.value
+                            .property_type()
+                            .ok_or_else(|| self.cannot_infer(schema_path))?,
+                    ),
+                };
+                (Property::Map(Vec::new()), kind)
+            }
+        };
+        self.new_schemas.push((schema_path.to_string(), kind));
+        self.created.push(schema_path.to_string());
+        Ok(prop)
+    }
+
+    /// Set `field` (then `rest`) inside a struct, creating it when missing
+    fn set_in_props(
+        &mut self,
+        props: &mut Properties,
+        field: &str,
+        rest: &[Segment],
+        prefix: &str,
+    ) -> AppResult<String> {
+        let schema_path = join_schema_path(prefix, &bare_field_name(props, field));
+        let existing = props
+            .0
+            .iter_mut()
+            .find(|(key, _)| field_matches(key, field))
+            .map(|(_, prop)| prop);
+        let prop = match existing {
+            Some(prop) => prop,
+            None => {
+                let prop = self.new_property(&schema_path, rest)?;
+                let key = new_field_key(field);
+                props.0.insert(key.clone(), prop);
+                props.0.get_mut(&key).expect("just inserted")
+            }
+        };
+        self.set_in_property(prop, rest, schema_path)
+    }
+
+    /// Apply `rest` to an existing property; an empty `rest` assigns the value
+    fn set_in_property(
+        &mut self,
+        prop: &mut Property,
+        rest: &[Segment],
+        schema_path: String,
+    ) -> AppResult<String> {
+        let Some(segment) = rest.first() else {
+            let held = TypedValue::from_property(prop).type_name();
+            if held != self.value.type_name() {
+                return Err(AppError::Validation(format!(
+                    "'{}' holds {}, not {}",
+                    self.path,
+                    held,
+                    self.value.type_name()
+                )));
+            }
+            *prop = self.value.to_property()?;
+            return Ok(schema_path);
+        };
+
+        match (segment, prop) {
+            (Segment::Field(f), Property::Struct(StructValue::Struct(props))) => {
+                self.set_in_props(props, f, &rest[1..], &schema_path)
+            }
+            (Segment::Index(i), Property::Array(ValueVec::Struct(values))) => {
+                let Some(Segment::Field(f)) = rest.get(1) else {
+                    return Err(AppError::Validation(format!(
+                        "'{}': struct array elements are set through a field",
+                        self.path
+                    )));
+                };
+                if *i == values.len() {
+                    values.push(StructValue::Struct(Properties::default()));
+                    self.created.push(format!("{}[{}]", schema_path, i));
+                }
+                match values.get_mut(*i) {
+                    Some(StructValue::Struct(props)) => {
+                        self.set_in_props(props, f, &rest[2..], &schema_path)
+                    }
+                    Some(_) => Err(not_found(self.path)),
+                    None => Err(AppError::Validation(format!(
+                        "Index {} is past the end of '{}'",
+                        i, schema_path
+                    ))),
+                }
+            }
+            (Segment::Index(i), Property::Array(values)) if rest.len() == 1 => {
+                if set_scalar_element(values, *i, self.value)? {
+                    self.created.push(format!("{}[{}]", schema_path, i));
+                }
+                Ok(schema_path)
+            }
+            (Segment::Key(k), Property::Map(entries)) => {
+                let index = match entries.iter().position(|e| map_key_matches(&e.key, k)) {
+                    Some(index) => index,
+                    None => {
+                        let value = match self.schemas.get(&schema_path).map(|tag| &tag.data) {
+                            Some(PropertyTagDataPartial::Map { value_type, .. })
+                                if matches!(
+                                    **value_type,
+                                    PropertyTagDataPartial::Struct { .. }
+                                ) =>
+                            {
+                                Property::Struct(StructValue::Struct(Properties::default()))
+                            }
+                            _ if rest.len() == 1 => self.value.to_property()?,
+                            _ => return Err(self.cannot_infer(&schema_path)),
+                        };
+                        entries.push(MapEntry {
+                            key: Property::Str(k.clone()),
+                            value,
+                        });
+                        self.created.push(format!("{}[\"{}\"]", schema_path, k));
+                        entries.len() - 1
+                    }
+                };
+                self.set_in_property(&mut entries[index].value, &rest[1..], schema_path)
+            }
+            _ => Err(not_found(self.path)),
+        }
+    }
+}
+
+/// Set one scalar value, creating missing intermediates and their schemas
+pub fn set_value(save: &mut Save, path: &str, value: &TypedValue) -> AppResult<PropertyValue> {
+    let segments = parse_path(path)?;
+    let Segment::Field(root) = &segments[0] else {
+        return Err(not_found(path));
+    };
+
+    let Save {
+        root: object,
+        schemas,
+        ..
+    } = save;
+    let mut ctx = SetContext {
+        path,
+        value,
+        schemas,
+        new_schemas: Vec::new(),
+        created: Vec::new(),
+    };
+    let schema_path = ctx.set_in_props(&mut object.properties, root, &segments[1..], "")?;
+    let SetContext {
+        new_schemas,
+        created,
+        ..
+    } = ctx;
+
+    for (schema_path, kind) in new_schemas {
+        save_schema::record_or(save, &schema_path, kind);
+    }
+
+    Ok(PropertyValue {
+        path: path.to_string(),
+        schema_path,
+        value: value.clone(),
+        created,
+    })
+}
+
+fn read_save_file(path: &Path) -> AppResult<(Vec<u8>, EtbSave)> {
+    let original = fs::read(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let save = EtbSave::read(&mut Cursor::new(&original))?;
+    Ok((original, save))
+}
+
+/// Read one value from a save on disk
+pub fn get_file_value(path: &Path, property_path: &str) -> AppResult<PropertyValue> {
+    let (_, save) = read_save_file(path)?;
+    get_value(&save, property_path)
+}
+
+/// Set one value in a save on disk (verified, via temp file)
+pub fn set_file_value(
+    paths: &SavePaths,
+    path: &Path,
+    property_path: &str,
+    value: &TypedValue,
+) -> AppResult<PropertyValue> {
+    let _lock = lock_save(paths, path)?;
+    let (original, mut save) = read_save_file(path)?;
+    let result = set_value(save.as_save_mut(), property_path, value)?;
+
+    let bytes = verified_bytes(&save, Some(&original), &path.to_string_lossy())?;
+    backup_file(paths, path, "set-property")?;
+    write_atomic(path, &bytes)?;
+
+    tracing::info!("Set {} in {:?}", property_path, path);
+    Ok(result)
+}
+
+/// Dry run of [`set_file_value`]
+pub fn preview_set_file_value(
+    path: &Path,
+    property_path: &str,
+    value: &TypedValue,
+) -> AppResult<DryRunReport> {
+    let (original, mut save) = read_save_file(path)?;
+    set_value(save.as_save_mut(), property_path, value)?;
+    verified_bytes(&save, Some(&original), &path.to_string_lossy())?;
+
+    let before = EtbSave::read(&mut Cursor::new(&original))?;
+    let file = path.to_string_lossy().into_owned();
+    let mut report = DryRunReport::default();
+    report.files.push(FileOp::Write { path: file.clone() });
+    report.push_diff(file, diff_roots(&before, &save));
+    Ok(report)
+}
+
+/// Read a single value from a save by property path.
+#[tauri::command]
+pub async fn get_save_property(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    path: String,
+) -> AppResult<PropertyValue> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let file = Path::new(&file_path);
+        paths.validate_save_games_path(file)?;
+        get_file_value(file, &path)
+    })
+    .await
+}
+
+/// Set a single typed value in a save by property path.
+#[tauri::command]
+pub async fn set_save_property(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    path: String,
+    value: TypedValue,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<PropertyValue>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let file = Path::new(&file_path);
+        paths.validate_save_games_path(file)?;
+        if dry_run.unwrap_or(false) {
+            return preview_set_file_value(file, &path, &value).map(Outcome::DryRun);
+        }
+        set_file_value(&paths, file, &path, &value).map(Outcome::Applied)
+    })
+    .await
+}
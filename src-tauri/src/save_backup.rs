@@ -0,0 +1,429 @@
+//! Save backup module - Versioned copies of saves and MAINSAVE taken before every
+//! destructive write. Backups live in `<app config>/backups/<library>/<archive>/`
+//! as `<id>.sav` plus an `<id>.json` sidecar, and are pruned by the retention
+//! settings in `backup_config.json`.
+
+use crate::common::{
+    add_save_to_mainsave, extract_archive_name, mainsave_lock, preview_mainsave, run_blocking,
+    write_atomic, MainsaveOp,
+};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::lock_save;
+use crate::library_profiles::DEFAULT_LIBRARY;
+use crate::save_diff::{preview_replace, DryRunReport, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Backups root, inside the app config dir
+const BACKUP_DIR: &str = "backups";
+/// Retention settings, stored in the app config dir
+const BACKUP_CONFIG_FILE: &str = "backup_config.json";
+/// Archive name MAINSAVE.sav is backed up under
+pub const MAINSAVE_ARCHIVE: &str = "MAINSAVE";
+/// Suffix soft-deleted saves carry after `.sav`
+const TRASH_SUFFIX: &str = ".trash";
+
+/// Retention limits; `None` disables a limit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    /// Backups kept per save
+    pub max_count: Option<usize>,
+    /// Backups older than this are dropped
+    pub max_age_days: Option<i64>,
+    /// Total size of one library's backups
+    pub max_total_mb: Option<u64>,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_count: Some(20),
+            max_age_days: Some(30),
+            max_total_mb: Some(512),
+        }
+    }
+}
+
+/// One backup (also the content of its `.json` sidecar)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub id: String,
+    pub archive_name: String,
+    /// Path of the backed-up file, relative to SaveGames
+    pub original_path: String,
+    /// Operation that was about to overwrite / delete the file
+    pub reason: String,
+    /// RFC 3339
+    pub created_at: String,
+    pub size: u64,
+}
+
+/// Backups of one save, for the overview
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSummary {
+    pub archive_name: String,
+    pub count: usize,
+    pub latest: String,
+    pub total_size: u64,
+}
+
+fn config_path(paths: &SavePaths) -> PathBuf {
+    paths.app_config_dir().join(BACKUP_CONFIG_FILE)
+}
+
+/// Load retention settings. Missing or corrupt files degrade to the defaults.
+pub fn load_backup_config(paths: &SavePaths) -> BackupConfig {
+    fs::read_to_string(config_path(paths))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Persist retention settings
+pub fn write_backup_config(paths: &SavePaths, config: &BackupConfig) -> AppResult<()> {
+    fs::create_dir_all(paths.app_config_dir())
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize backup config: {}", e))?;
+    fs::write(config_path(paths), json)
+        .map_err(|e| format!("Failed to write backup config: {}", e))?;
+    Ok(())
+}
+
+/// Keep a directory name to plain file name characters
+fn dir_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '(' | ')' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Backups of the library `paths` points at
+fn library_dir(paths: &SavePaths) -> PathBuf {
+    paths
+        .app_config_dir()
+        .join(BACKUP_DIR)
+        .join(dir_name(paths.library().unwrap_or(DEFAULT_LIBRARY)))
+}
+
+fn archive_dir(paths: &SavePaths, archive_name: &str) -> AppResult<PathBuf> {
+    let name = archive_name.trim();
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(AppError::Validation(format!(
+            "Invalid archive name: {}",
+            archive_name
+        )));
+    }
+    Ok(library_dir(paths).join(name))
+}
+
+/// Archive name a save file is backed up under (`X.sav.trash` belongs to `X`)
+pub fn archive_name_of(paths: &SavePaths, path: &Path) -> AppResult<String> {
+    if path == paths.mainsave_path() {
+        return Ok(MAINSAVE_ARCHIVE.to_string());
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file path")?;
+    let file_name = file_name.strip_suffix(TRASH_SUFFIX).unwrap_or(file_name);
+    Ok(extract_archive_name(file_name).to_string())
+}
+
+/// Copy the current bytes of `path` into the backup store before it is
+/// overwritten or deleted. Does nothing when the file doesn't exist yet or
+/// backups are disabled; fails (so the caller aborts) when the copy can't be made.
+pub fn backup_file(paths: &SavePaths, path: &Path, reason: &str) -> AppResult<Option<BackupEntry>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let config = load_backup_config(paths);
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let archive_name = archive_name_of(paths, path)?;
+    let dir = archive_dir(paths, &archive_name)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file for backup: {}", e))?;
+    let now = Local::now();
+    let stamp = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+    // Claim the id with `create_new`, so concurrent backups (other processes
+    // included) of the same save in the same millisecond never share a file
+    let mut id = stamp.clone();
+    let mut n = 1;
+    let mut file = loop {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(format!("{}.sav", id)))
+        {
+            Ok(file) => break file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                id = format!("{}-{}", stamp, n);
+                n += 1;
+            }
+            Err(e) => return Err(format!("Failed to create backup: {}", e).into()),
+        }
+    };
+
+    let original_path = path
+        .strip_prefix(paths.save_games_dir())
+        .unwrap_or_else(|_| Path::new(path.file_name().unwrap_or_default()));
+    let entry = BackupEntry {
+        id: id.clone(),
+        archive_name: archive_name.clone(),
+        original_path: original_path.to_string_lossy().into_owned(),
+        reason: reason.to_string(),
+        created_at: now.to_rfc3339(),
+        size: bytes.len() as u64,
+    };
+
+    if let Err(e) = file.write_all(&bytes) {
+        drop(file);
+        let _ = fs::remove_file(dir.join(format!("{}.sav", id)));
+        return Err(format!("Failed to write backup: {}", e).into());
+    }
+    let meta = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize backup metadata: {}", e))?;
+    fs::write(dir.join(format!("{}.json", id)), meta)
+        .map_err(|e| format!("Failed to write backup metadata: {}", e))?;
+    tracing::info!(
+        "Backed up {:?} as {}/{} ({})",
+        path,
+        archive_name,
+        id,
+        reason
+    );
+
+    // Retention problems must not block the write that asked for the backup
+    if let Err(e) = prune(paths, &config, &archive_name) {
+        tracing::warn!("Backup pruning failed: {}", e);
+    }
+    Ok(Some(entry))
+}
+
+/// Backups of one save, newest first
+pub fn list_backups(paths: &SavePaths, archive_name: &str) -> AppResult<Vec<BackupEntry>> {
+    let dir = archive_dir(paths, archive_name)?;
+    let Ok(read_dir) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut entries: Vec<BackupEntry> = read_dir
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    entries.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(entries)
+}
+
+/// Every save with backups in the current library
+pub fn list_backup_summaries(paths: &SavePaths) -> AppResult<Vec<BackupSummary>> {
+    let Ok(read_dir) = fs::read_dir(library_dir(paths)) else {
+        return Ok(Vec::new());
+    };
+    let mut summaries = Vec::new();
+    for dir in read_dir.flatten().filter(|e| e.path().is_dir()) {
+        let archive_name = dir.file_name().to_string_lossy().into_owned();
+        let entries = list_backups(paths, &archive_name)?;
+        if let Some(latest) = entries.first() {
+            summaries.push(BackupSummary {
+                archive_name,
+                count: entries.len(),
+                latest: latest.created_at.clone(),
+                total_size: entries.iter().map(|e| e.size).sum(),
+            });
+        }
+    }
+    summaries.sort_by(|a, b| b.latest.cmp(&a.latest));
+    Ok(summaries)
+}
+
+/// The `.sav` copy a backup entry describes
+pub fn backup_path(paths: &SavePaths, entry: &BackupEntry) -> AppResult<PathBuf> {
+    Ok(archive_dir(paths, &entry.archive_name)?.join(format!("{}.sav", entry.id)))
+}
+
+fn remove_backup(paths: &SavePaths, entry: &BackupEntry) -> AppResult<()> {
+    let dir = archive_dir(paths, &entry.archive_name)?;
+    fs::remove_file(dir.join(format!("{}.sav", entry.id)))
+        .map_err(|e| format!("Failed to remove backup: {}", e))?;
+    let _ = fs::remove_file(dir.join(format!("{}.json", entry.id)));
+    Ok(())
+}
+
+/// Apply the count / age limits to one save, then the size limit to the whole
+/// library (oldest first). The newest backup of a save is never pruned.
+fn prune(paths: &SavePaths, config: &BackupConfig, archive_name: &str) -> AppResult<()> {
+    let cutoff = config
+        .max_age_days
+        .map(|days| Local::now() - Duration::days(days));
+    for (i, entry) in list_backups(paths, archive_name)?
+        .iter()
+        .enumerate()
+        .skip(1)
+    {
+        let too_many = config.max_count.is_some_and(|max| i >= max.max(1));
+        let too_old = cutoff.is_some_and(|cutoff| {
+            DateTime::parse_from_rfc3339(&entry.created_at).is_ok_and(|t| t < cutoff)
+        });
+        if too_many || too_old {
+            remove_backup(paths, entry)?;
+        }
+    }
+
+    let Some(max_mb) = config.max_total_mb else {
+        return Ok(());
+    };
+    let max_bytes = max_mb.saturating_mul(1024 * 1024);
+    let mut prunable = Vec::new();
+    let mut total = 0u64;
+    for summary in list_backup_summaries(paths)? {
+        let entries = list_backups(paths, &summary.archive_name)?;
+        total += summary.total_size;
+        prunable.extend(entries.into_iter().skip(1));
+    }
+    prunable.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    for entry in prunable {
+        if total <= max_bytes {
+            break;
+        }
+        remove_backup(paths, &entry)?;
+        total = total.saturating_sub(entry.size);
+    }
+    Ok(())
+}
+
+/// The file a backup restores to, and the backed-up bytes
+fn restore_plan(paths: &SavePaths, archive_name: &str, id: &str) -> AppResult<(PathBuf, Vec<u8>)> {
+    let entry = list_backups(paths, archive_name)?
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| {
+            AppError::Validation(format!("Backup not found: {}/{}", archive_name, id))
+        })?;
+    let bytes = fs::read(backup_path(paths, &entry)?)
+        .map_err(|e| format!("Failed to read backup: {}", e))?;
+
+    let relative = entry
+        .original_path
+        .strip_suffix(TRASH_SUFFIX)
+        .unwrap_or(&entry.original_path);
+    let target = paths.save_games_dir().join(relative);
+    paths.validate_save_games_path(&target)?;
+    Ok((target, bytes))
+}
+
+/// Put a backup back where it was taken from (a trashed save comes back as a
+/// plain `.sav`). The file it replaces is backed up first. Returns the restored path.
+pub fn restore_backup(paths: &SavePaths, archive_name: &str, id: &str) -> AppResult<PathBuf> {
+    let (target, bytes) = restore_plan(paths, archive_name, id)?;
+
+    let is_mainsave = archive_name == MAINSAVE_ARCHIVE;
+    // MAINSAVE writers hold the MAINSAVE lock; a save only needs its own
+    let _mainsave_lock = is_mainsave.then(|| mainsave_lock(paths)).transpose()?;
+    let _save_lock = (!is_mainsave)
+        .then(|| lock_save(paths, &target))
+        .transpose()?;
+
+    backup_file(paths, &target, "restore")?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create target directory: {}", e))?;
+    }
+    write_atomic(&target, &bytes)?;
+    tracing::info!("Restored backup {}/{} to {:?}", archive_name, id, target);
+
+    if !is_mainsave && !paths.is_hidden(&target) {
+        add_save_to_mainsave(paths, archive_name)?;
+    }
+    Ok(target)
+}
+
+/// Dry run of [`restore_backup`]
+pub fn preview_restore_backup(
+    paths: &SavePaths,
+    archive_name: &str,
+    id: &str,
+) -> AppResult<DryRunReport> {
+    let (target, bytes) = restore_plan(paths, archive_name, id)?;
+    let mut report = preview_replace(&target, &bytes);
+    if archive_name != MAINSAVE_ARCHIVE && !paths.is_hidden(&target) {
+        report.push_diff(
+            paths.mainsave_path().to_string_lossy(),
+            preview_mainsave(paths, &[MainsaveOp::Add(archive_name.to_string())])?,
+        );
+    }
+    Ok(report)
+}
+
+/// List backups of one save (`MAINSAVE` for MAINSAVE.sav), newest first.
+#[tauri::command]
+pub async fn list_save_backups(
+    state: tauri::State<'_, SavePathsState>,
+    archive_name: String,
+) -> AppResult<Vec<BackupEntry>> {
+    let paths = state.current()?;
+    run_blocking(move || list_backups(&paths, &archive_name)).await
+}
+
+/// List every save that has backups in the current library.
+#[tauri::command]
+pub async fn list_all_backups(
+    state: tauri::State<'_, SavePathsState>,
+) -> AppResult<Vec<BackupSummary>> {
+    let paths = state.current()?;
+    run_blocking(move || list_backup_summaries(&paths)).await
+}
+
+/// Restore one backup over the current file. Returns the restored path.
+#[tauri::command]
+pub async fn restore_save_backup(
+    state: tauri::State<'_, SavePathsState>,
+    archive_name: String,
+    backup_id: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<String>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return preview_restore_backup(&paths, &archive_name, &backup_id).map(Outcome::DryRun);
+        }
+        restore_backup(&paths, &archive_name, &backup_id)
+            .map(|path| Outcome::Applied(path.to_string_lossy().into_owned()))
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_backup_config(state: tauri::State<'_, SavePathsState>) -> AppResult<BackupConfig> {
+    let paths = state.current()?;
+    run_blocking(move || Ok(load_backup_config(&paths))).await
+}
+
+#[tauri::command]
+pub async fn set_backup_config(
+    state: tauri::State<'_, SavePathsState>,
+    config: BackupConfig,
+) -> AppResult<()> {
+    let paths = state.current()?;
+    run_blocking(move || write_backup_config(&paths, &config)).await
+}
@@ -2,34 +2,29 @@
 //! Extracted from save_commands.rs for better modularity
 
 use crate::cli_handlers;
-use crate::common::{get_app_config_dir, get_save_games_dir, validate_save_games_path};
+use crate::common::run_blocking;
 use crate::error::AppResult;
+use crate::etb_save::{is_real_eos_key, pure_player_id};
 use crate::player_data;
+use crate::save_diff::Outcome;
 use crate::save_editor;
+use crate::save_paths::{SavePaths, SavePathsState};
 use serde_json::json;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-/// Helper: run a blocking closure via tokio::task::spawn_blocking,
-/// mapping the join error into an AppResult.
-async fn run_blocking<F, T>(f: F) -> AppResult<T>
-where
-    F: FnOnce() -> AppResult<T> + Send + 'static,
-    T: Send + 'static,
-{
-    tokio::task::spawn_blocking(f)
-        .await
-        .map_err(|e| format!("Task was cancelled: {}", e))?
-}
-
 /// Get player data from a save file.
 #[tauri::command]
-pub async fn get_player_data(file_path: String) -> AppResult<Value> {
+pub async fn get_player_data(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+) -> AppResult<Value> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&file_path);
-        validate_save_games_path(path)?;
+        paths.validate_save_games_path(path)?;
         let save = cli_handlers::parse_sav_file(path)?;
 
         let (ids, sanities, inventories) = player_data::extract_player_data(&save);
@@ -48,56 +43,28 @@ pub async fn get_player_data(file_path: String) -> AppResult<Value> {
 /// rescan every save on each lookup.
 const PLAYER_ID_MAP_FILE: &str = "player_id_map.json";
 
-fn player_id_map_path() -> AppResult<PathBuf> {
-    Ok(get_app_config_dir()?.join(PLAYER_ID_MAP_FILE))
+fn player_id_map_path(paths: &SavePaths) -> PathBuf {
+    paths.app_config_dir().join(PLAYER_ID_MAP_FILE)
 }
 
 /// Load the cached player id map. Failures (missing/corrupt file) degrade to empty.
-fn load_player_id_map() -> HashMap<String, String> {
-    let Ok(path) = player_id_map_path() else {
-        return HashMap::new();
-    };
-    let Ok(content) = fs::read_to_string(&path) else {
+fn load_player_id_map(paths: &SavePaths) -> HashMap<String, String> {
+    let Ok(content) = fs::read_to_string(player_id_map_path(paths)) else {
         return HashMap::new();
     };
     serde_json::from_str(&content).unwrap_or_default()
 }
 
 /// Persist the player id map (best-effort; a write failure only loses the cache).
-fn save_player_id_map(map: &HashMap<String, String>) {
-    let Ok(dir) = get_app_config_dir() else {
-        return;
-    };
+fn save_player_id_map(paths: &SavePaths, map: &HashMap<String, String>) {
+    let dir = paths.app_config_dir();
     if !dir.exists() {
-        let _ = fs::create_dir_all(&dir);
+        let _ = fs::create_dir_all(dir);
     }
     let Ok(json) = serde_json::to_string_pretty(map) else {
         return;
     };
-    let _ = fs::write(dir.join(PLAYER_ID_MAP_FILE), json);
-}
-
-/// Pure player id: strip the `_+_|<suffix>` (online) or `-<15 chars>` (offline) part.
-fn pure_player_key(id: &str) -> String {
-    if let Some(idx) = id.find("_+_|") {
-        id[..idx].to_string()
-    } else if let Some(idx) = id.find('-') {
-        id[..idx].to_string()
-    } else {
-        id.to_string()
-    }
-}
-
-/// Whether the key carries a real EOS account id suffix: 32 hex chars and not the
-/// all-zeros placeholder an earlier app version wrote. The game ignores keys with a
-/// placeholder EOS id, so a create flow must never reuse one.
-fn is_real_eos_key(key: &str) -> bool {
-    if let Some(idx) = key.find("_+_|") {
-        let suffix = &key[idx + 4..];
-        suffix.len() == 32 && suffix != "00000000000000000000000000000000"
-    } else {
-        false
-    }
+    let _ = fs::write(player_id_map_path(paths), json);
 }
 
 /// Resolve the full PlayerData key (`<steam id>_+_|<EOS PUID>`) for a player id.
@@ -114,13 +81,13 @@ fn is_real_eos_key(key: &str) -> bool {
 ///   discovery is persisted for next time.
 /// - Returns `None` when no real EOS key can be found anywhere (e.g. a player
 ///   that has never been saved — the game has no PUID for it yet).
-pub(crate) fn resolve_player_full_key(player_id: &str) -> Option<String> {
+pub fn resolve_player_full_key(paths: &SavePaths, player_id: &str) -> Option<String> {
     // Already a real key — nothing to resolve.
     if is_real_eos_key(player_id) {
         return Some(player_id.to_string());
     }
 
-    let mut map = load_player_id_map();
+    let mut map = load_player_id_map(paths);
     // Cache hit must still pass the real-key check: older app versions persisted
     // all-zeros placeholders that the game ignores.
     if let Some(full) = map.get(player_id) {
@@ -131,23 +98,22 @@ pub(crate) fn resolve_player_full_key(player_id: &str) -> Option<String> {
 
     // Scan existing saves for a real EOS-suffixed entry belonging to this id.
     let mut discovered: Option<String> = None;
-    if let Ok(save_games_dir) = get_save_games_dir() {
-        if save_games_dir.exists() {
-            let iter = walkdir::WalkDir::new(&save_games_dir)
-                .max_depth(1)
-                .into_iter()
-                .filter_map(Result::ok)
-                .filter(|e| e.file_type().is_file())
-                .filter(|e| e.path().extension().is_some_and(|x| x == "sav"));
-
-            'scan: for entry in iter {
-                if let Ok(save) = cli_handlers::parse_sav_file(entry.path()) {
-                    let (ids, _, _) = player_data::extract_player_data(&save);
-                    for id in ids {
-                        if is_real_eos_key(&id) && pure_player_key(&id) == player_id {
-                            discovered = Some(id);
-                            break 'scan;
-                        }
+    let save_games_dir = paths.save_games_dir();
+    if save_games_dir.exists() {
+        let iter = walkdir::WalkDir::new(save_games_dir)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| e.path().extension().is_some_and(|x| x == "sav"));
+
+        'scan: for entry in iter {
+            if let Ok(save) = cli_handlers::parse_sav_file(entry.path()) {
+                let (ids, _, _) = player_data::extract_player_data(&save);
+                for id in ids {
+                    if is_real_eos_key(&id) && pure_player_id(&id) == player_id {
+                        discovered = Some(id);
+                        break 'scan;
                     }
                 }
             }
@@ -156,7 +122,7 @@ pub(crate) fn resolve_player_full_key(player_id: &str) -> Option<String> {
 
     if let Some(full) = discovered {
         map.insert(player_id.to_string(), full.clone());
-        save_player_id_map(&map);
+        save_player_id_map(paths, &map);
         Some(full)
     } else {
         None
@@ -169,9 +135,13 @@ pub(crate) fn resolve_player_full_key(player_id: &str) -> Option<String> {
 /// be synthesized, so it must be read back from a save (or cache) that already contains it.
 /// Returns a map: pure steam id -> full key (only for players found in saves/cache).
 #[tauri::command]
-pub async fn get_player_unique_ids(steam_ids: Vec<String>) -> AppResult<Value> {
+pub async fn get_player_unique_ids(
+    state: tauri::State<'_, SavePathsState>,
+    steam_ids: Vec<String>,
+) -> AppResult<Value> {
+    let paths = state.current()?;
     run_blocking(move || {
-        let mut map = load_player_id_map();
+        let mut map = load_player_id_map(&paths);
 
         // Only scan saves for ids missing from the cache
         let missing: Vec<String> = steam_ids
@@ -182,25 +152,22 @@ pub async fn get_player_unique_ids(steam_ids: Vec<String>) -> AppResult<Value> {
 
         let mut discovered: HashMap<String, String> = HashMap::new();
 
-        if !missing.is_empty() {
-            if let Ok(save_games_dir) = get_save_games_dir() {
-                if save_games_dir.exists() {
-                    let iter = walkdir::WalkDir::new(&save_games_dir)
-                        .max_depth(1)
-                        .into_iter()
-                        .filter_map(Result::ok)
-                        .filter(|e| e.file_type().is_file())
-                        .filter(|e| e.path().extension().is_some_and(|x| x == "sav"));
+        let save_games_dir = paths.save_games_dir();
+        if !missing.is_empty() && save_games_dir.exists() {
+            let iter = walkdir::WalkDir::new(save_games_dir)
+                .max_depth(1)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| e.path().extension().is_some_and(|x| x == "sav"));
 
-                    for entry in iter {
-                        if let Ok(save) = cli_handlers::parse_sav_file(entry.path()) {
-                            let (ids, _, _) = player_data::extract_player_data(&save);
-                            for id in ids {
-                                let pure = pure_player_key(&id);
-                                if missing.contains(&pure) && is_real_eos_key(&id) {
-                                    discovered.insert(pure, id);
-                                }
-                            }
+            for entry in iter {
+                if let Ok(save) = cli_handlers::parse_sav_file(entry.path()) {
+                    let (ids, _, _) = player_data::extract_player_data(&save);
+                    for id in ids {
+                        let pure = pure_player_id(&id).to_string();
+                        if missing.contains(&pure) && is_real_eos_key(&id) {
+                            discovered.insert(pure, id);
                         }
                     }
                 }
@@ -211,7 +178,7 @@ pub async fn get_player_unique_ids(steam_ids: Vec<String>) -> AppResult<Value> {
         for (pure, full) in discovered {
             map.insert(pure, full);
         }
-        save_player_id_map(&map);
+        save_player_id_map(&paths, &map);
 
         // Build result map, filtering out keys without a real EOS suffix. The local cache
         // may still hold all-zeros placeholders written by an older version, so re-validate
@@ -232,17 +199,30 @@ pub async fn get_player_unique_ids(steam_ids: Vec<String>) -> AppResult<Value> {
 
 /// Unlock all hub doors in a save file.
 #[tauri::command]
-pub async fn unlock_all_hub_doors(file_path: String) -> AppResult<String> {
+pub async fn unlock_all_hub_doors(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<String>> {
+    let paths = state.current()?;
     run_blocking(move || {
-        validate_save_games_path(Path::new(&file_path))?;
-        save_editor::unlock_all_hub_doors(&file_path)
+        paths.validate_save_games_path(Path::new(&file_path))?;
+        if dry_run.unwrap_or(false) {
+            return save_editor::preview_unlock_hub_doors(&paths, &file_path).map(Outcome::DryRun);
+        }
+        save_editor::unlock_all_hub_doors(&paths, &file_path).map(Outcome::Applied)
     })
     .await
 }
 
 /// Handle save file editing from JSON input.
 #[tauri::command]
-pub async fn handle_edit_save(json_input: Value) -> AppResult<String> {
+pub async fn handle_edit_save(
+    state: tauri::State<'_, SavePathsState>,
+    json_input: Value,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<String>> {
+    let paths = state.current()?;
     run_blocking(move || {
         let save_data = json_input
             .get("saveData")
@@ -253,7 +233,7 @@ pub async fn handle_edit_save(json_input: Value) -> AppResult<String> {
             .and_then(Value::as_str)
             .ok_or("Missing or invalid 'outputDir' in saveData")?;
 
-        validate_save_games_path(Path::new(output_dir))?;
+        paths.validate_save_games_path(Path::new(output_dir))?;
 
         let json_data = save_data
             .get("jsonData")
@@ -261,7 +241,11 @@ pub async fn handle_edit_save(json_input: Value) -> AppResult<String> {
             .ok_or("Missing or invalid 'jsonData' in saveData")?;
 
         let json_value = Value::Object(json_data.clone());
-        save_editor::edit_save_file(&json_value, output_dir)
+        if dry_run.unwrap_or(false) {
+            return save_editor::preview_edit_save(&paths, &json_value, output_dir)
+                .map(Outcome::DryRun);
+        }
+        save_editor::edit_save_file(&paths, &json_value, output_dir).map(Outcome::Applied)
     })
     .await
 }
@@ -0,0 +1,510 @@
+//! Save bulk module - Transactional delete / soft-delete / restore / toggle of many saves
+//! Every item is validated before anything is touched. File renames are journaled,
+//! MAINSAVE is read and written once under the MAINSAVE lock, and a failure at any
+//! step renames everything back. Permanent deletes are staged as renames and only
+//! removed once MAINSAVE has been written; staged files a crash leaves behind are
+//! settled by `recover_staged_deletes`.
+
+use crate::common::{
+    apply_mainsave_op, extract_archive_name, get_visible_saves_set, mainsave_lock,
+    preview_mainsave, read_mainsave, run_blocking, visible_saves_of, write_mainsave, MainsaveOp,
+};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::{lock_save, lock_saves};
+use crate::save_backup::backup_file;
+use crate::save_deleter::deletable_file_name;
+use crate::save_diff::{DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_trash;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use walkdir::WalkDir;
+
+/// Extension a permanently deleted save is parked under until the batch commits
+const STAGED_DELETE_EXTENSION: &str = "sav.deleting";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BulkAction {
+    Delete,
+    SoftDelete,
+    /// Items are the original `.sav` paths or the `.sav.trash` files
+    Restore,
+    ToggleVisibility,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemStatus {
+    /// Done and committed
+    Applied,
+    /// This item failed, so the batch was rolled back
+    Failed,
+    /// Undone because another step failed
+    RolledBack,
+    /// Not attempted: the batch stopped before it, or it was listed twice
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub path: String,
+    pub status: ItemStatus,
+    /// Where the file ended up (restores may get a new name)
+    pub new_path: Option<String>,
+    /// Visibility after a toggle
+    pub visible: Option<bool>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkReport {
+    pub action: BulkAction,
+    /// Whether the batch went through; `false` means nothing changed
+    pub committed: bool,
+    pub mainsave_written: bool,
+    /// Batch-level failure: MAINSAVE could not be written (nothing committed), or
+    /// could not be read (committed, but MAINSAVE still lists the old names)
+    pub error: Option<String>,
+    pub items: Vec<BulkItemResult>,
+}
+
+impl BulkReport {
+    fn new(action: BulkAction, files: &[String]) -> Self {
+        Self {
+            action,
+            committed: false,
+            mainsave_written: false,
+            error: None,
+            items: files
+                .iter()
+                .map(|path| BulkItemResult {
+                    path: path.clone(),
+                    status: ItemStatus::Skipped,
+                    new_path: None,
+                    visible: None,
+                    error: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn fail(&mut self, index: usize, error: impl ToString) {
+        let item = &mut self.items[index];
+        item.status = ItemStatus::Failed;
+        item.error = Some(error.to_string());
+    }
+}
+
+/// What one item will do
+struct PlannedItem {
+    index: usize,
+    /// `from -> to`; staged deletes rename too
+    rename: Option<(PathBuf, PathBuf)>,
+    mainsave_op: Option<MainsaveOp>,
+    /// Visibility after a toggle
+    visible: Option<bool>,
+}
+
+fn require_file(path: &Path) -> AppResult<()> {
+    if !path.is_file() {
+        return Err(format!("File does not exist: {}", path.display()).into());
+    }
+    Ok(())
+}
+
+fn plan_item(
+    paths: &SavePaths,
+    action: BulkAction,
+    index: usize,
+    path: &Path,
+    visible: Option<&HashSet<String>>,
+    claimed: &mut HashSet<PathBuf>,
+) -> AppResult<PlannedItem> {
+    let mut plan = PlannedItem {
+        index,
+        rename: None,
+        mainsave_op: None,
+        visible: None,
+    };
+    match action {
+        BulkAction::Delete => {
+            let archive_name = extract_archive_name(deletable_file_name(paths, path, "deleted")?);
+            require_file(path)?;
+            let staged = path.with_extension(STAGED_DELETE_EXTENSION);
+            if staged.exists() {
+                return Err(format!("Staging file already exists: {}", staged.display()).into());
+            }
+            plan.mainsave_op = Some(MainsaveOp::Remove(archive_name.to_string()));
+            plan.rename = Some((path.to_path_buf(), staged));
+        }
+        BulkAction::SoftDelete => {
+            let archive_name =
+                extract_archive_name(deletable_file_name(paths, path, "soft-deleted")?);
+            require_file(path)?;
+            let trash_path = path.with_extension("sav.trash");
+            if trash_path.exists() {
+                return Err(AppError::Validation(format!(
+                    "{} is already in the trash",
+                    archive_name
+                )));
+            }
+            plan.mainsave_op = Some(MainsaveOp::Remove(archive_name.to_string()));
+            plan.rename = Some((path.to_path_buf(), trash_path));
+        }
+        BulkAction::Restore => {
+            let trash_path = save_trash::trash_path_for(path);
+            save_trash::validate_trash_path(paths, &trash_path)?;
+            let target = save_trash::restore_target(paths, &trash_path, None, claimed)?;
+            claimed.insert(target.clone());
+            // Saves in hidden subfolders stay out of MAINSAVE
+            if !paths.is_hidden(&target) {
+                let file_name = target.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                plan.mainsave_op =
+                    Some(MainsaveOp::Add(extract_archive_name(file_name).to_string()));
+            }
+            plan.rename = Some((trash_path, target));
+        }
+        BulkAction::ToggleVisibility => {
+            let visible = visible.ok_or("MAINSAVE.sav can't be read")?;
+            require_file(path)?;
+            paths.validate_save_games_path(path)?;
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or("Invalid filename")?;
+            let archive_name = extract_archive_name(file_name).to_string();
+            let is_visible = visible.contains(&archive_name);
+            plan.mainsave_op = Some(if is_visible {
+                MainsaveOp::Remove(archive_name)
+            } else {
+                MainsaveOp::Add(archive_name)
+            });
+            plan.visible = Some(!is_visible);
+        }
+    }
+    Ok(plan)
+}
+
+/// Plan every item; `None` (with the failing item marked) when any can't be done
+fn plan_all(
+    paths: &SavePaths,
+    action: BulkAction,
+    files: &[String],
+    visible: Option<&HashSet<String>>,
+    report: &mut BulkReport,
+) -> Option<Vec<PlannedItem>> {
+    let mut seen = HashSet::new();
+    let mut claimed = HashSet::new();
+    let mut plans = Vec::with_capacity(files.len());
+    for (index, file) in files.iter().enumerate() {
+        if !seen.insert(file.as_str()) {
+            report.items[index].error = Some("Listed more than once".into());
+            continue;
+        }
+        match plan_item(paths, action, index, Path::new(file), visible, &mut claimed) {
+            Ok(plan) => plans.push(plan),
+            Err(e) => {
+                report.fail(index, e);
+                return None;
+            }
+        }
+    }
+    Some(plans)
+}
+
+/// Settle `.sav.deleting` files a crash left between staging and removal. A save
+/// MAINSAVE still lists (or any save, when MAINSAVE can't be read) never had its
+/// batch committed and is renamed back when its name is free; the rest are
+/// removed, which loses nothing since deletes are backed up before staging.
+/// Returns how many files were settled.
+pub fn recover_staged_deletes(paths: &SavePaths) -> usize {
+    let suffix = format!(".{}", STAGED_DELETE_EXTENSION);
+    let staged: Vec<PathBuf> = WalkDir::new(paths.save_games_dir())
+        .max_depth(2)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file() && e.file_name().to_string_lossy().ends_with(&suffix))
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut settled = 0;
+    for staged_path in staged {
+        // `x.sav.deleting` -> `x.sav`
+        let original = staged_path.with_extension("");
+        let result = (|| -> AppResult<bool> {
+            // A running batch holds this lock until it has removed its staged files
+            let _lock = lock_save(paths, &original)?;
+            if !staged_path.is_file() {
+                return Ok(false);
+            }
+            let file_name = original
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let listed = match get_visible_saves_set(paths) {
+                Ok(visible) => visible.contains(extract_archive_name(file_name)),
+                Err(_) => true,
+            };
+            if listed && !original.exists() {
+                fs::rename(&staged_path, &original)?;
+                tracing::info!("Recovered uncommitted delete {:?}", original);
+            } else {
+                fs::remove_file(&staged_path)?;
+                tracing::info!("Removed leftover staged delete {:?}", staged_path);
+            }
+            Ok(true)
+        })();
+        match result {
+            Ok(true) => settled += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to settle {:?}: {}", staged_path, e),
+        }
+    }
+    settled
+}
+
+/// Undo the renames in `journal` (newest first)
+fn roll_back(journal: &[(usize, PathBuf, PathBuf)], report: &mut BulkReport) {
+    for (index, from, to) in journal.iter().rev() {
+        let item = &mut report.items[*index];
+        match fs::rename(to, from) {
+            Ok(()) => {
+                if item.status != ItemStatus::Failed {
+                    item.status = ItemStatus::RolledBack;
+                }
+            }
+            Err(e) => {
+                tracing::error!("Rollback of {:?} -> {:?} failed: {}", from, to, e);
+                item.status = ItemStatus::Failed;
+                item.error = Some(format!(
+                    "Rollback failed, file left at {}: {}",
+                    to.display(),
+                    e
+                ));
+            }
+        }
+    }
+}
+
+/// Run `action` on every file as one transaction
+pub fn run_bulk(paths: &SavePaths, action: BulkAction, files: &[String]) -> AppResult<BulkReport> {
+    let mut report = BulkReport::new(action, files);
+    // Saves before MAINSAVE, the order the single-save helpers take them in
+    let mainsave_path = paths.mainsave_path();
+    let _save_locks = lock_saves(
+        paths,
+        files
+            .iter()
+            .map(Path::new)
+            .filter(|path| *path != mainsave_path),
+    )?;
+    let _lock = mainsave_lock(paths)?;
+
+    // Missing or unreadable MAINSAVE: the file operations still go ahead (as the
+    // single-save helpers do), only toggling needs it
+    let (mut mainsave, mainsave_error) = match read_mainsave(paths) {
+        Ok(mainsave) => (Some(mainsave), None),
+        Err(e) => {
+            tracing::warn!("Bulk {:?} without MAINSAVE: {}", action, e);
+            (None, Some(e))
+        }
+    };
+    let visible = mainsave.as_ref().map(visible_saves_of);
+    let Some(plans) = plan_all(paths, action, files, visible.as_ref(), &mut report) else {
+        return Ok(report);
+    };
+
+    let mut journal: Vec<(usize, PathBuf, PathBuf)> = Vec::new();
+    for plan in &plans {
+        let Some((from, to)) = &plan.rename else {
+            continue;
+        };
+        let step = (|| -> AppResult<()> {
+            if action == BulkAction::Delete {
+                backup_file(paths, from, "delete")?;
+            }
+            fs::rename(from, to).map_err(|e| format!("Failed to rename file: {}", e))?;
+            Ok(())
+        })();
+        if let Err(e) = step {
+            report.fail(plan.index, e);
+            roll_back(&journal, &mut report);
+            return Ok(report);
+        }
+        journal.push((plan.index, from.clone(), to.clone()));
+    }
+
+    if let Some(mainsave) = mainsave.as_mut() {
+        let mut changed = false;
+        for op in plans.iter().filter_map(|p| p.mainsave_op.as_ref()) {
+            changed |= apply_mainsave_op(mainsave, op);
+        }
+        if changed {
+            if let Err(e) = write_mainsave(paths, mainsave) {
+                report.error = Some(e.to_string());
+                for plan in &plans {
+                    report.items[plan.index].status = ItemStatus::RolledBack;
+                }
+                roll_back(&journal, &mut report);
+                return Ok(report);
+            }
+            report.mainsave_written = true;
+        }
+    } else if plans.iter().any(|p| p.mainsave_op.is_some()) {
+        // The files still change; say that MAINSAVE didn't follow
+        report.error = mainsave_error.map(|e| e.to_string());
+    }
+
+    // Committed: nothing below is rolled back
+    report.committed = true;
+    for plan in &plans {
+        let item = &mut report.items[plan.index];
+        item.status = ItemStatus::Applied;
+        item.visible = plan.visible;
+        let Some((_, to)) = &plan.rename else {
+            continue;
+        };
+        match action {
+            BulkAction::Delete => {
+                if let Err(e) = fs::remove_file(to) {
+                    tracing::warn!("Failed to remove staged delete {:?}: {}", to, e);
+                    item.error = Some(format!("Staged file left at {}: {}", to.display(), e));
+                }
+            }
+            BulkAction::SoftDelete => {
+                save_trash::record_trashed(paths, to);
+                item.new_path = Some(to.to_string_lossy().into_owned());
+            }
+            _ => item.new_path = Some(to.to_string_lossy().into_owned()),
+        }
+    }
+    tracing::info!(
+        "Bulk {:?}: {} item(s), MAINSAVE {}",
+        action,
+        plans.len(),
+        if report.mainsave_written {
+            "written once"
+        } else {
+            "unchanged"
+        }
+    );
+    Ok(report)
+}
+
+/// Dry run of [`run_bulk`]
+pub fn preview_bulk(
+    paths: &SavePaths,
+    action: BulkAction,
+    files: &[String],
+) -> AppResult<DryRunReport> {
+    let visible = {
+        let _lock = mainsave_lock(paths)?;
+        read_mainsave(paths).ok().map(|m| visible_saves_of(&m))
+    };
+    let mut bulk = BulkReport::new(action, files);
+    let Some(plans) = plan_all(paths, action, files, visible.as_ref(), &mut bulk) else {
+        let failed = bulk.items.iter().find(|i| i.status == ItemStatus::Failed);
+        return Err(AppError::Validation(failed.map_or_else(
+            || "Bulk operation can't be planned".into(),
+            |i| format!("{}: {}", i.path, i.error.as_deref().unwrap_or_default()),
+        )));
+    };
+
+    let mut report = DryRunReport::default();
+    for plan in &plans {
+        match &plan.rename {
+            Some((from, _)) if action == BulkAction::Delete => report.files.push(FileOp::Delete {
+                path: from.to_string_lossy().into_owned(),
+            }),
+            Some((from, to)) => report.files.push(FileOp::Rename {
+                from: from.to_string_lossy().into_owned(),
+                to: to.to_string_lossy().into_owned(),
+            }),
+            None => {}
+        }
+    }
+    report.notes.extend(
+        bulk.items
+            .iter()
+            .filter_map(|i| Some(format!("{}: {}", i.path, i.error.as_deref()?))),
+    );
+    let ops: Vec<MainsaveOp> = plans.into_iter().filter_map(|p| p.mainsave_op).collect();
+    report.push_diff(
+        paths.mainsave_path().to_string_lossy(),
+        preview_mainsave(paths, &ops)?,
+    );
+    Ok(report)
+}
+
+async fn bulk_command(
+    paths: Arc<SavePaths>,
+    action: BulkAction,
+    file_paths: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<BulkReport>> {
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return preview_bulk(&paths, action, &file_paths).map(Outcome::DryRun);
+        }
+        run_bulk(&paths, action, &file_paths).map(Outcome::Applied)
+    })
+    .await
+}
+
+/// Permanently delete many saves in one transaction.
+#[tauri::command]
+pub async fn bulk_delete_files(
+    state: tauri::State<'_, SavePathsState>,
+    file_paths: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<BulkReport>> {
+    bulk_command(state.current()?, BulkAction::Delete, file_paths, dry_run).await
+}
+
+/// Move many saves to the trash in one transaction.
+#[tauri::command]
+pub async fn bulk_soft_delete_files(
+    state: tauri::State<'_, SavePathsState>,
+    file_paths: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<BulkReport>> {
+    bulk_command(
+        state.current()?,
+        BulkAction::SoftDelete,
+        file_paths,
+        dry_run,
+    )
+    .await
+}
+
+/// Restore many trashed saves in one transaction; taken names get a free `(n)` name.
+#[tauri::command]
+pub async fn bulk_restore_files(
+    state: tauri::State<'_, SavePathsState>,
+    file_paths: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<BulkReport>> {
+    bulk_command(state.current()?, BulkAction::Restore, file_paths, dry_run).await
+}
+
+/// Toggle MAINSAVE visibility of many saves with a single MAINSAVE write.
+#[tauri::command]
+pub async fn bulk_toggle_visibility(
+    state: tauri::State<'_, SavePathsState>,
+    file_paths: Vec<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<BulkReport>> {
+    bulk_command(
+        state.current()?,
+        BulkAction::ToggleVisibility,
+        file_paths,
+        dry_run,
+    )
+    .await
+}
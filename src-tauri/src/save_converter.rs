@@ -2,33 +2,29 @@
 //! Extracted from save_commands.rs for better modularity
 
 use crate::cli_handlers;
-use crate::common::validate_save_games_path;
+use crate::common::{run_blocking, write_atomic};
 use crate::error::AppResult;
+use crate::file_lock::lock_save;
 use crate::new_save;
+use crate::save_backup::backup_file;
+use crate::save_diff::{preview_replace, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_verify::verified_bytes;
 use serde_json::json;
 use serde_json::Value;
 use std::fs;
-use std::io::{BufWriter, Write};
 use std::path::Path;
 
-/// Helper: run a blocking closure via tokio::task::spawn_blocking,
-/// mapping the join error into an AppResult.
-async fn run_blocking<F, T>(f: F) -> AppResult<T>
-where
-    F: FnOnce() -> AppResult<T> + Send + 'static,
-    T: Send + 'static,
-{
-    tokio::task::spawn_blocking(f)
-        .await
-        .map_err(|e| format!("Task was cancelled: {}", e))?
-}
-
 /// Convert a .sav file to JSON format for editing.
 #[tauri::command]
-pub async fn convert_sav_to_json(file_path: String) -> AppResult<Value> {
+pub async fn convert_sav_to_json(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+) -> AppResult<Value> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&file_path);
-        validate_save_games_path(path)?;
+        paths.validate_save_games_path(path)?;
         if !path.exists() {
             return Err(format!("File does not exist: {}", file_path).into());
         }
@@ -37,7 +33,7 @@ pub async fn convert_sav_to_json(file_path: String) -> AppResult<Value> {
         // NOTE (uesave 0.7): serialize directly to a string to preserve field order
         // (header → schemas → root → extra); serde_json::to_value would reorder keys
         // alphabetically (BTreeMap) and break Deserialize on the way back.
-        let json_string = serde_json::to_string_pretty(&save)
+        let json_string = serde_json::to_string_pretty(save.as_save())
             .map_err(|e| format!("JSON formatting failed: {}", e))?;
 
         Ok(json!({
@@ -48,71 +44,87 @@ pub async fn convert_sav_to_json(file_path: String) -> AppResult<Value> {
     .await
 }
 
+/// Check the output path and rebuild verified .sav bytes from the JSON
+fn json_to_sav_bytes(
+    paths: &SavePaths,
+    json_content: &str,
+    output_path: &str,
+) -> AppResult<Vec<u8>> {
+    let out_path = Path::new(output_path);
+    paths.validate_save_games_path(out_path)?;
+    if !output_path.to_lowercase().ends_with(".sav") {
+        return Err("Output file must be .sav".to_string().into());
+    }
+
+    // Verify output directory exists
+    if let Some(parent) = out_path.parent() {
+        if !parent.exists() {
+            return Err(format!("Output directory does not exist: {}", parent.display()).into());
+        }
+    }
+
+    // Parse JSON content
+    let json_value: Value =
+        serde_json::from_str(json_content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    // Validate JSON structure
+    if json_value.get("root").is_none() {
+        return Err("JSON data missing required root field".to_string().into());
+    }
+    if json_value.get("schemas").is_none() {
+        return Err(
+            "JSON data missing required schemas field (regenerate with convert_sav_to_json)"
+                .to_string()
+                .into(),
+        );
+    }
+
+    // Rebuild Save object from JSON string (preserves field order for uesave 0.7)
+    let save: uesave::Save = serde_json::from_str(json_content)
+        .map_err(|e| format!("Failed to rebuild Save object from JSON: {}", e))?;
+
+    // Serialize and verify before creating the output file
+    verified_bytes(&save, None, output_path)
+}
+
 /// Convert JSON content back to .sav format.
 #[tauri::command]
-pub async fn convert_json_to_sav(json_content: String, output_path: String) -> AppResult<Value> {
+pub async fn convert_json_to_sav(
+    state: tauri::State<'_, SavePathsState>,
+    json_content: String,
+    output_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<Value>> {
+    let paths = state.current()?;
     run_blocking(move || {
+        let bytes = json_to_sav_bytes(&paths, &json_content, &output_path)?;
         let out_path = Path::new(&output_path);
-        validate_save_games_path(out_path)?;
-        if !output_path.to_lowercase().ends_with(".sav") {
-            return Err("Output file must be .sav".to_string().into());
-        }
-
-        // Verify output directory exists
-        if let Some(parent) = Path::new(&output_path).parent() {
-            if !parent.exists() {
-                return Err(
-                    format!("Output directory does not exist: {}", parent.display()).into(),
-                );
-            }
-        }
-
-        // Parse JSON content
-        let json_value: Value = serde_json::from_str(&json_content)
-            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-        // Validate JSON structure
-        if json_value.get("root").is_none() {
-            return Err("JSON data missing required root field".to_string().into());
+        if dry_run.unwrap_or(false) {
+            return Ok(Outcome::DryRun(preview_replace(out_path, &bytes)));
         }
-        if json_value.get("schemas").is_none() {
-            return Err(
-                "JSON data missing required schemas field (regenerate with convert_sav_to_json)"
-                    .to_string()
-                    .into(),
-            );
-        }
-
-        // Rebuild Save object from JSON string (preserves field order for uesave 0.7)
-        let save: uesave::Save = serde_json::from_str(&json_content)
-            .map_err(|e| format!("Failed to rebuild Save object from JSON: {}", e))?;
-
-        // Create output file (using buffered write)
-        let file = fs::File::create(&output_path)
-            .map_err(|e| format!("Failed to create output file: {}", e))?;
-        let mut writer = BufWriter::with_capacity(16384, file);
 
-        save.write(&mut writer)
-            .map_err(|e| format!("Failed to write sav file: {:?}", e))?;
+        let _lock = lock_save(&paths, out_path)?;
+        backup_file(&paths, out_path, "convert")?;
+        write_atomic(out_path, &bytes)?;
 
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush buffer: {}", e))?;
-
-        Ok(json!({
+        Ok(Outcome::Applied(json!({
             "success": true,
             "message": "JSON data successfully converted and saved to sav file"
-        }))
+        })))
     })
     .await
 }
 
 /// Ensure a directory exists within the save games path.
 #[tauri::command]
-pub async fn ensure_dir_exists(path: String) -> AppResult<()> {
+pub async fn ensure_dir_exists(
+    state: tauri::State<'_, SavePathsState>,
+    path: String,
+) -> AppResult<()> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&path);
-        validate_save_games_path(path)?;
+        paths.validate_save_games_path(path)?;
 
         if !path.exists() {
             fs::create_dir_all(path).map_err(|e| e.to_string())?;
@@ -124,6 +136,17 @@ pub async fn ensure_dir_exists(path: String) -> AppResult<()> {
 
 /// Handle new save creation.
 #[tauri::command]
-pub async fn handle_new_save(save_data: new_save::SaveData) -> AppResult<()> {
-    run_blocking(move || new_save::create_new_save(save_data)).await
+pub async fn handle_new_save(
+    state: tauri::State<'_, SavePathsState>,
+    save_data: new_save::SaveData,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<()>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return new_save::preview_new_save(&paths, &save_data).map(Outcome::DryRun);
+        }
+        new_save::create_new_save(&paths, save_data).map(|_| Outcome::Applied(()))
+    })
+    .await
 }
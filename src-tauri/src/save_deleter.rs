@@ -2,118 +2,211 @@
 //! Extracted from save_commands.rs for better modularity
 
 use crate::common::{
-    add_save_to_mainsave, extract_archive_name, get_save_games_dir, get_visible_saves_set,
-    remove_save_from_mainsave, validate_save_games_path,
+    add_save_to_mainsave, extract_archive_name, get_visible_saves_set, preview_mainsave,
+    remove_save_from_mainsave, run_blocking, MainsaveOp,
 };
 use crate::error::AppResult;
+use crate::file_lock::lock_save;
+use crate::save_backup::backup_file;
+use crate::save_diff::{DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_trash::{self, RestoreResult};
+use serde::Serialize;
 use serde_json::json;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-/// Helper: run a blocking closure via tokio::task::spawn_blocking,
-/// mapping the join error into an AppResult.
-async fn run_blocking<F, T>(f: F) -> AppResult<T>
-where
-    F: FnOnce() -> AppResult<T> + Send + 'static,
-    T: Send + 'static,
-{
-    tokio::task::spawn_blocking(f)
-        .await
-        .map_err(|e| format!("Task was cancelled: {}", e))?
+/// Check that `path` is a deletable .sav inside SaveGames; returns its file name
+pub(crate) fn deletable_file_name<'a>(
+    paths: &SavePaths,
+    path: &'a Path,
+    action: &str,
+) -> AppResult<&'a str> {
+    let filename = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file path")?;
+
+    if !filename.to_lowercase().ends_with(".sav") {
+        return Err(format!("Only .sav save files can be {}", action).into());
+    }
+
+    paths.validate_save_games_path(path)?;
+    Ok(filename)
+}
+
+/// A deleted save; the file is gone even when MAINSAVE couldn't be updated
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteResult {
+    /// Where a soft delete moved the file
+    pub trash_path: Option<String>,
+    /// Why MAINSAVE may still list the save
+    pub mainsave_error: Option<String>,
+}
+
+/// Drop a deleted save from MAINSAVE, returning the error instead of failing
+/// the delete that already happened
+fn unlist_deleted(paths: &SavePaths, archive_name: &str) -> Option<String> {
+    remove_save_from_mainsave(paths, archive_name)
+        .inspect_err(|e| {
+            tracing::warn!("'{}' deleted but MAINSAVE not updated: {}", archive_name, e)
+        })
+        .err()
+        .map(|e| e.to_string())
+}
+
+/// Dry-run report for file operations followed by MAINSAVE edits
+fn preview_report(
+    paths: &SavePaths,
+    files: Vec<FileOp>,
+    ops: &[MainsaveOp],
+) -> AppResult<DryRunReport> {
+    let mut report = DryRunReport {
+        files,
+        ..Default::default()
+    };
+    report.push_diff(
+        paths.mainsave_path().to_string_lossy(),
+        preview_mainsave(paths, ops)?,
+    );
+    Ok(report)
 }
 
 /// Permanently delete a save file.
 /// Removes from MAINSAVE records.
 #[tauri::command]
-pub async fn delete_file(file_path: String) -> AppResult<()> {
+pub async fn delete_file(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<DeleteResult>> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&file_path);
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid file path")?;
-
-        if !filename.to_lowercase().ends_with(".sav") {
-            return Err("Only .sav save files can be deleted".to_string().into());
+        let filename = deletable_file_name(&paths, path, "deleted")?;
+        let archive_name = extract_archive_name(filename);
+
+        if dry_run.unwrap_or(false) {
+            return preview_report(
+                &paths,
+                vec![FileOp::Delete {
+                    path: file_path.clone(),
+                }],
+                &[MainsaveOp::Remove(archive_name.to_string())],
+            )
+            .map(Outcome::DryRun);
         }
 
-        validate_save_games_path(path)?;
-
+        let _lock = lock_save(&paths, path)?;
+        backup_file(&paths, path, "delete")?;
         fs::remove_file(&file_path).map_err(|e| format!("Failed to delete file: {}", e))?;
 
-        // Remove from MAINSAVE records (failure does not affect main operation)
-        let _ = remove_save_from_mainsave(extract_archive_name(filename));
-
-        Ok(())
+        // Remove from MAINSAVE records (failure is reported, the file stays deleted)
+        Ok(Outcome::Applied(DeleteResult {
+            trash_path: None,
+            mainsave_error: unlist_deleted(&paths, archive_name),
+        }))
     })
     .await
 }
 
 /// Soft-delete: rename .sav → .sav.trash so it can be restored later.
 /// Removes from MAINSAVE records.
-#[tauri::command]
-pub async fn soft_delete_file(file_path: String) -> AppResult<()> {
-    run_blocking(move || {
-        let path = Path::new(&file_path);
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid file path")?;
-
-        if !filename.to_lowercase().ends_with(".sav") {
-            return Err("Only .sav save files can be soft-deleted"
-                .to_string()
-                .into());
-        }
+pub fn soft_delete(paths: &SavePaths, path: &Path) -> AppResult<DeleteResult> {
+    let filename = deletable_file_name(paths, path, "soft-deleted")?;
 
-        validate_save_games_path(path)?;
+    let trash_path = path.with_extension("sav.trash");
 
-        let trash_path = path.with_extension("sav.trash");
-
-        // Move to trash (rename)
-        fs::rename(path, &trash_path)
-            .map_err(|e| format!("Failed to move file to trash: {}", e))?;
+    // Move to trash (rename)
+    let _lock = lock_save(paths, path)?;
+    fs::rename(path, &trash_path).map_err(|e| format!("Failed to move file to trash: {}", e))?;
+    save_trash::record_trashed(paths, &trash_path);
 
-        // Remove from MAINSAVE records
-        remove_save_from_mainsave(extract_archive_name(filename))?;
-
-        Ok(())
+    // Remove from MAINSAVE records (failure is reported, the file stays trashed)
+    Ok(DeleteResult {
+        trash_path: Some(trash_path.to_string_lossy().into_owned()),
+        mainsave_error: unlist_deleted(paths, extract_archive_name(filename)),
     })
-    .await
 }
 
-/// Restore a soft-deleted file: rename .sav.trash → .sav.
-/// Adds back to MAINSAVE records.
+/// Dry run of [`soft_delete`]
+pub fn preview_soft_delete(paths: &SavePaths, path: &Path) -> AppResult<DryRunReport> {
+    let filename = deletable_file_name(paths, path, "soft-deleted")?;
+    preview_report(
+        paths,
+        vec![FileOp::Rename {
+            from: path.to_string_lossy().into_owned(),
+            to: path
+                .with_extension("sav.trash")
+                .to_string_lossy()
+                .into_owned(),
+        }],
+        &[MainsaveOp::Remove(
+            extract_archive_name(filename).to_string(),
+        )],
+    )
+}
+
+/// Soft-delete command, see [`soft_delete`].
 #[tauri::command]
-pub async fn restore_file(file_path: String) -> AppResult<()> {
+pub async fn soft_delete_file(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<DeleteResult>> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&file_path);
-
-        // The .trash path is the original path with .sav.trash extension
-        let trash_path = path.with_extension("sav.trash");
-
-        validate_save_games_path(&trash_path)?;
-
-        if !trash_path.exists() {
-            return Err(format!("Trash file not found: {}", trash_path.display()).into());
+        if dry_run.unwrap_or(false) {
+            return preview_soft_delete(&paths, path).map(Outcome::DryRun);
         }
+        soft_delete(&paths, path).map(Outcome::Applied)
+    })
+    .await
+}
+
+/// Restore a soft-deleted file: rename .sav.trash → .sav and add it back to
+/// MAINSAVE records. `path` is the original .sav path; a save that has taken that
+/// name since is kept and the trashed one comes back as `<name> (n)` (see
+/// [`save_trash::restore_trash`]).
+pub fn restore(paths: &SavePaths, path: &Path) -> AppResult<RestoreResult> {
+    save_trash::restore_trash(paths, &existing_trash_path(paths, path)?, None)
+}
 
-        let filename = trash_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid trash file path")?;
+/// The existing .sav.trash file for the original `path`
+fn existing_trash_path(paths: &SavePaths, path: &Path) -> AppResult<PathBuf> {
+    // The .trash path is the original path with .sav.trash extension
+    let trash_path = path.with_extension("sav.trash");
 
-        // Restore (rename back)
-        fs::rename(&trash_path, path).map_err(|e| format!("Failed to restore file: {}", e))?;
+    paths.validate_save_games_path(&trash_path)?;
 
-        // Validate restored path
-        validate_save_games_path(path)?;
+    if !trash_path.exists() {
+        return Err(format!("Trash file not found: {}", trash_path.display()).into());
+    }
+    Ok(trash_path)
+}
 
-        // Add back to MAINSAVE records
-        add_save_to_mainsave(extract_archive_name(filename))?;
+/// Dry run of [`restore`]
+pub fn preview_restore(paths: &SavePaths, path: &Path) -> AppResult<DryRunReport> {
+    save_trash::preview_restore_trash(paths, &existing_trash_path(paths, path)?, None)
+}
 
-        Ok(())
+/// Restore command, see [`restore`].
+#[tauri::command]
+pub async fn restore_file(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<RestoreResult>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let path = Path::new(&file_path);
+        if dry_run.unwrap_or(false) {
+            return preview_restore(&paths, path).map(Outcome::DryRun);
+        }
+        restore(&paths, path).map(Outcome::Applied)
     })
     .await
 }
@@ -121,27 +214,45 @@ pub async fn restore_file(file_path: String) -> AppResult<()> {
 /// Permanently delete a trashed file (.sav.trash).
 /// Called after the undo window expires.
 #[tauri::command]
-pub async fn permanent_delete_file(file_path: String) -> AppResult<()> {
+pub async fn permanent_delete_file(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<()>> {
+    let paths = state.current()?;
     run_blocking(move || {
         let path = Path::new(&file_path);
         let trash_path = path.with_extension("sav.trash");
 
-        validate_save_games_path(&trash_path)?;
+        paths.validate_save_games_path(&trash_path)?;
+
+        if dry_run.unwrap_or(false) {
+            let mut report = DryRunReport::default();
+            if trash_path.exists() {
+                report.files.push(FileOp::Delete {
+                    path: trash_path.to_string_lossy().into_owned(),
+                });
+            }
+            return Ok(Outcome::DryRun(report));
+        }
 
+        let _lock = lock_save(&paths, &trash_path)?;
         if trash_path.exists() {
+            backup_file(&paths, &trash_path, "purge")?;
             fs::remove_file(&trash_path)
                 .map_err(|e| format!("Failed to delete trash file: {}", e))?;
         }
-        Ok(())
+        Ok(Outcome::Applied(()))
     })
     .await
 }
 
 /// Open the save games folder in the system file explorer.
 #[tauri::command]
-pub async fn open_save_games_folder() -> AppResult<()> {
+pub async fn open_save_games_folder(state: tauri::State<'_, SavePathsState>) -> AppResult<()> {
+    let paths = state.current()?;
     run_blocking(move || {
-        let save_games_path = get_save_games_dir()?;
+        let save_games_path = paths.save_games_dir();
 
         if !save_games_path.exists() {
             return Err(format!(
@@ -165,13 +276,13 @@ pub async fn open_save_games_folder() -> AppResult<()> {
 
         #[cfg(target_os = "macos")]
         Command::new("open")
-            .arg(&save_games_path)
+            .arg(save_games_path)
             .spawn()
             .map_err(|e| format!("Failed to open folder: {}", e))?;
 
         #[cfg(target_os = "linux")]
         Command::new("xdg-open")
-            .arg(&save_games_path)
+            .arg(save_games_path)
             .spawn()
             .map_err(|e| format!("Failed to open folder: {}", e))?;
 
@@ -180,63 +291,96 @@ pub async fn open_save_games_folder() -> AppResult<()> {
     .await
 }
 
+/// Toggle whether a save is listed in MAINSAVE `SingleplayerSaves`.
+/// Returns the new visibility.
+pub fn toggle_visibility(paths: &SavePaths, file_path: &Path) -> AppResult<bool> {
+    let (archive_name, is_visible) = visibility_of(paths, file_path)?;
+    let archive_name = archive_name.as_str();
+
+    tracing::info!(
+        "toggling visibility for '{}' (currently {})",
+        archive_name,
+        if is_visible { "visible" } else { "hidden" }
+    );
+
+    if is_visible {
+        let removed = remove_save_from_mainsave(paths, archive_name)?;
+        if !removed {
+            tracing::warn!("'{}' not found in MAINSAVE SingleplayerSaves", archive_name);
+        }
+    } else {
+        add_save_to_mainsave(paths, archive_name)?;
+    }
+
+    Ok(!is_visible)
+}
+
+/// Archive name of a save and whether MAINSAVE currently lists it
+fn visibility_of(paths: &SavePaths, file_path: &Path) -> AppResult<(String, bool)> {
+    if !file_path.exists() {
+        return Err("File does not exist".to_string().into());
+    }
+
+    paths.validate_save_games_path(file_path)?;
+
+    let file_name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid filename")?;
+    let archive_name = extract_archive_name(file_name).to_string();
+
+    // Get current visibility state
+    let is_visible = get_visible_saves_set(paths)?.contains(&archive_name);
+    Ok((archive_name, is_visible))
+}
+
+/// Dry run of [`toggle_visibility`]
+pub fn preview_toggle_visibility(paths: &SavePaths, file_path: &Path) -> AppResult<DryRunReport> {
+    let (archive_name, is_visible) = visibility_of(paths, file_path)?;
+    let op = if is_visible {
+        MainsaveOp::Remove(archive_name)
+    } else {
+        MainsaveOp::Add(archive_name)
+    };
+    preview_report(paths, Vec::new(), &[op])
+}
+
 /// Handle file operations: toggle visibility, read MAINSAVE.
 #[tauri::command]
 pub async fn handle_file(
+    state: tauri::State<'_, SavePathsState>,
     file_path: String,
     action: Option<String>,
     _archive_name: Option<String>,
-) -> AppResult<String> {
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<String>> {
+    let paths = state.current()?;
     run_blocking(move || {
         // Handle special request to read MAINSAVE file
         if action.as_deref() == Some("read") && file_path == "MAINSAVE.sav" {
-            let visible_saves: Vec<String> = get_visible_saves_set()?.into_iter().collect();
+            let visible_saves: Vec<String> = get_visible_saves_set(&paths)?.into_iter().collect();
             let response = json!({
                 "success": true,
                 "data": {
                     "SingleplayerSaves": visible_saves
                 }
             });
-            return Ok(response.to_string());
+            return Ok(Outcome::Applied(response.to_string()));
         }
 
         let file_path = PathBuf::from(&file_path);
-        if !file_path.exists() {
-            return Err("File does not exist".to_string().into());
-        }
-
-        validate_save_games_path(&file_path)?;
-
-        let file_name = file_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid filename")?;
-        let archive_name = extract_archive_name(file_name);
-
-        // Get current visibility state and toggle
-        let visible_saves = get_visible_saves_set()?;
-        let is_visible = visible_saves.contains(archive_name);
-
-        tracing::info!(
-            "toggling visibility for '{}' (currently {})",
-            archive_name,
-            if is_visible { "visible" } else { "hidden" }
-        );
-
-        if is_visible {
-            let removed = remove_save_from_mainsave(archive_name)?;
-            if !removed {
-                tracing::warn!("'{}' not found in MAINSAVE SingleplayerSaves", archive_name);
-            }
-        } else {
-            add_save_to_mainsave(archive_name)?;
+        if dry_run.unwrap_or(false) {
+            return preview_toggle_visibility(&paths, &file_path).map(Outcome::DryRun);
         }
+        toggle_visibility(&paths, &file_path)?;
 
         if action.as_deref() == Some("toggle_visibility") {
-            return Ok(json!({"success": true}).to_string());
+            return Ok(Outcome::Applied(json!({"success": true}).to_string()));
         }
 
-        Ok(file_path.to_str().unwrap_or_default().to_string())
+        Ok(Outcome::Applied(
+            file_path.to_str().unwrap_or_default().to_string(),
+        ))
     })
     .await
 }
@@ -0,0 +1,790 @@
+//! Save diff module - Property-level diff between two save trees, and dry-run reports
+//! Change paths use the `property_path` syntax (`LevelsCompleted[4].HasCompleted_4_...`,
+//! `PlayerData["7656..."].Sanity_6_...`) so any of them can be read back with
+//! `get_save_property`. Mutating commands return a [`DryRunReport`] instead of
+//! writing when called with `dry_run`; `diff_saves` compares two files on disk.
+
+use crate::cli_handlers;
+use crate::common::run_blocking;
+use crate::error::AppResult;
+use crate::etb_save::{pure_player_id, LEVELS_COMPLETED, PLAYER_DATA};
+use crate::property_path::TypedValue;
+use crate::save_paths::SavePathsState;
+use crate::save_shared::LEVEL_NAME_FIELD;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use uesave::{MapEntry, Properties, Property, PropertyKey, Save, StructValue, ValueVec};
+
+/// Truncate composite values in the text rendering
+const MAX_TEXT_VALUE_LEN: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One property that differs between two trees
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<TypedValue>,
+    pub new: Option<TypedValue>,
+}
+
+impl PropertyChange {
+    /// `~ Path: old -> new` style line
+    pub fn to_text(&self) -> String {
+        match self.kind {
+            ChangeKind::Added => format!("+ {}: {}", self.path, value_text(self.new.as_ref())),
+            ChangeKind::Removed => format!("- {}: {}", self.path, value_text(self.old.as_ref())),
+            ChangeKind::Changed => format!(
+                "~ {}: {} -> {}",
+                self.path,
+                value_text(self.old.as_ref()),
+                value_text(self.new.as_ref())
+            ),
+        }
+    }
+}
+
+fn value_text(value: Option<&TypedValue>) -> String {
+    let text = match value {
+        None => return "<none>".to_string(),
+        Some(TypedValue::Bool(v)) => v.to_string(),
+        Some(TypedValue::Int(v)) => v.to_string(),
+        Some(TypedValue::Int64(v)) => v.to_string(),
+        Some(TypedValue::Float(v)) => v.to_string(),
+        Some(TypedValue::Double(v)) => v.to_string(),
+        Some(TypedValue::Byte(v)) => v.to_string(),
+        Some(TypedValue::Str(v)) | Some(TypedValue::Name(v)) => format!("{:?}", v),
+        Some(TypedValue::ByteLabel(v)) | Some(TypedValue::Enum(v)) => v.clone(),
+        Some(TypedValue::Raw(v)) => v.to_string(),
+    };
+    if text.chars().count() > MAX_TEXT_VALUE_LEN {
+        format!(
+            "{}…",
+            text.chars().take(MAX_TEXT_VALUE_LEN).collect::<String>()
+        )
+    } else {
+        text
+    }
+}
+
+/// Diff the root properties of two saves
+pub fn diff_roots(old: &Save, new: &Save) -> Vec<PropertyChange> {
+    diff_properties(&old.root.properties, &new.root.properties)
+}
+
+/// Diff two property lists (use `Properties::default()` for "nothing yet")
+pub fn diff_properties(old: &Properties, new: &Properties) -> Vec<PropertyChange> {
+    let mut changes = Vec::new();
+    diff_props("", old, new, &mut changes);
+    changes
+}
+
+fn push(
+    out: &mut Vec<PropertyChange>,
+    path: String,
+    old: Option<TypedValue>,
+    new: Option<TypedValue>,
+) {
+    let kind = match (&old, &new) {
+        (None, _) => ChangeKind::Added,
+        (_, None) => ChangeKind::Removed,
+        _ => ChangeKind::Changed,
+    };
+    out.push(PropertyChange {
+        path,
+        kind,
+        old,
+        new,
+    });
+}
+
+fn field_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn key_path(prefix: &str, key: &str) -> String {
+    format!(
+        "{}[\"{}\"]",
+        prefix,
+        key.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn raw<T: Serialize>(value: &T) -> TypedValue {
+    TypedValue::Raw(serde_json::to_value(value).unwrap_or(JsonValue::Null))
+}
+
+fn diff_props(prefix: &str, old: &Properties, new: &Properties, out: &mut Vec<PropertyChange>) {
+    for (key, old_prop) in old.0.iter() {
+        let path = field_path(prefix, &key.1);
+        match new.0.get(key) {
+            Some(new_prop) => diff_property(&path, old_prop, new_prop, out),
+            None => push(out, path, Some(TypedValue::from_property(old_prop)), None),
+        }
+    }
+    for (key, new_prop) in new.0.iter() {
+        if !old.0.contains_key(key) {
+            let path = field_path(prefix, &key.1);
+            push(out, path, None, Some(TypedValue::from_property(new_prop)));
+        }
+    }
+}
+
+fn diff_struct_value(
+    path: &str,
+    old: &StructValue,
+    new: &StructValue,
+    out: &mut Vec<PropertyChange>,
+) {
+    match (old, new) {
+        (StructValue::Struct(a), StructValue::Struct(b)) => diff_props(path, a, b, out),
+        _ if old != new => push(out, path.to_string(), Some(raw(old)), Some(raw(new))),
+        _ => {}
+    }
+}
+
+fn diff_property(path: &str, old: &Property, new: &Property, out: &mut Vec<PropertyChange>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (Property::Struct(a), Property::Struct(b)) => diff_struct_value(path, a, b, out),
+        (Property::Array(ValueVec::Struct(a)), Property::Array(ValueVec::Struct(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                let element = format!("{}[{}]", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => diff_struct_value(&element, x, y, out),
+                    (Some(x), None) => push(out, element, Some(raw(x)), None),
+                    (None, Some(y)) => push(out, element, None, Some(raw(y))),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Property::Map(a), Property::Map(b)) => diff_maps(path, a, b, out),
+        // Scalar arrays (inventories, MAINSAVE's list) read best as one value
+        _ => push(
+            out,
+            path.to_string(),
+            Some(TypedValue::from_property(old)),
+            Some(TypedValue::from_property(new)),
+        ),
+    }
+}
+
+/// Map entries are matched by key; entries with non-string keys by position
+fn diff_maps(path: &str, old: &[MapEntry], new: &[MapEntry], out: &mut Vec<PropertyChange>) {
+    let key_of = |entry: &MapEntry, index: usize| match &entry.key {
+        Property::Str(k) | Property::Name(k) => key_path(path, k),
+        _ => format!("{}[{}]", path, index),
+    };
+    let old_keyed: Vec<(String, &MapEntry)> = old
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (key_of(e, i), e))
+        .collect();
+    let new_keyed: Vec<(String, &MapEntry)> = new
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (key_of(e, i), e))
+        .collect();
+
+    for (entry_path, old_entry) in &old_keyed {
+        match new_keyed.iter().find(|(p, _)| p == entry_path) {
+            Some((_, new_entry)) => {
+                diff_property(entry_path, &old_entry.value, &new_entry.value, out)
+            }
+            None => push(
+                out,
+                entry_path.clone(),
+                Some(TypedValue::from_property(&old_entry.value)),
+                None,
+            ),
+        }
+    }
+    for (entry_path, new_entry) in &new_keyed {
+        if !old_keyed.iter().any(|(p, _)| p == entry_path) {
+            push(
+                out,
+                entry_path.clone(),
+                None,
+                Some(TypedValue::from_property(&new_entry.value)),
+            );
+        }
+    }
+}
+
+/// A file system effect of a mutating command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum FileOp {
+    /// New file
+    Create {
+        path: String,
+    },
+    /// Existing file rewritten in place
+    Write {
+        path: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Delete {
+        path: String,
+    },
+}
+
+impl FileOp {
+    pub fn to_text(&self) -> String {
+        match self {
+            FileOp::Create { path } => format!("create  {}", path),
+            FileOp::Write { path } => format!("write   {}", path),
+            FileOp::Rename { from, to } => format!("rename  {} -> {}", from, to),
+            FileOp::Delete { path } => format!("delete  {}", path),
+        }
+    }
+}
+
+/// Property changes one file would receive
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    pub changes: Vec<PropertyChange>,
+}
+
+/// Everything a mutating command would do, computed without touching the disk
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunReport {
+    /// In the order the command performs them
+    pub files: Vec<FileOp>,
+    /// Per written file (the save and/or MAINSAVE.sav); unchanged files are left out
+    pub diffs: Vec<FileDiff>,
+    /// Side effects worth calling out (merged players, overwritten files...)
+    pub notes: Vec<String>,
+}
+
+impl DryRunReport {
+    /// Add a file's changes unless there are none
+    pub fn push_diff(&mut self, path: impl Into<String>, changes: Vec<PropertyChange>) {
+        if !changes.is_empty() {
+            self.diffs.push(FileDiff {
+                path: path.into(),
+                changes,
+            });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.diffs.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "No changes".to_string();
+        }
+        let mut lines: Vec<String> = self.files.iter().map(FileOp::to_text).collect();
+        for diff in &self.diffs {
+            lines.push(format!("{}:", diff.path));
+            lines.extend(diff.changes.iter().map(|c| format!("  {}", c.to_text())));
+        }
+        lines.extend(self.notes.iter().map(|note| format!("note: {}", note)));
+        lines.join("\n")
+    }
+}
+
+/// Preview of putting `bytes` in place of `target` (restoring a backup or snapshot):
+/// the file op plus the root diff against the current file. A side that doesn't
+/// parse gets a note instead of a diff.
+pub fn preview_replace(target: &Path, bytes: &[u8]) -> DryRunReport {
+    let path = target.to_string_lossy().into_owned();
+    let parse = |bytes: &[u8]| Save::read(&mut Cursor::new(bytes)).ok();
+    let mut report = DryRunReport::default();
+    let Some(new) = parse(bytes) else {
+        report.files.push(FileOp::Write { path });
+        report
+            .notes
+            .push("The restored copy doesn't parse; no property diff".to_string());
+        return report;
+    };
+    if !target.is_file() {
+        report.files.push(FileOp::Create { path: path.clone() });
+        report.push_diff(
+            path,
+            diff_properties(&Properties::default(), &new.root.properties),
+        );
+        return report;
+    }
+    report.files.push(FileOp::Write { path: path.clone() });
+    match fs::read(target).ok().and_then(|current| parse(&current)) {
+        Some(old) => report.push_diff(path, diff_roots(&old, &new)),
+        None => report
+            .notes
+            .push(format!("{} doesn't parse; no property diff", path)),
+    }
+    report
+}
+
+/// Result of a command that supports `dry_run`: its usual value, or the preview.
+/// Untagged, so a real run serializes exactly as before.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Outcome<T> {
+    Applied(T),
+    DryRun(DryRunReport),
+}
+
+impl<T> Outcome<T> {
+    /// Transform the applied value; a preview passes through
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Outcome::Applied(value) => Outcome::Applied(f(value)),
+            Outcome::DryRun(report) => Outcome::DryRun(report),
+        }
+    }
+}
+
+/// A header field or recorded schema that differs (values as JSON)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<JsonValue>,
+    pub new: Option<JsonValue>,
+}
+
+impl ValueChange {
+    fn new(path: String, old: Option<JsonValue>, new: Option<JsonValue>) -> Self {
+        let kind = match (&old, &new) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        };
+        ValueChange {
+            path,
+            kind,
+            old,
+            new,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let text =
+            |value: &Option<JsonValue>| value_text(value.clone().map(TypedValue::Raw).as_ref());
+        match self.kind {
+            ChangeKind::Added => format!("+ {}: {}", self.path, text(&self.new)),
+            ChangeKind::Removed => format!("- {}: {}", self.path, text(&self.old)),
+            ChangeKind::Changed => {
+                format!(
+                    "~ {}: {} -> {}",
+                    self.path,
+                    text(&self.old),
+                    text(&self.new)
+                )
+            }
+        }
+    }
+}
+
+/// A LevelsCompleted / PlayerData entry matched across both saves
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryDiff {
+    /// LevelName, or the pure player id
+    pub key: String,
+    pub kind: ChangeKind,
+    /// Where the entry sits in each save (`LevelsCompleted[3]`, `PlayerData["..."]`)
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    /// Field changes, relative to the entry (every field for added / removed entries)
+    pub changes: Vec<PropertyChange>,
+}
+
+impl EntryDiff {
+    fn to_text(&self) -> Vec<String> {
+        let sign = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        let location = match (&self.old_path, &self.new_path) {
+            (Some(old), Some(new)) if old != new => format!("{} -> {}", old, new),
+            (_, Some(path)) | (Some(path), None) => path.clone(),
+            (None, None) => String::new(),
+        };
+        let mut lines = vec![format!("  {} {} ({})", sign, self.key, location)];
+        lines.extend(
+            self.changes
+                .iter()
+                .map(|c| format!("      {}", c.to_text())),
+        );
+        lines
+    }
+}
+
+/// Structured difference between two saves: `a` is old, `b` is new
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveDiff {
+    pub header: Vec<ValueChange>,
+    /// Recorded property schemas, by dotted path
+    pub schemas: Vec<ValueChange>,
+    /// Root properties other than LevelsCompleted / PlayerData
+    pub properties: Vec<PropertyChange>,
+    /// LevelsCompleted entries matched by LevelName
+    pub levels: Vec<EntryDiff>,
+    /// PlayerData entries matched by pure player id
+    pub players: Vec<EntryDiff>,
+}
+
+impl SaveDiff {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty()
+            && self.schemas.is_empty()
+            && self.properties.is_empty()
+            && self.levels.is_empty()
+            && self.players.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "No differences".to_string();
+        }
+        let mut lines = Vec::new();
+        let mut section = |title: &str, body: Vec<String>| {
+            if !body.is_empty() {
+                lines.push(format!("{}:", title));
+                lines.extend(body);
+            }
+        };
+        section(
+            "Header",
+            self.header
+                .iter()
+                .map(|c| format!("  {}", c.to_text()))
+                .collect(),
+        );
+        section(
+            "Schemas",
+            self.schemas
+                .iter()
+                .map(|c| format!("  {}", c.to_text()))
+                .collect(),
+        );
+        section(
+            "Properties",
+            self.properties
+                .iter()
+                .map(|c| format!("  {}", c.to_text()))
+                .collect(),
+        );
+        section(
+            LEVELS_COMPLETED,
+            self.levels.iter().flat_map(EntryDiff::to_text).collect(),
+        );
+        section(
+            PLAYER_DATA,
+            self.players.iter().flat_map(EntryDiff::to_text).collect(),
+        );
+        lines.join("\n")
+    }
+}
+
+/// Compare two parsed saves
+pub fn compare_saves(a: &Save, b: &Save) -> SaveDiff {
+    let mut diff = SaveDiff {
+        header: diff_json_fields(
+            &serde_json::to_value(&a.header).unwrap_or(JsonValue::Null),
+            &serde_json::to_value(&b.header).unwrap_or(JsonValue::Null),
+        ),
+        schemas: diff_schemas(a, b),
+        ..Default::default()
+    };
+
+    let (a_props, b_props) = (&a.root.properties, &b.root.properties);
+    let mut rest_a = Properties::default();
+    let mut rest_b = Properties::default();
+    for (props, rest) in [(a_props, &mut rest_a), (b_props, &mut rest_b)] {
+        for (key, prop) in props.0.iter() {
+            if key.1 != LEVELS_COMPLETED && key.1 != PLAYER_DATA {
+                rest.0.insert(key.clone(), prop.clone());
+            }
+        }
+    }
+    diff.properties = diff_properties(&rest_a, &rest_b);
+
+    let root =
+        |props: &'_ Properties, name: &str| props.0.get(&PropertyKey(0, name.to_string())).cloned();
+    let (levels_a, levels_b) = (
+        root(a_props, LEVELS_COMPLETED),
+        root(b_props, LEVELS_COMPLETED),
+    );
+    match (
+        level_entries(levels_a.as_ref()),
+        level_entries(levels_b.as_ref()),
+    ) {
+        (Some(old), Some(new)) => diff.levels = diff_entries(old, new),
+        _ => diff_root_fallback(LEVELS_COMPLETED, levels_a, levels_b, &mut diff.properties),
+    }
+
+    let (players_a, players_b) = (root(a_props, PLAYER_DATA), root(b_props, PLAYER_DATA));
+    match (
+        player_entries(players_a.as_ref()),
+        player_entries(players_b.as_ref()),
+    ) {
+        (Some(old), Some(new)) => diff.players = diff_entries(old, new),
+        _ => diff_root_fallback(PLAYER_DATA, players_a, players_b, &mut diff.properties),
+    }
+    diff
+}
+
+/// LevelsCompleted / PlayerData missing on a side, or not in the expected
+/// shape: compare it like any other root property
+fn diff_root_fallback(
+    name: &str,
+    old: Option<Property>,
+    new: Option<Property>,
+    out: &mut Vec<PropertyChange>,
+) {
+    let mut a = Properties::default();
+    let mut b = Properties::default();
+    let key = PropertyKey(0, name.to_string());
+    if let Some(old) = old {
+        a.0.insert(key.clone(), old);
+    }
+    if let Some(new) = new {
+        b.0.insert(key, new);
+    }
+    out.extend(diff_properties(&a, &b));
+}
+
+fn diff_json_fields(old: &JsonValue, new: &JsonValue) -> Vec<ValueChange> {
+    let empty = serde_json::Map::new();
+    let a = old.as_object().unwrap_or(&empty);
+    let b = new.as_object().unwrap_or(&empty);
+    let mut changes = Vec::new();
+    for (key, value) in a {
+        match b.get(key) {
+            Some(other) if other == value => {}
+            other => changes.push(ValueChange::new(
+                key.clone(),
+                Some(value.clone()),
+                other.cloned(),
+            )),
+        }
+    }
+    for (key, value) in b {
+        if !a.contains_key(key) {
+            changes.push(ValueChange::new(key.clone(), None, Some(value.clone())));
+        }
+    }
+    changes
+}
+
+fn diff_schemas(a: &Save, b: &Save) -> Vec<ValueChange> {
+    let (a, b) = (a.schemas.schemas(), b.schemas.schemas());
+    let paths: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    let json = |tag| serde_json::to_value(tag).unwrap_or(JsonValue::Null);
+    paths
+        .into_iter()
+        .filter(|path| a.get(*path) != b.get(*path))
+        .map(|path| ValueChange::new(path.clone(), a.get(path).map(json), b.get(path).map(json)))
+        .collect()
+}
+
+/// One LevelsCompleted element / PlayerData entry prepared for matching
+struct Entry<'a> {
+    /// Matching key (LevelName, pure player id)
+    key: String,
+    /// Exact identity preferred when several entries share `key` (the full PlayerData key)
+    exact: String,
+    path: String,
+    props: &'a Properties,
+}
+
+fn level_entries(prop: Option<&Property>) -> Option<Vec<Entry<'_>>> {
+    let Some(Property::Array(ValueVec::Struct(values))) = prop else {
+        return None;
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let StructValue::Struct(props) = value else {
+                return None;
+            };
+            let name = props
+                .0
+                .iter()
+                .find(|(key, _)| key.1 == LEVEL_NAME_FIELD)
+                .and_then(|(_, prop)| match prop {
+                    Property::Name(name) | Property::Str(name) => Some(name.clone()),
+                    _ => None,
+                })
+                .unwrap_or_default();
+            Some(Entry {
+                key: name.clone(),
+                exact: name,
+                path: format!("{}[{}]", LEVELS_COMPLETED, i),
+                props,
+            })
+        })
+        .collect()
+}
+
+fn player_entries(prop: Option<&Property>) -> Option<Vec<Entry<'_>>> {
+    let Some(Property::Map(entries)) = prop else {
+        return None;
+    };
+    entries
+        .iter()
+        .map(|entry| {
+            let (Property::Str(key) | Property::Name(key)) = &entry.key else {
+                return None;
+            };
+            let Property::Struct(StructValue::Struct(props)) = &entry.value else {
+                return None;
+            };
+            Some(Entry {
+                key: pure_player_id(key).to_string(),
+                exact: key.clone(),
+                path: key_path(PLAYER_DATA, key),
+                props,
+            })
+        })
+        .collect()
+}
+
+/// Pair entries by exact identity first, then by key in order; unpaired ones
+/// are added / removed
+fn diff_entries(old: Vec<Entry>, new: Vec<Entry>) -> Vec<EntryDiff> {
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    let mut new_taken = vec![false; new.len()];
+    let mut old_taken = vec![false; old.len()];
+    for by_exact in [true, false] {
+        for (i, a) in old.iter().enumerate() {
+            if old_taken[i] {
+                continue;
+            }
+            let found = new.iter().enumerate().position(|(j, b)| {
+                !new_taken[j]
+                    && if by_exact {
+                        a.exact == b.exact
+                    } else {
+                        a.key == b.key
+                    }
+            });
+            if let Some(j) = found {
+                old_taken[i] = true;
+                new_taken[j] = true;
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs.sort_by_key(|&(_, j)| j);
+
+    let empty = Properties::default();
+    let mut diffs = Vec::new();
+    for (i, a) in old.iter().enumerate() {
+        if !old_taken[i] {
+            diffs.push(EntryDiff {
+                key: a.key.clone(),
+                kind: ChangeKind::Removed,
+                old_path: Some(a.path.clone()),
+                new_path: None,
+                changes: diff_properties(a.props, &empty),
+            });
+        }
+    }
+    for (i, j) in pairs {
+        let (a, b) = (&old[i], &new[j]);
+        let changes = diff_properties(a.props, b.props);
+        // A moved LevelsCompleted element is not a change; a re-keyed player is
+        if changes.is_empty() && a.exact == b.exact {
+            continue;
+        }
+        diffs.push(EntryDiff {
+            key: b.key.clone(),
+            kind: ChangeKind::Changed,
+            old_path: Some(a.path.clone()),
+            new_path: Some(b.path.clone()),
+            changes,
+        });
+    }
+    for (j, b) in new.iter().enumerate() {
+        if !new_taken[j] {
+            diffs.push(EntryDiff {
+                key: b.key.clone(),
+                kind: ChangeKind::Added,
+                old_path: None,
+                new_path: Some(b.path.clone()),
+                changes: diff_properties(&empty, b.props),
+            });
+        }
+    }
+    diffs
+}
+
+/// Compare two save files on disk
+pub fn diff_files(a: &Path, b: &Path) -> AppResult<SaveDiff> {
+    let old = cli_handlers::parse_sav_file(a)?;
+    let new = cli_handlers::parse_sav_file(b)?;
+    Ok(compare_saves(&old, &new))
+}
+
+/// Output format of [`diff_saves`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffFormat {
+    #[default]
+    Json,
+    Text,
+}
+
+/// A [`SaveDiff`] as structured JSON or as text
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum DiffOutput {
+    Json(SaveDiff),
+    Text(String),
+}
+
+/// Compare two saves (`a` before, `b` after).
+#[tauri::command]
+pub async fn diff_saves(
+    state: tauri::State<'_, SavePathsState>,
+    a: String,
+    b: String,
+    format: Option<DiffFormat>,
+) -> AppResult<DiffOutput> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let (a, b) = (Path::new(&a), Path::new(&b));
+        paths.validate_save_games_path(a)?;
+        paths.validate_save_games_path(b)?;
+        let diff = diff_files(a, b)?;
+        Ok(match format.unwrap_or_default() {
+            DiffFormat::Json => DiffOutput::Json(diff),
+            DiffFormat::Text => DiffOutput::Text(diff.to_text()),
+        })
+    })
+    .await
+}
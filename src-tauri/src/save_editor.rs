@@ -1,51 +1,20 @@
 use crate::common::{
-    add_save_to_mainsave, extract_archive_name, remove_save_from_mainsave, validate_save_games_path,
+    add_save_to_mainsave, extract_archive_name, preview_mainsave, remove_save_from_mainsave,
+    write_atomic, MainsaveOp,
 };
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{is_real_eos_key, pure_player_id, Difficulty, EtbSave, PlayerEntry};
+use crate::file_lock::{lock_save, lock_saves};
+use crate::save_backup::backup_file;
+use crate::save_diff::{diff_roots, DryRunReport, FileOp};
+use crate::save_paths::SavePaths;
 use crate::save_shared;
+use crate::save_verify::verified_bytes;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{BufReader, BufWriter, Write};
-use std::path::Path;
-use uesave::{
-    FGuid, Properties, Property, PropertyKey, PropertyTagDataPartial, PropertyTagPartial,
-    PropertyType, Save, StructType, StructValue, ValueVec,
-};
-
-/// Handle special logic for Pipes level
-fn process_pipes_level(save: &mut Save, level: &str) -> String {
-    let unlocked_fun_key = PropertyKey(0, "UnlockedFun".to_string());
-
-    match level {
-        "Pipes1" => {
-            tracing::info!("Pipes1 detected, changing to Pipes and deleting UnlockedFun_0");
-            if save.root.properties.0.contains_key(&unlocked_fun_key) {
-                save.root.properties.0.shift_remove(&unlocked_fun_key);
-                tracing::info!("Deleted UnlockedFun_0 field");
-            }
-            "Pipes".to_string()
-        }
-        "Pipes2" => {
-            tracing::info!("Pipes2 detected, changing to Pipes and creating UnlockedFun_0");
-            save_shared::record_root_schema(
-                save,
-                "UnlockedFun",
-                PropertyTagPartial {
-                    id: None,
-                    data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-                },
-            );
-            save.root
-                .properties
-                .0
-                .insert(unlocked_fun_key, Property::Bool(true));
-            tracing::info!("Created UnlockedFun_0 field");
-            "Pipes".to_string()
-        }
-        _ => level.to_string(),
-    }
-}
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 
 /// Extract inventory items from JSON data
 fn extract_inventory_items(json_data: &JsonValue, steam_id: &str) -> Vec<String> {
@@ -63,82 +32,35 @@ fn extract_inventory_items(json_data: &JsonValue, steam_id: &str) -> Vec<String>
     items
 }
 
-/// Update existing player data
-fn update_player_data(player_struct: &mut Properties, steam_id: &str, json_data: &JsonValue) {
-    // Modify inventory
-    if let Some(Property::Array(ValueVec::Name(ref mut str_values))) =
-        save_shared::get_property_by_name_mut(player_struct, save_shared::INVENTORY_PROP_NAME)
-    {
-        *str_values = extract_inventory_items(json_data, steam_id);
-    }
-
-    // Modify sanity
-    if let Some(Property::Float(ref mut val)) =
-        save_shared::get_property_by_name_mut(player_struct, save_shared::SANITY_PROP_NAME)
-    {
-        let new_sanity = json_data["playerSanity"][steam_id]
-            .as_f64()
-            .map(|v| v as f32)
-            .unwrap_or(100.0);
-        *val = uesave::Float(new_sanity.clamp(0.0, 100.0));
+/// Player entry built from the frontend JSON (missing sanity = 100)
+fn player_entry_from_json(steam_id: &str, json_data: &JsonValue) -> PlayerEntry {
+    PlayerEntry {
+        key: steam_id.to_string(),
+        sanity: Some(
+            json_data["playerSanity"][steam_id]
+                .as_f64()
+                .map(|v| v as f32)
+                .unwrap_or(100.0),
+        ),
+        inventory: extract_inventory_items(json_data, steam_id),
     }
 }
 
-/// Create new player data struct
-fn create_new_player_struct(steam_id: &str, json_data: &JsonValue) -> Properties {
-    tracing::info!("Creating new player data struct: {}", steam_id);
-    let mut properties = Properties::default();
-
-    let inventory_items = extract_inventory_items(json_data, steam_id);
-    tracing::debug!("Final inventory items: {:?}", inventory_items);
-
-    let sanity_value = json_data["playerSanity"][steam_id]
-        .as_f64()
-        .map(|v| v as f32)
-        .unwrap_or(100.0)
-        .clamp(0.0, 100.0);
-
-    properties.0.insert(
-        PropertyKey(0, save_shared::INVENTORY_PROP_NAME.to_string()),
-        save_shared::create_inventory_property(inventory_items),
-    );
-    properties.0.insert(
-        PropertyKey(0, save_shared::SANITY_PROP_NAME.to_string()),
-        save_shared::create_sanity_property(sanity_value),
-    );
-
-    properties
-}
-
-/// Record schemas for the PlayerData map and its nested player struct fields
-fn record_player_data_schemas(save: &mut Save) {
-    save.schemas.record(
-        "PlayerData".to_string(),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Map {
-                key_type: Box::new(PropertyTagDataPartial::Other(PropertyType::StrProperty)),
-                value_type: Box::new(PropertyTagDataPartial::Struct {
-                    struct_type: StructType::Struct(None),
-                    id: FGuid::nil(),
-                }),
-            },
-        },
-    );
-    save.schemas.record(
-        format!("PlayerData.{}", save_shared::INVENTORY_PROP_NAME),
-        save_shared::array_scalar_tag(PropertyTagDataPartial::Other(PropertyType::NameProperty)),
-    );
-    save.schemas.record(
-        format!("PlayerData.{}", save_shared::SANITY_PROP_NAME),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::FloatProperty),
-        },
-    );
+/// An edit computed in memory: everything `edit_save_file` would write
+struct EditPlan {
+    original_path: PathBuf,
+    output_path: PathBuf,
+    new_filename: String,
+    /// Bytes of the original file, for verification and the dry-run diff
+    original: Vec<u8>,
+    save: EtbSave,
+    /// Duplicate PlayerData entries dropped by `merge_player_data`
+    merged: usize,
+    mainsave_ops: Vec<MainsaveOp>,
 }
 
-pub fn edit_save_file(json_data: &JsonValue, output_dir: &str) -> AppResult<String> {
+/// Apply the frontend edit to the save in memory
+fn plan_edit(paths: &SavePaths, json_data: &JsonValue, output_dir: &str) -> AppResult<EditPlan> {
     tracing::info!("Processing save file...");
 
     let original_path = json_data["path"]
@@ -146,8 +68,8 @@ pub fn edit_save_file(json_data: &JsonValue, output_dir: &str) -> AppResult<Stri
         .ok_or("Missing path in JSON data")?
         .to_string();
 
-    validate_save_games_path(Path::new(&original_path))?;
-    validate_save_games_path(Path::new(output_dir))?;
+    paths.validate_save_games_path(Path::new(&original_path))?;
+    paths.validate_save_games_path(Path::new(output_dir))?;
 
     // Remember old archive name for MAINSAVE cleanup if name changes
     let old_archive_name: Option<String> = Path::new(&original_path)
@@ -186,14 +108,13 @@ pub fn edit_save_file(json_data: &JsonValue, output_dir: &str) -> AppResult<Stri
     );
     let output_path = Path::new(output_dir).join(&new_filename);
 
-    validate_save_games_path(&output_path)?;
+    paths.validate_save_games_path(&output_path)?;
 
     tracing::info!("Reading original save file: {:?}", original_path);
 
-    let file =
-        File::open(&original_path).map_err(|e| format!("Failed to open save file: {}", e))?;
-    let mut reader = BufReader::with_capacity(16384, file);
-    let mut save = Save::read(&mut reader).map_err(|e| format!("Failed to parse save: {:?}", e))?;
+    let original =
+        fs::read(&original_path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let mut save = EtbSave::read(&mut Cursor::new(&original))?;
 
     // Auto-merge duplicate PlayerData entries for the same player
     // (bare id + EOS-suffixed key + all-zeros key from older app versions coexist;
@@ -203,40 +124,73 @@ pub fn edit_save_file(json_data: &JsonValue, output_dir: &str) -> AppResult<Stri
         tracing::info!("Auto-merged {} duplicate player entry(ies)", merged);
     }
 
-    // Handle Pipes level
-    let processed_level = process_pipes_level(&mut save, current_level);
-
-    // Modify CurrentLevel
-    if save_shared::modify_current_level(&mut save, processed_level.clone()) {
-        tracing::info!("Current level name modified to: {}", processed_level);
+    // Modify CurrentLevel (Pipes1 / Pipes2 also set UnlockedFun)
+    if save.set_current_level(current_level) {
+        tracing::info!("Current level name modified to: {}", current_level);
     }
 
     // Update difficulty
-    save_shared::update_difficulty(&mut save, actual_difficulty);
+    let actual_difficulty = Difficulty::from_label(actual_difficulty).ok_or_else(|| {
+        AppError::Validation(format!("Invalid actualDifficulty: {}", actual_difficulty))
+    })?;
+    save.set_difficulty(actual_difficulty);
 
     // Process player data
     process_player_data(&mut save, json_data)?;
 
-    // Write to temp file first to avoid data loss on crash
-    let temp_path = output_path.with_extension("sav.tmp");
-    {
-        let file =
-            File::create(&temp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
-        let mut writer = BufWriter::new(file);
-        save.write(&mut writer)
-            .map_err(|e| format!("Failed to write save: {:?}", e))?;
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush buffer: {}", e))?;
+    // Remove old MAINSAVE entry if the archive name changed (rename / difficulty change)
+    let archive_name = extract_archive_name(&new_filename).to_string();
+    let mut mainsave_ops = Vec::with_capacity(2);
+    if let Some(old_name) = old_archive_name {
+        if old_name != archive_name {
+            mainsave_ops.push(MainsaveOp::Remove(old_name));
+        }
+    }
+    mainsave_ops.push(MainsaveOp::Add(archive_name));
+
+    Ok(EditPlan {
+        original_path: PathBuf::from(original_path),
+        output_path,
+        new_filename,
+        original,
+        save,
+        merged,
+        mainsave_ops,
+    })
+}
+
+pub fn edit_save_file(
+    paths: &SavePaths,
+    json_data: &JsonValue,
+    output_dir: &str,
+) -> AppResult<String> {
+    let plan = plan_edit(paths, json_data, output_dir)?;
+    let output_path = &plan.output_path;
+
+    // Verify the round trip before anything touches the disk
+    let bytes = verified_bytes(&plan.save, Some(&plan.original), &plan.new_filename)?;
+
+    // Lock both names, then make sure nobody rewrote the original since we read it
+    let _locks = lock_saves(paths, [plan.original_path.as_path(), output_path.as_path()])?;
+    if fs::read(&plan.original_path).ok().as_deref() != Some(plan.original.as_slice()) {
+        return Err(AppError::Validation(format!(
+            "{:?} changed on disk while it was being edited; reload it and try again",
+            plan.original_path
+        )));
+    }
+
+    // Keep the original, and any other save the rename would replace
+    backup_file(paths, &plan.original_path, "edit")?;
+    if plan.original_path != *output_path {
+        backup_file(paths, output_path, "edit")?;
     }
 
-    // Atomically rename temp to target path
-    fs::rename(&temp_path, &output_path)
-        .map_err(|e| format!("Failed to rename temp file: {}", e))?;
+    // Write to temp file first to avoid data loss on crash, then atomically rename
+    write_atomic(output_path, &bytes)?;
 
     // Delete original save file only if it differs from output path
     // (rename already overwrites output_path when they are the same)
-    let original_path = Path::new(&original_path);
+    let original_path = plan.original_path.as_path();
     if original_path != output_path && original_path.exists() {
         fs::remove_file(original_path).map_err(|e| format!("Failed to delete old file: {}", e))?;
         tracing::info!("Deleted original save file");
@@ -245,114 +199,63 @@ pub fn edit_save_file(json_data: &JsonValue, output_dir: &str) -> AppResult<Stri
     tracing::info!("Save saved to: {:?}", output_path);
 
     // Update MAINSAVE.sav
-    let archive_name = extract_archive_name(&new_filename);
-
-    // Remove old MAINSAVE entry if the archive name changed (rename / difficulty change)
-    if let Some(ref old_name) = old_archive_name {
-        if old_name != archive_name {
-            let _ = remove_save_from_mainsave(old_name);
-        }
-    }
-
-    add_save_to_mainsave(archive_name)?;
-
-    Ok(output_path.to_str().unwrap_or("Invalid path").to_string())
-}
-
-/// Pure player id: strip the `_+_|<suffix>` (online) or `-<15 chars>` (offline) part.
-fn pure_player_id(key: &str) -> String {
-    if let Some(idx) = key.find("_+_|") {
-        key[..idx].to_string()
-    } else if let Some(idx) = key.find('-') {
-        key[..idx].to_string()
-    } else {
-        key.to_string()
-    }
-}
-
-/// A player entry carries real data when sanity != default 100 or any inventory slot is filled.
-fn entry_has_real_data(value: &Property) -> bool {
-    if let Property::Struct(StructValue::Struct(props)) = value {
-        for (_, prop) in props.0.iter() {
-            match prop {
-                Property::Float(f) => {
-                    if (f.0 - 100.0).abs() > 0.001 {
-                        return true;
-                    }
-                }
-                Property::Array(ValueVec::Name(names))
-                    if names.iter().any(|n| n.as_str() != "None") =>
-                {
-                    return true;
-                }
-                _ => {}
+    for op in &plan.mainsave_ops {
+        match op {
+            MainsaveOp::Remove(old_name) => {
+                remove_save_from_mainsave(paths, old_name)?;
             }
+            MainsaveOp::Add(archive_name) => add_save_to_mainsave(paths, archive_name)?,
+            MainsaveOp::Rename { .. } => {}
         }
     }
-    false
-}
 
-/// Whether the key carries a real EOS account id suffix (32 hex, not the all-zeros
-/// placeholder an earlier app version wrote).
-fn is_real_eos_key(key: &str) -> bool {
-    if let Some(idx) = key.find("_+_|") {
-        let suffix = &key[idx + 4..];
-        suffix.len() == 32 && suffix != "00000000000000000000000000000000"
-    } else {
-        false
-    }
+    Ok(output_path.to_str().unwrap_or("Invalid path").to_string())
 }
 
-/// Read the save's current level name (e.g. "Level5"), or "" when absent.
-fn save_current_level(save: &Save) -> String {
-    if let Some(Property::Name(name)) = save
-        .root
-        .properties
-        .0
-        .get(&PropertyKey(0, "CurrentLevel".to_string()))
-    {
-        name.clone()
+/// Dry run of [`edit_save_file`]: the file moves, property changes (including
+/// PlayerData merges) and MAINSAVE changes the edit would make
+pub fn preview_edit_save(
+    paths: &SavePaths,
+    json_data: &JsonValue,
+    output_dir: &str,
+) -> AppResult<DryRunReport> {
+    let plan = plan_edit(paths, json_data, output_dir)?;
+    // Fail the preview exactly where the real run would
+    verified_bytes(&plan.save, Some(&plan.original), &plan.new_filename)?;
+
+    let before = EtbSave::read(&mut Cursor::new(&plan.original))?;
+    let original_path = plan.original_path.to_string_lossy().into_owned();
+    let output_path = plan.output_path.to_string_lossy().into_owned();
+
+    let mut report = DryRunReport::default();
+    if plan.original_path == plan.output_path {
+        report.files.push(FileOp::Write {
+            path: output_path.clone(),
+        });
     } else {
-        String::new()
-    }
-}
-
-/// Read the save's difficulty label. "Normal" when no Difficulty field exists.
-/// Easy saves store `E_Difficulty::NewEnumerator0`, so that exact label is the
-/// easy-mode signal used by the backpack >12 merge rules.
-fn save_difficulty(save: &Save) -> String {
-    for (key, prop) in save.root.properties.0.iter() {
-        if !key.1.starts_with("Difficulty") {
-            continue;
-        }
-        if let Property::Byte(uesave::Byte::Label(label)) = prop {
-            return label.clone();
+        if plan.output_path.exists() {
+            report.notes.push(format!(
+                "{} already exists and will be replaced",
+                output_path
+            ));
         }
+        report.files.push(FileOp::Rename {
+            from: original_path,
+            to: output_path.clone(),
+        });
     }
-    "Normal".to_string()
-}
-
-/// The 12-slot inventory array of a player entry value, or None if absent.
-fn entry_inventory(value: &Property) -> Option<Vec<String>> {
-    let Property::Struct(StructValue::Struct(props)) = value else {
-        return None;
-    };
-    for (key, prop) in props.0.iter() {
-        if key.1 == save_shared::INVENTORY_PROP_NAME {
-            if let Property::Array(ValueVec::Name(names)) = prop {
-                return Some(names.clone());
-            }
-        }
+    report.push_diff(output_path, diff_roots(&before, &plan.save));
+    report.push_diff(
+        paths.mainsave_path().to_string_lossy(),
+        preview_mainsave(paths, &plan.mainsave_ops)?,
+    );
+    if plan.merged > 0 {
+        report.notes.push(format!(
+            "Merges {} duplicate PlayerData entry(ies)",
+            plan.merged
+        ));
     }
-    None
-}
-
-/// Number of filled (non-"None") slots in an inventory array.
-fn inventory_count(items: &[String]) -> usize {
-    items
-        .iter()
-        .filter(|i| i.as_str() != "None" && !i.is_empty())
-        .count()
+    Ok(report)
 }
 
 /// Keep at most `keep` occurrences of `category`, preserving order.
@@ -480,7 +383,11 @@ fn arrange_inventory_under_12(pool: Vec<String>) -> Vec<String> {
 }
 
 /// Trim a merged pool (>12 items) down to ≤12 following the over-capacity rules.
-fn trim_inventory_over_12(mut pool: Vec<String>, level: &str, difficulty: &str) -> Vec<String> {
+fn trim_inventory_over_12(
+    mut pool: Vec<String>,
+    level: &str,
+    difficulty: Difficulty,
+) -> Vec<String> {
     // 1. Keep a single flashlight (main hand), drop the extras.
     let flashlights = pool.iter().filter(|i| i.as_str() == "Flashlight").count();
     if flashlights > 1 {
@@ -497,8 +404,7 @@ fn trim_inventory_over_12(mut pool: Vec<String>, level: &str, difficulty: &str)
 
     let has_almond = pool.iter().any(|i| i.as_str() == "AlmondWater");
     if has_almond && pool.len() > save_shared::INVENTORY_SLOTS {
-        let is_easy = difficulty == "E_Difficulty::NewEnumerator0";
-        if is_easy {
+        if difficulty == Difficulty::Easy {
             // Easy: delete (prefer almond water, then anything) until ≤12.
             pool = drop_by_priority(
                 pool,
@@ -602,24 +508,31 @@ fn arrange_inventory_over_12(pool: Vec<String>) -> Vec<String> {
 /// inventory is written into the EOS entry, and the pure entry is dropped. When
 /// only one entry has real data, the single best entry is kept (real EOS key breaks
 /// ties). Returns how many entries were dropped.
-fn merge_player_data(save: &mut Save) -> usize {
-    // Read level/difficulty before taking the mutable borrow of the PlayerData map.
-    let current_level = save_current_level(save);
-    let difficulty = save_difficulty(save);
-
-    let player_data_key = PropertyKey(0, "PlayerData".to_string());
-    let Some(Property::Map(entries)) = save.root.properties.0.get_mut(&player_data_key) else {
-        return 0;
-    };
+fn merge_player_data(save: &mut EtbSave) -> usize {
+    let current_level = save.raw_current_level().unwrap_or_default().to_string();
+    let difficulty = save.difficulty();
+    let players = save.players();
 
     // Group entry indices by pure player id
     let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
-    for (i, entry) in entries.iter().enumerate() {
-        if let Property::Str(key) = &entry.key {
-            groups.entry(pure_player_id(key)).or_default().push(i);
-        }
+    for (i, player) in players.iter().enumerate() {
+        groups
+            .entry(pure_player_id(&player.key).to_string())
+            .or_default()
+            .push(i);
     }
 
+    // Score: real data dominates, then the key shape (`eos_score`) breaks ties
+    let best_by = |indices: &[usize], eos_score: &dyn Fn(&str) -> bool| -> usize {
+        *indices
+            .iter()
+            .max_by_key(|&&i| {
+                (if players[i].has_real_data() { 10 } else { 0 })
+                    + usize::from(eos_score(&players[i].key))
+            })
+            .unwrap()
+    };
+
     let mut remove: Vec<usize> = Vec::new();
     for indices in groups.values() {
         if indices.len() < 2 {
@@ -627,122 +540,62 @@ fn merge_player_data(save: &mut Save) -> usize {
         }
 
         // Entries that actually carry inventory data.
-        let data_entries: Vec<&usize> = indices
+        let data_entries = indices
             .iter()
-            .filter(|&&i| {
-                entry_inventory(&entries[i].value)
-                    .map(|inv| inventory_count(&inv) > 0)
-                    .unwrap_or(false)
-            })
-            .collect();
+            .filter(|&&i| players[i].item_count() > 0)
+            .count();
 
-        if data_entries.len() >= 2 {
+        if data_entries >= 2 {
             // Merge all filled backpacks (pure + EOS) into one.
-            let mut pool: Vec<String> = Vec::new();
-            for &i in indices {
-                if let Some(inv) = entry_inventory(&entries[i].value) {
-                    pool.extend(
-                        inv.into_iter()
-                            .filter(|x| x.as_str() != "None" && !x.is_empty()),
-                    );
-                }
-            }
+            let pool: Vec<String> = indices
+                .iter()
+                .flat_map(|&i| players[i].inventory.iter())
+                .filter(|x| x.as_str() != "None" && !x.is_empty())
+                .cloned()
+                .collect();
             let merged = if pool.len() <= save_shared::INVENTORY_SLOTS {
                 arrange_inventory_under_12(pool)
             } else {
-                arrange_inventory_over_12(trim_inventory_over_12(pool, &current_level, &difficulty))
+                arrange_inventory_over_12(trim_inventory_over_12(pool, &current_level, difficulty))
             };
 
             // Survivor: real EOS-suffixed key preferred, else highest score.
-            let survivor = match indices
-                .iter()
-                .find(|&&i| matches!(&entries[i].key, Property::Str(k) if is_real_eos_key(k)))
-            {
+            let survivor = match indices.iter().find(|&&i| is_real_eos_key(&players[i].key)) {
                 Some(&i) => i,
-                None => *indices
-                    .iter()
-                    .max_by_key(|&&i| {
-                        (if entry_has_real_data(&entries[i].value) {
-                            10
-                        } else {
-                            0
-                        }) + (if matches!(&entries[i].key, Property::Str(k) if k.contains("_+_|")) {
-                            1
-                        } else {
-                            0
-                        })
-                    })
-                    .unwrap(),
+                None => best_by(indices, &|k| k.contains("_+_|")),
             };
 
             // Write the merged inventory into the survivor entry.
-            if let Property::Struct(StructValue::Struct(props)) = &mut entries[survivor].value {
-                if let Some(Property::Array(ValueVec::Name(names))) =
-                    save_shared::get_property_by_name_mut(props, save_shared::INVENTORY_PROP_NAME)
-                {
-                    *names = merged;
-                } else {
-                    props.0.insert(
-                        PropertyKey(0, save_shared::INVENTORY_PROP_NAME.to_string()),
-                        save_shared::create_inventory_property(merged),
-                    );
-                }
-            }
+            save.set_player(&PlayerEntry {
+                key: players[survivor].key.clone(),
+                sanity: None,
+                inventory: merged,
+            });
 
             tracing::info!(
                 "Merged {} player entries into key '{}'",
                 indices.len(),
-                match &entries[survivor].key {
-                    Property::Str(k) => k.clone(),
-                    _ => "?".to_string(),
-                }
+                players[survivor].key
             );
-            for &i in indices {
-                if i != survivor {
-                    remove.push(i);
-                }
-            }
+            remove.extend(indices.iter().filter(|&&i| i != survivor));
             continue;
         }
 
         // Only one entry has real data — keep the single best (existing behavior).
-        // Score: real data dominates, real EOS key breaks ties
-        let score = |i: &usize| {
-            let data = if entry_has_real_data(&entries[*i].value) {
-                10
-            } else {
-                0
-            };
-            let eos = if let Property::Str(k) = &entries[*i].key {
-                if is_real_eos_key(k) {
-                    1
-                } else {
-                    0
-                }
-            } else {
-                0
-            };
-            data + eos
-        };
-        let best = *indices.iter().max_by_key(|&i| score(i)).unwrap();
-        for &i in indices {
-            if i != best {
-                remove.push(i);
-            }
-        }
+        let best = best_by(indices, &is_real_eos_key);
+        remove.extend(indices.iter().filter(|&&i| i != best));
     }
 
-    remove.sort_unstable();
-    for &i in remove.iter().rev() {
-        entries.remove(i);
-    }
+    // Remove by key: retain_players also visits the bad-id placeholder, which
+    // players() skips, so positions would not line up
+    let drop_keys: std::collections::HashSet<&str> =
+        remove.iter().map(|&i| players[i].key.as_str()).collect();
+    save.retain_players(|p| !drop_keys.contains(p.key.as_str()));
     remove.len()
 }
 
 /// Process player data
-fn process_player_data(save: &mut Save, json_data: &JsonValue) -> AppResult<()> {
-    let player_data_key = PropertyKey(0, "PlayerData".to_string());
-
+fn process_player_data(save: &mut EtbSave, json_data: &JsonValue) -> AppResult<()> {
     // Collect Steam IDs from frontend data (source of truth)
     let mut steam_ids_from_frontend: Vec<String> = Vec::new();
     if let Some(player_inventory) = json_data["playerInventory"].as_object() {
@@ -755,91 +608,24 @@ fn process_player_data(save: &mut Save, json_data: &JsonValue) -> AppResult<()>
         }
     }
 
-    // Record schemas for PlayerData (idempotent — harmless to do up front)
-    record_player_data_schemas(save);
-
-    if let Some(player_data_prop) = save.root.properties.0.get_mut(&player_data_key) {
-        if let Property::Map(ref mut map_value) = player_data_prop {
-            // Remove players that are no longer in the frontend data
-            map_value.retain(|entry| match &entry.key {
-                Property::Str(s) => {
-                    let keep = steam_ids_from_frontend.iter().any(|id| id == s.trim());
-                    if !keep {
-                        tracing::info!("Removed deleted player: {}", s);
-                    }
-                    keep
-                }
-                _ => true,
-            });
-
-            // Update or create players from frontend data
-            for steam_id in &steam_ids_from_frontend {
-                let player_entry = map_value.iter_mut().find(
-                    |entry| matches!(&entry.key, Property::Str(s) if s.trim() == steam_id.trim()),
-                );
-
-                match player_entry {
-                    Some(entry) => {
-                        if let Property::Struct(StructValue::Struct(ref mut player_struct)) =
-                            &mut entry.value
-                        {
-                            update_player_data(player_struct, steam_id, json_data);
-                        }
-                    }
-                    None => {
-                        tracing::info!("Creating new player data: {}", steam_id);
-                        let new_player_struct = create_new_player_struct(steam_id, json_data);
-                        map_value.push(uesave::MapEntry {
-                            key: Property::Str(steam_id.clone()),
-                            value: Property::Struct(StructValue::Struct(new_player_struct)),
-                        });
-                    }
-                }
-            }
-
-            // No players left after deletion — remove the whole field to avoid empty/ghost records
-            if map_value.is_empty() {
-                tracing::info!("All players removed, deleting PlayerData_0 field");
-                save.root.properties.0.shift_remove(&player_data_key);
-            }
+    // Remove players that are no longer in the frontend data
+    save.retain_players(|player| {
+        let keep = steam_ids_from_frontend
+            .iter()
+            .any(|id| id == player.key.trim());
+        if !keep {
+            tracing::info!("Removed deleted player: {}", player.key);
         }
-    } else if !steam_ids_from_frontend.is_empty() {
-        // Create new PlayerData_0 field
-        create_player_data_field(save, &steam_ids_from_frontend, json_data);
-    }
-
-    Ok(())
-}
+        keep
+    });
 
-/// Create PlayerData field
-fn create_player_data_field(save: &mut Save, steam_ids: &[String], json_data: &JsonValue) {
-    if steam_ids.is_empty() {
-        tracing::warn!("No player data provided, skipping PlayerData_0 creation");
-        return;
+    // Update or create players from frontend data
+    for steam_id in &steam_ids_from_frontend {
+        let player = player_entry_from_json(steam_id, json_data);
+        save.set_player(&player);
     }
 
-    tracing::info!("PlayerData_0 field not found, creating...");
-
-    record_player_data_schemas(save);
-
-    let mut map_value = Vec::new();
-
-    for steam_id in steam_ids.iter() {
-        tracing::info!("Creating new player data: {}", steam_id);
-        let new_player_struct = create_new_player_struct(steam_id, json_data);
-        map_value.push(uesave::MapEntry {
-            key: Property::Str(steam_id.clone()),
-            value: Property::Struct(StructValue::Struct(new_player_struct)),
-        });
-    }
-
-    let player_data_prop = Property::Map(map_value);
-
-    save.root
-        .properties
-        .0
-        .insert(PropertyKey(0, "PlayerData".to_string()), player_data_prop);
-    tracing::info!("Successfully created PlayerData_0 field");
+    Ok(())
 }
 
 /// Level list for unlocking hub doors (excluding unnecessary levels)
@@ -880,208 +666,55 @@ const HUB_DOOR_LEVELS: &[(&str, &str)] = &[
     ("Level 55.1", "TunnelLevel"),
 ];
 
-/// Record schemas for a LevelsCompleted struct element's fields
-fn record_level_struct_schemas(save: &mut Save) {
-    let parent = "LevelsCompleted";
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::DISPLAY_NAME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::StrProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::HAS_COMPLETED_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::HAS_UNLOCKED_HUB_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::BoolProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::LEVEL_NAME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::NameProperty),
-        },
-    );
-    save.schemas.record(
-        format!("{}.{}", parent, save_shared::TIME_FIELD),
-        PropertyTagPartial {
-            id: None,
-            data: PropertyTagDataPartial::Other(PropertyType::FloatProperty),
-        },
-    );
-    // World struct + nested Items/SanityLevel schemas
-    save_shared::create_default_world_property(save, parent);
-}
+/// Read a save and unlock every hub door in memory; returns the original bytes,
+/// the edited save and how many level records were added
+fn plan_unlock_hub_doors(
+    paths: &SavePaths,
+    file_path: &str,
+) -> AppResult<(Vec<u8>, EtbSave, usize)> {
+    paths.validate_save_games_path(Path::new(file_path))?;
 
-/// Create a single level struct (schemas are recorded by record_level_struct_schemas)
-fn create_level_struct(display_name: &str, level_name: &str) -> StructValue {
-    let mut level_props = Properties::default();
+    let original = fs::read(file_path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let mut save = EtbSave::read(&mut Cursor::new(&original))?;
 
-    // DisplayName
-    level_props.0.insert(
-        PropertyKey(0, save_shared::DISPLAY_NAME_FIELD.to_string()),
-        Property::Str(display_name.to_string()),
-    );
-
-    // HasCompleted - true
-    level_props.0.insert(
-        PropertyKey(0, save_shared::HAS_COMPLETED_FIELD.to_string()),
-        Property::Bool(true),
-    );
-
-    // HasUnlockedHub - true
-    level_props.0.insert(
-        PropertyKey(0, save_shared::HAS_UNLOCKED_HUB_FIELD.to_string()),
-        Property::Bool(true),
-    );
-
-    // LevelName
-    level_props.0.insert(
-        PropertyKey(0, save_shared::LEVEL_NAME_FIELD.to_string()),
-        Property::Name(level_name.to_string()),
-    );
-
-    // Time
-    level_props.0.insert(
-        PropertyKey(0, save_shared::TIME_FIELD.to_string()),
-        Property::Float(uesave::Float(-1.0)),
-    );
-
-    // World
-    level_props.0.insert(
-        PropertyKey(0, save_shared::WORLD_FIELD.to_string()),
-        save_shared::create_default_world_property_no_schema(),
-    );
-
-    StructValue::Struct(level_props)
-}
-
-/// Create default LevelsCompleted_0 property (empty array)
-fn create_default_levels_completed_property(save: &mut Save) -> Property {
-    save.schemas.record(
-        "LevelsCompleted".to_string(),
-        save_shared::array_struct_tag(StructType::Struct(Some("S_LevelStats".to_string()))),
-    );
-    record_level_struct_schemas(save);
-
-    Property::Array(ValueVec::Struct(vec![]))
+    let added = save.unlock_hub_doors(HUB_DOOR_LEVELS);
+    Ok((original, save, added))
 }
 
 /// Unlock all hub doors
 /// Reads LevelsCompleted_0 in the save, fills up to ALL_LEVELS count, and sets all Bool values to true
-pub fn unlock_all_hub_doors(file_path: &str) -> AppResult<String> {
+pub fn unlock_all_hub_doors(paths: &SavePaths, file_path: &str) -> AppResult<String> {
     tracing::info!("Unlocking all hub doors: {}", file_path);
 
-    validate_save_games_path(Path::new(file_path))?;
-
-    let file = File::open(file_path).map_err(|e| format!("Failed to open save file: {}", e))?;
-    let mut reader = BufReader::with_capacity(16384, file);
-    let mut save = Save::read(&mut reader).map_err(|e| format!("Failed to parse save: {:?}", e))?;
-
-    let levels_completed_key = PropertyKey(0, "LevelsCompleted".to_string());
-
-    // Create default structure when LevelsCompleted_0 does not exist
-    if !save.root.properties.0.contains_key(&levels_completed_key) {
-        tracing::warn!(
-            "LevelsCompleted_0 field not found, automatically creating default structure..."
-        );
-        let default_prop = create_default_levels_completed_property(&mut save);
-        save.root
-            .properties
-            .0
-            .insert(PropertyKey(0, "LevelsCompleted".to_string()), default_prop);
-    }
-
-    // Rebuild default structure if LevelsCompleted_0 format is invalid, preventing unlock flow interruption
-    let is_valid_levels_completed = matches!(
-        save.root.properties.0.get(&levels_completed_key),
-        Some(Property::Array(ValueVec::Struct { .. }))
-    );
-    if !is_valid_levels_completed {
-        tracing::warn!("LevelsCompleted_0 format is incorrect, rebuilding default structure...");
-        let default_prop = create_default_levels_completed_property(&mut save);
-        save.root
-            .properties
-            .0
-            .insert(PropertyKey(0, "LevelsCompleted".to_string()), default_prop);
-    }
-
-    // Record schemas for level struct fields up front (idempotent)
-    record_level_struct_schemas(&mut save);
-
-    // Get existing LevelsCompleted_0
-    let levels_completed_prop = save
-        .root
-        .properties
-        .0
-        .get_mut(&levels_completed_key)
-        .ok_or("Failed to create LevelsCompleted_0 field")?;
-
-    if let Property::Array(ValueVec::Struct(value)) = levels_completed_prop {
-        tracing::info!(
-            "Current level count: {}, target count: {}",
-            value.len(),
-            HUB_DOOR_LEVELS.len()
-        );
-
-        // Collect existing LevelNames
-        let mut existing_levels: std::collections::HashSet<String> =
-            std::collections::HashSet::new();
-
-        for level_struct in value.iter_mut() {
-            if let StructValue::Struct(props) = level_struct {
-                // Get LevelName
-                if let Some(level_name_prop) =
-                    props.0.iter().find(|(k, _)| k.1.starts_with("LevelName"))
-                {
-                    if let Property::Name(name) = &level_name_prop.1 {
-                        existing_levels.insert(name.clone());
-                    }
-                }
-
-                // Set all Bool values to true
-                for (_, prop) in props.0.iter_mut() {
-                    if let Property::Bool(ref mut b) = prop {
-                        *b = true;
-                    }
-                }
-            }
-        }
-
-        tracing::debug!("Existing levels: {:?}", existing_levels);
-
-        // Add missing levels
-        for (display_name, level_name) in HUB_DOOR_LEVELS.iter() {
-            if !existing_levels.contains(*level_name) {
-                tracing::info!("Adding missing level: {} ({})", display_name, level_name);
-                value.push(create_level_struct(display_name, level_name));
-            }
-        }
-
-        tracing::info!("Level count after processing: {}", value.len());
-    } else {
-        return Err("LevelsCompleted_0 format is incorrect".to_string().into());
-    }
+    // Read and write under the save's lock
+    let _lock = lock_save(paths, Path::new(file_path))?;
+    let (original, save, added) = plan_unlock_hub_doors(paths, file_path)?;
+    tracing::info!("Unlocked all recorded levels, added {} missing", added);
 
     // Write back to file
-    let file =
-        File::create(file_path).map_err(|e| format!("Failed to create output file: {}", e))?;
-    let mut writer = BufWriter::new(file);
-    save.write(&mut writer)
-        .map_err(|e| format!("Failed to write save: {:?}", e))?;
-    writer
-        .flush()
-        .map_err(|e| format!("Failed to flush buffer: {}", e))?;
+    let bytes = verified_bytes(&save, Some(&original), file_path)?;
+    backup_file(paths, Path::new(file_path), "unlock-doors")?;
+    write_atomic(Path::new(file_path), &bytes)?;
 
     tracing::info!("Hub door unlocking complete, save saved");
     Ok("Hub doors unlocked successfully".to_string())
 }
+
+/// Dry run of [`unlock_all_hub_doors`]
+pub fn preview_unlock_hub_doors(paths: &SavePaths, file_path: &str) -> AppResult<DryRunReport> {
+    let (original, save, added) = plan_unlock_hub_doors(paths, file_path)?;
+    verified_bytes(&save, Some(&original), file_path)?;
+
+    let before = EtbSave::read(&mut Cursor::new(&original))?;
+    let mut report = DryRunReport::default();
+    report.files.push(FileOp::Write {
+        path: file_path.to_string(),
+    });
+    report.push_diff(file_path, diff_roots(&before, &save));
+    if added > 0 {
+        report
+            .notes
+            .push(format!("Adds {} missing level record(s)", added));
+    }
+    Ok(report)
+}
@@ -0,0 +1,393 @@
+//! Save lint module - Consistency checks over a parsed save, with optional fixes
+//! Each issue carries a severity, a description and (when one is safe) a fix the
+//! frontend can hand back to `apply_lint_fixes`.
+
+use crate::common::{run_blocking, write_atomic};
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{
+    is_placeholder_eos_key, is_real_eos_key, pure_player_id, Difficulty, EtbSave, LevelCompletion,
+    PlayerEntry, BAD_PLAYER_KEY, PIPES, PIPES2,
+};
+use crate::file_lock::lock_save;
+use crate::new_save::ALL_LEVELS;
+use crate::save_backup::backup_file;
+use crate::save_diff::{diff_roots, DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_verify::verified_bytes;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Which check produced an issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintCode {
+    CurrentLevelNotRecorded,
+    UnlockedFunMismatch,
+    DifficultyMismatch,
+    PlaceholderPlayerKey,
+    BadPlayerKey,
+    SanityOutOfRange,
+}
+
+/// A change that resolves one issue. Sent back as-is to apply it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LintFix {
+    /// Append a LevelsCompleted record (not completed) for `level`
+    AddLevelRecord {
+        level: String,
+        display: String,
+    },
+    SetUnlockedFun {
+        value: bool,
+    },
+    /// Set the in-game difficulty (display label)
+    SetDifficulty {
+        difficulty: String,
+    },
+    RemovePlayer {
+        key: String,
+    },
+    SetSanity {
+        key: String,
+        value: f32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintIssue {
+    pub code: LintCode,
+    pub severity: Severity,
+    pub description: String,
+    pub fix: Option<LintFix>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub file_path: String,
+    pub issues: Vec<LintIssue>,
+    /// Fixes applied before this report was taken (0 for a plain lint)
+    pub applied: usize,
+}
+
+fn issue(
+    code: LintCode,
+    severity: Severity,
+    description: String,
+    fix: Option<LintFix>,
+) -> LintIssue {
+    LintIssue {
+        code,
+        severity,
+        description,
+        fix,
+    }
+}
+
+/// Run every check. `file_name` enables the file-name difficulty check.
+pub fn lint(save: &EtbSave, file_name: Option<&str>) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    check_levels(save, &mut issues);
+    if let Some(file_name) = file_name {
+        check_difficulty(save, file_name, &mut issues);
+    }
+    check_players(save, &mut issues);
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+/// CurrentLevel must have a LevelsCompleted record, and UnlockedFun must agree
+/// with whether the save reached the second Pipe Dreams visit
+fn check_levels(save: &EtbSave, issues: &mut Vec<LintIssue>) {
+    let levels = save.levels_completed();
+    let recorded = |name: &str| {
+        levels
+            .iter()
+            .any(|l| l.level_name.eq_ignore_ascii_case(name))
+    };
+    let raw_level = save.raw_current_level();
+    let on_pipes = raw_level == Some(PIPES);
+
+    // A save without CurrentLevel starts in Level 0 and may have no records yet
+    if raw_level.is_some() {
+        let current = save.current_level();
+        if !recorded(&current) {
+            let display = ALL_LEVELS
+                .iter()
+                .find(|(_, level)| level.eq_ignore_ascii_case(&current))
+                .map_or(current.as_str(), |(display, _)| display);
+            issues.push(issue(
+                LintCode::CurrentLevelNotRecorded,
+                Severity::Warning,
+                format!(
+                    "CurrentLevel is {} but LevelsCompleted has no record for it",
+                    current
+                ),
+                Some(LintFix::AddLevelRecord {
+                    level: current.clone(),
+                    display: display.to_string(),
+                }),
+            ));
+        }
+    }
+
+    // On Pipes a missing Pipes2 record is already reported above
+    if save.unlocked_fun() && !on_pipes && !recorded(PIPES2) {
+        issues.push(issue(
+            LintCode::UnlockedFunMismatch,
+            Severity::Warning,
+            "UnlockedFun is set but the save never reached the second Pipe Dreams visit"
+                .to_string(),
+            Some(LintFix::SetUnlockedFun { value: false }),
+        ));
+    }
+    if !save.unlocked_fun() && on_pipes && recorded(PIPES2) {
+        issues.push(issue(
+            LintCode::UnlockedFunMismatch,
+            Severity::Warning,
+            "LevelsCompleted records the second Pipe Dreams visit but UnlockedFun is not set, \
+             so the game loads the first one"
+                .to_string(),
+            Some(LintFix::SetUnlockedFun { value: true }),
+        ));
+    }
+}
+
+/// The difficulty suffix of `MODE_Name_Difficulty.sav` should match the save's
+/// Difficulty (a deliberate mismatch is possible, hence Info)
+fn check_difficulty(save: &EtbSave, file_name: &str, issues: &mut Vec<LintIssue>) {
+    let Some(from_name) = file_name
+        .strip_suffix(".sav")
+        .and_then(|stem| stem.rsplit_once('_'))
+        .and_then(|(_, suffix)| Difficulty::from_label(suffix))
+    else {
+        return;
+    };
+    let actual = save.difficulty();
+    if actual != from_name {
+        issues.push(issue(
+            LintCode::DifficultyMismatch,
+            Severity::Info,
+            format!(
+                "File name says {} but the in-game difficulty is {}",
+                from_name.label(),
+                actual.label()
+            ),
+            Some(LintFix::SetDifficulty {
+                difficulty: from_name.label().to_string(),
+            }),
+        ));
+    }
+}
+
+/// Placeholder / bad keys the game ignores, and sanity outside 0–100
+fn check_players(save: &EtbSave, issues: &mut Vec<LintIssue>) {
+    let keys = save.player_keys();
+    let players = save.players();
+
+    for key in &keys {
+        if *key == BAD_PLAYER_KEY {
+            issues.push(issue(
+                LintCode::BadPlayerKey,
+                Severity::Warning,
+                format!("PlayerData holds a '{}' entry", BAD_PLAYER_KEY),
+                Some(LintFix::RemovePlayer {
+                    key: key.to_string(),
+                }),
+            ));
+        } else if is_placeholder_eos_key(key) {
+            let pure = pure_player_id(key);
+            let has_real_sibling = keys
+                .iter()
+                .any(|k| is_real_eos_key(k) && pure_player_id(k) == pure);
+            let has_data = players.iter().any(|p| p.key == *key && p.has_real_data());
+            // Only drop data when a real entry exists or there is nothing to lose
+            let fix = (has_real_sibling || !has_data).then(|| LintFix::RemovePlayer {
+                key: key.to_string(),
+            });
+            issues.push(issue(
+                LintCode::PlaceholderPlayerKey,
+                Severity::Warning,
+                format!(
+                    "Player {} has an all-zeros EOS id the game ignores{}",
+                    pure,
+                    if fix.is_none() {
+                        "; load the save in-game once to get a real key"
+                    } else {
+                        ""
+                    }
+                ),
+                fix,
+            ));
+        }
+    }
+
+    for player in &players {
+        let Some(sanity) = player.sanity else {
+            continue;
+        };
+        if sanity.is_nan() || !(0.0..=100.0).contains(&sanity) {
+            let value = if sanity.is_nan() {
+                100.0
+            } else {
+                sanity.clamp(0.0, 100.0)
+            };
+            issues.push(issue(
+                LintCode::SanityOutOfRange,
+                Severity::Error,
+                format!(
+                    "Player {} has sanity {} (expected 0–100)",
+                    pure_player_id(&player.key),
+                    sanity
+                ),
+                Some(LintFix::SetSanity {
+                    key: player.key.clone(),
+                    value,
+                }),
+            ));
+        }
+    }
+}
+
+/// Apply fixes in order; returns how many changed the save
+pub fn apply_fixes(save: &mut EtbSave, fixes: &[LintFix]) -> AppResult<usize> {
+    let mut applied = 0;
+    for fix in fixes {
+        let changed = match fix {
+            LintFix::AddLevelRecord { level, display } => {
+                save.add_level_completed(&LevelCompletion::new(display, level, false))
+            }
+            LintFix::SetUnlockedFun { value } => {
+                let changed = save.unlocked_fun() != *value;
+                save.set_unlocked_fun(*value);
+                changed
+            }
+            LintFix::SetDifficulty { difficulty } => {
+                let difficulty = Difficulty::from_label(difficulty).ok_or_else(|| {
+                    AppError::Validation(format!("Invalid difficulty: {}", difficulty))
+                })?;
+                let changed = save.difficulty() != difficulty;
+                save.set_difficulty(difficulty);
+                changed
+            }
+            LintFix::RemovePlayer { key } => save.remove_player(key),
+            LintFix::SetSanity { key, value } => {
+                match save.players().into_iter().find(|p| &p.key == key) {
+                    Some(player) => {
+                        save.set_player(&PlayerEntry {
+                            sanity: Some(*value),
+                            ..player
+                        });
+                        true
+                    }
+                    None => false,
+                }
+            }
+        };
+        if changed {
+            tracing::info!("Applied lint fix: {:?}", fix);
+            applied += 1;
+        }
+    }
+    Ok(applied)
+}
+
+fn file_name_of(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|n| n.to_str())
+}
+
+/// Lint a save on disk
+pub fn lint_file(path: &Path) -> AppResult<LintReport> {
+    let original = fs::read(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let save = EtbSave::read(&mut Cursor::new(&original))?;
+    Ok(LintReport {
+        file_path: path.to_string_lossy().into_owned(),
+        issues: lint(&save, file_name_of(path)),
+        applied: 0,
+    })
+}
+
+/// Apply `fixes` to a save on disk and return the issues that remain.
+/// Nothing is written when no fix changed the save.
+pub fn fix_file(paths: &SavePaths, path: &Path, fixes: &[LintFix]) -> AppResult<LintReport> {
+    let _lock = lock_save(paths, path)?;
+    let original = fs::read(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let mut save = EtbSave::read(&mut Cursor::new(&original))?;
+
+    let applied = apply_fixes(&mut save, fixes)?;
+    if applied > 0 {
+        let bytes = verified_bytes(&save, Some(&original), &path.to_string_lossy())?;
+        backup_file(paths, path, "lint-fix")?;
+        write_atomic(path, &bytes)?;
+        tracing::info!("Applied {} lint fix(es) to {:?}", applied, path);
+    }
+
+    Ok(LintReport {
+        file_path: path.to_string_lossy().into_owned(),
+        issues: lint(&save, file_name_of(path)),
+        applied,
+    })
+}
+
+/// Dry run of [`fix_file`]: the changes `fixes` would make
+pub fn preview_fix_file(path: &Path, fixes: &[LintFix]) -> AppResult<DryRunReport> {
+    let original = fs::read(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let mut save = EtbSave::read(&mut Cursor::new(&original))?;
+
+    let mut report = DryRunReport::default();
+    if apply_fixes(&mut save, fixes)? > 0 {
+        verified_bytes(&save, Some(&original), &path.to_string_lossy())?;
+        let before = EtbSave::read(&mut Cursor::new(&original))?;
+        let file = path.to_string_lossy().into_owned();
+        report.files.push(FileOp::Write { path: file.clone() });
+        report.push_diff(file, diff_roots(&before, &save));
+    }
+    Ok(report)
+}
+
+/// Check a save for inconsistencies.
+#[tauri::command]
+pub async fn lint_save_file(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+) -> AppResult<LintReport> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let path = Path::new(&file_path);
+        paths.validate_save_games_path(path)?;
+        lint_file(path)
+    })
+    .await
+}
+
+/// Apply the chosen lint fixes to a save.
+#[tauri::command]
+pub async fn apply_lint_fixes(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    fixes: Vec<LintFix>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<LintReport>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let path = Path::new(&file_path);
+        paths.validate_save_games_path(path)?;
+        if dry_run.unwrap_or(false) {
+            return preview_fix_file(path, &fixes).map(Outcome::DryRun);
+        }
+        fix_file(&paths, path, &fixes).map(Outcome::Applied)
+    })
+    .await
+}
@@ -38,7 +38,7 @@ fn convert_filename(original: &str) -> Option<String> {
 /// all archives are unified under the MULTIPLAYER_ prefix.
 ///
 /// # Arguments
-/// * `save_dir` - Path to the SaveGames directory
+/// * `paths` - Active save paths (SaveGames directory + MAINSAVE)
 ///
 /// # Returns
 /// A vector of `ConversionResult` indicating the outcome of each conversion attempt.
@@ -49,8 +49,9 @@ fn convert_filename(original: &str) -> Option<String> {
 ///
 /// # Requirements
 /// - Requirements 3.1, 3.2, 3.4, 3.5
-pub fn convert_singleplayer_archives(save_dir: &Path) -> Vec<ConversionResult> {
+pub fn convert_singleplayer_archives(paths: &SavePaths) -> Vec<ConversionResult> {
     let mut conversions = Vec::new();
+    let save_dir = paths.save_games_dir();
 
     // Read all entries in the SaveGames directory
     let entries = match fs::read_dir(save_dir) {
@@ -93,6 +94,15 @@ pub fn convert_singleplayer_archives(save_dir: &Path) -> Vec<ConversionResult> {
 
         let new_path = save_dir.join(&new_filename);
 
+        // Another process is changing one of the two names: leave it for next time
+        let _locks = match lock_saves(paths, [path.as_path(), new_path.as_path()]) {
+            Ok(locks) => locks,
+            Err(e) => {
+                tracing::warn!("Skipping conversion of {}: {}", filename, e);
+                continue;
+            }
+        };
+
         // Perform the rename operation
         match fs::rename(&path, &new_path) {
             Ok(()) => {
@@ -103,7 +113,8 @@ pub fn convert_singleplayer_archives(save_dir: &Path) -> Vec<ConversionResult> {
                 );
                 
                 // Update MAINSAVE.sav's SingleplayerSaves list
-                if let Err(e) = crate::common::update_mainsave_archive_name(filename, &new_filename)
+                if let Err(e) =
+                    crate::common::update_mainsave_archive_name(paths, filename, &new_filename)
                 {
                     tracing::error!("Failed to update MAINSAVE for {}: {}", filename, e);
                 }
@@ -133,78 +144,150 @@ pub fn convert_singleplayer_archives(save_dir: &Path) -> Vec<ConversionResult> {
     conversions
 }
 
-use crate::common::{extract_archive_name, get_visible_saves_set};
-use crate::error::AppResult;
+use crate::common::{extract_archive_name, run_blocking, visible_saves_for_listing};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::lock_saves;
 use crate::get_file_path;
+use crate::library_cursor::LibraryCursors;
+use crate::library_events::emit_mainsave_unreadable;
+use crate::metadata_index::{self, DetailDepth, IndexedDetails, MetadataIndex};
+use crate::save_paths::{SavePaths, SavePathsState};
 use crate::save_utils;
 use crate::save_utils::{SaveFileDetail, SaveFileInfo, SaveFileMeta};
 use rayon::prelude::*;
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
+use tauri::AppHandle;
 
-/// Load all save files with full parsing
-#[tauri::command]
-pub async fn load_all_saves() -> AppResult<Vec<SaveFileInfo>> {
-    let start_time = Instant::now();
-
+/// Scan a SaveGames root and build full save info. Only visible saves are parsed
+/// (hidden ones keep their filename-derived difficulty and an empty level).
+/// An unreadable MAINSAVE lists every save as hidden and is returned alongside.
+pub fn collect_saves(save_paths: &SavePaths) -> AppResult<(Vec<SaveFileInfo>, Option<AppError>)> {
     // Parallel fetch file list and visible saves set
-    let (paths_result, visible_saves_result) =
-        rayon::join(get_file_path::list_save_paths, get_visible_saves_set);
+    let (paths_result, (visible_saves, mainsave_error)) = rayon::join(
+        || get_file_path::list_save_paths(save_paths),
+        || visible_saves_for_listing(save_paths),
+    );
 
     let paths = paths_result?;
-    let visible_saves = Arc::new(visible_saves_result?);
-    let path_count = paths.len();
 
-    // Use rayon to process all save files in parallel
-    let results: Vec<SaveFileInfo> = paths
-        .into_par_iter()
+    // Details come from the metadata index; only changed visible saves get parsed
+    let visible_paths: Vec<PathBuf> = paths
+        .iter()
+        .filter(|p| is_visible(p, &visible_saves))
+        .cloned()
+        .collect();
+    let mut details: HashMap<PathBuf, IndexedDetails> = visible_paths
+        .iter()
+        .cloned()
+        .zip(metadata_index::details_for(save_paths, &visible_paths, DetailDepth::Basic))
+        .filter_map(|(path, detail)| Some((path, detail?)))
+        .collect();
+    match MetadataIndex::open(save_paths).and_then(|index| index.prune(save_paths, &paths)) {
+        Ok(0) => {}
+        Ok(n) => tracing::debug!("Pruned {} stale metadata index row(s)", n),
+        Err(e) => tracing::warn!("Failed to prune metadata index: {}", e),
+    }
+
+    let saves = paths
+        .into_iter()
         .enumerate()
-        .filter_map(|(i, path)| process_save_file(i, &path, &visible_saves))
+        .filter_map(|(i, path)| {
+            let detail = details.remove(&path);
+            process_save_file(save_paths, i, &path, &visible_saves, detail)
+        })
         .collect();
+    Ok((saves, mainsave_error))
+}
+
+#[inline]
+fn is_visible(path: &Path, visible_saves: &HashSet<String>) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| visible_saves.contains(extract_archive_name(n)))
+}
+
+/// Load all save files with full parsing.
+/// `library` selects a library profile; `None` loads the active one.
+/// An unreadable MAINSAVE is reported with a `mainsave-unreadable` event.
+#[tauri::command]
+pub async fn load_all_saves(
+    app: AppHandle,
+    state: tauri::State<'_, SavePathsState>,
+    library: Option<String>,
+) -> AppResult<Vec<SaveFileInfo>> {
+    let start_time = Instant::now();
+    let save_paths = state.for_library(library.as_deref())?;
+
+    let (results, mainsave_error) = collect_saves(&save_paths)?;
+    emit_mainsave_unreadable(&app, mainsave_error);
 
     let elapsed = start_time.elapsed();
     tracing::info!(
-        "load_all_saves: {}/{} saves, took {:.2}ms",
+        "load_all_saves: {} saves, took {:.2}ms",
         results.len(),
-        path_count,
         elapsed.as_secs_f64() * 1000.0
     );
 
     Ok(results)
 }
 
+/// Filename-derived metadata for one save, `None` when the name doesn't parse
+pub fn save_meta(
+    save_paths: &SavePaths,
+    index: u32,
+    path: &Path,
+    visible_saves: &HashSet<String>,
+) -> Option<SaveFileMeta> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let archive_name = extract_archive_name(file_name);
+    let date = cli_handlers::get_modified_date(path).unwrap_or_default();
+    let is_visible = visible_saves.contains(archive_name);
+
+    save_utils::build_save_meta(save_paths, index, path, date, is_visible).ok()
+}
+
+/// Card details for one save (via the metadata index), `None` when it can't be read
+pub fn save_detail(save_paths: &SavePaths, path: &Path) -> Option<SaveFileDetail> {
+    metadata_index::details_for(save_paths, &[path.to_path_buf()], DetailDepth::Basic)
+        .pop()
+        .flatten()
+        .map(SaveFileDetail::from)
+}
+
 /// Phase 1 of incremental loading: return only filename-derived metadata,
 /// no .sav file parsing. Extremely fast even for 1000+ files.
+/// An unreadable MAINSAVE is reported with a `mainsave-unreadable` event.
 #[tauri::command]
-pub async fn load_save_metadata() -> AppResult<Vec<SaveFileMeta>> {
+pub async fn load_save_metadata(
+    app: AppHandle,
+    state: tauri::State<'_, SavePathsState>,
+    library: Option<String>,
+) -> AppResult<Vec<SaveFileMeta>> {
     let start_time = Instant::now();
+    let save_paths = state.for_library(library.as_deref())?;
 
     // Phase 0: Convert any SINGLEPLAYER_ archives to MULTIPLAYER_
-    let save_dir = crate::common::get_save_games_dir()?;
-    let conversions = convert_singleplayer_archives(&save_dir);
+    let conversions = convert_singleplayer_archives(&save_paths);
     if !conversions.is_empty() {
         tracing::info!("Converted {} singleplayer archives", conversions.len());
     }
 
-    let (paths_result, visible_saves_result) =
-        rayon::join(get_file_path::list_save_paths, get_visible_saves_set);
+    let (paths_result, (visible_saves, mainsave_error)) = rayon::join(
+        || get_file_path::list_save_paths(&save_paths),
+        || visible_saves_for_listing(&save_paths),
+    );
 
     let paths = paths_result?;
-    let visible_saves = Arc::new(visible_saves_result?);
+    let visible_saves = Arc::new(visible_saves);
+    emit_mainsave_unreadable(&app, mainsave_error);
 
     let results: Vec<SaveFileMeta> = paths
         .into_par_iter()
         .enumerate()
-        .filter_map(|(i, path)| {
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            let archive_name = extract_archive_name(file_name);
-            let date = cli_handlers::get_modified_date(&path).unwrap_or_default();
-            let is_visible = visible_saves.contains(archive_name);
-
-            save_utils::build_save_meta(i as u32, &path, date, is_visible).ok()
-        })
+        .filter_map(|(i, path)| save_meta(&save_paths, i as u32, &path, &visible_saves))
         .collect();
 
     let elapsed = start_time.elapsed();
@@ -218,81 +301,85 @@ pub async fn load_save_metadata() -> AppResult<Vec<SaveFileMeta>> {
 }
 
 /// Paginated metadata loading for progressive scroll.
-/// Returns a page of SaveFileMeta starting from the given `offset`,
-/// so the frontend can progressively fetch archives as the user scrolls.
+/// Without a `cursor` the library is scanned once into a snapshot and the first
+/// page is cut from it; passing the returned cursor back serves later pages from
+/// the same snapshot, so ids are stable and pages never shift or repeat.
+/// An expired or invalidated cursor fails with a `cursorExpired` error.
 /// Returns SaveFileMetaPage with the total count so the frontend knows when the list ends.
+/// Capturing a snapshot with an unreadable MAINSAVE emits `mainsave-unreadable`.
 #[tauri::command]
 pub async fn load_save_metadata_page(
+    app: AppHandle,
+    state: tauri::State<'_, SavePathsState>,
+    cursors: tauri::State<'_, LibraryCursors>,
     offset: u32,
     limit: u32,
+    library: Option<String>,
+    cursor: Option<String>,
 ) -> AppResult<save_utils::SaveFileMetaPage> {
     let start_time = Instant::now();
 
-    let (paths_result, visible_saves_result) =
-        rayon::join(get_file_path::list_save_paths, get_visible_saves_set);
-
-    let paths = paths_result?;
-    let visible_saves = visible_saves_result?;
-    let total = paths.len() as u32;
-
-    // Clamp to valid range
-    let start = offset.min(total) as usize;
-    let end = (offset + limit).min(total) as usize;
-    let page_paths = &paths[start..end];
-
-    let mut results: Vec<SaveFileMeta> = Vec::with_capacity(page_paths.len());
-    for (rel_i, path) in page_paths.iter().enumerate() {
-        let global_idx = (start + rel_i) as u32;
-        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        let archive_name = extract_archive_name(file_name);
-        let date = cli_handlers::get_modified_date(path).unwrap_or_default();
-        let is_visible = visible_saves.contains(archive_name);
-
-        if let Ok(meta) = save_utils::build_save_meta(global_idx, path, date, is_visible) {
-            results.push(meta);
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => {
+            let save_paths = state.for_library(library.as_deref())?;
+            let (cursor, mainsave_error) = cursors.capture(&save_paths)?;
+            emit_mainsave_unreadable(&app, mainsave_error);
+            cursor
         }
-    }
+    };
+    let page = cursors.page(&cursor, offset, limit)?;
 
     let elapsed = start_time.elapsed();
     tracing::info!(
         "load_save_metadata_page(offset={}, limit={}): {} items of {}, took {:.2}ms",
         offset,
         limit,
-        results.len(),
-        total,
+        page.items.len(),
+        page.total,
         elapsed.as_secs_f64() * 1000.0
     );
 
-    Ok(save_utils::SaveFileMetaPage {
-        items: results,
-        total,
-        offset,
-        has_more: end < total as usize,
-    })
+    Ok(page)
+}
+
+/// Drop a pagination snapshot early. Returns whether it still existed.
+#[tauri::command]
+pub fn release_library_cursor(
+    cursors: tauri::State<'_, LibraryCursors>,
+    cursor: String,
+) -> AppResult<bool> {
+    cursors.release(&cursor)
 }
 
 /// Phase 2 of incremental loading: parse specific .sav files in batch
 /// to get current_level and actual_difficulty.
+/// Unchanged files are answered from the metadata index without parsing.
+/// `library` selects a library profile; `None` loads the active one.
 #[tauri::command]
-pub async fn load_save_details_batch(paths: Vec<String>) -> AppResult<Vec<SaveFileDetail>> {
+pub async fn load_save_details_batch(
+    state: tauri::State<'_, SavePathsState>,
+    paths: Vec<String>,
+    library: Option<String>,
+) -> AppResult<Vec<SaveFileDetail>> {
     let start_time = Instant::now();
     let count = paths.len();
+    let save_paths = state.for_library(library.as_deref())?;
 
-    let results: Vec<SaveFileDetail> = paths
-        .into_par_iter()
-        .filter_map(|path| {
-            let p = Path::new(&path);
-            cli_handlers::parse_sav_file(p).ok().map(|save| {
-                let current_level = cli_handlers::extract_current_level(&save);
-                let actual_difficulty = cli_handlers::extract_difficulty_label(&save).into_owned();
-                SaveFileDetail {
-                    path,
-                    current_level,
-                    actual_difficulty,
-                }
-            })
-        })
-        .collect();
+    let results: Vec<SaveFileDetail> = run_blocking(move || {
+        let files: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        for file in &files {
+            save_paths.validate_save_games_path(file)?;
+        }
+        Ok(
+            metadata_index::details_for(&save_paths, &files, DetailDepth::Basic)
+                .into_iter()
+                .flatten()
+                .map(SaveFileDetail::from)
+                .collect(),
+        )
+    })
+    .await?;
 
     let elapsed = start_time.elapsed();
     tracing::info!(
@@ -308,21 +395,21 @@ pub async fn load_save_details_batch(paths: Vec<String>) -> AppResult<Vec<SaveFi
 /// Process a single save file (optimized version)
 #[inline]
 fn process_save_file(
+    save_paths: &SavePaths,
     index: usize,
     path: &Path,
-    visible_saves: &Arc<HashSet<String>>,
+    visible_saves: &HashSet<String>,
+    detail: Option<IndexedDetails>,
 ) -> Option<SaveFileInfo> {
-    let file_name = path.file_name().and_then(|n| n.to_str())?;
-    let archive_name = extract_archive_name(file_name);
     let date = cli_handlers::get_modified_date(path).unwrap_or_default();
-    let is_visible = visible_saves.contains(archive_name);
+    let is_visible = is_visible(path, visible_saves);
 
-    // Skip parsing .sav if not visible (performance optimization)
+    // Hidden saves are never parsed (performance optimization)
     if !is_visible {
         return save_utils::build_save_file_info(
+            save_paths,
             index as u32,
             path,
-            archive_name,
             date,
             None,
             None,
@@ -331,19 +418,16 @@ fn process_save_file(
         .ok();
     }
 
-    // Parse .sav file for visible saves
-    cli_handlers::parse_sav_file(path).ok().and_then(|save| {
-        let current_level = cli_handlers::extract_current_level(&save);
-        let actual_difficulty = cli_handlers::extract_difficulty_label(&save).into_owned();
-        save_utils::build_save_file_info(
-            index as u32,
-            path,
-            archive_name,
-            date,
-            Some(current_level),
-            Some(actual_difficulty),
-            is_visible,
-        )
-        .ok()
-    })
+    // Visible saves that failed to parse are skipped
+    let detail = detail?;
+    save_utils::build_save_file_info(
+        save_paths,
+        index as u32,
+        path,
+        date,
+        Some(detail.current_level),
+        Some(detail.actual_difficulty),
+        is_visible,
+    )
+    .ok()
 }
@@ -0,0 +1,152 @@
+//! Save paths module - Single source of truth for SaveGames / MAINSAVE / app config locations
+//! Held in Tauri managed state and passed explicitly to every save operation,
+//! so the save root can be switched at runtime and all modules agree on it.
+
+use crate::common::validate_path_under_base;
+use crate::error::{AppError, AppResult};
+use crate::library_profiles;
+use crate::path_resolver::{self, SaveRootSource};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Resolved locations for one SaveGames root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavePaths {
+    save_games_dir: PathBuf,
+    app_config_dir: PathBuf,
+    library: Option<String>,
+}
+
+impl SavePaths {
+    pub fn new(save_games_dir: impl Into<PathBuf>, app_config_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            save_games_dir: save_games_dir.into(),
+            app_config_dir: app_config_dir.into(),
+            library: None,
+        }
+    }
+
+    /// Tag these paths with the library profile they belong to
+    pub fn with_library(mut self, library: impl Into<String>) -> Self {
+        self.library = Some(library.into());
+        self
+    }
+
+    /// Resolve from the environment, the active library profile, the configured
+    /// override and platform discovery
+    pub fn resolve() -> AppResult<Self> {
+        let (save_games_dir, source) = path_resolver::resolve_save_games_dir()?;
+        tracing::info!(
+            "SaveGames directory: {} ({:?})",
+            save_games_dir.display(),
+            source
+        );
+        let paths = Self::new(save_games_dir, path_resolver::resolve_app_config_dir()?);
+        let library = (source == SaveRootSource::Library)
+            .then(library_profiles::active_profile)
+            .flatten();
+        Ok(match library {
+            Some(profile) => paths.with_library(profile.name),
+            None => paths,
+        })
+    }
+
+    /// Paths of a named library profile, independent of which one is active.
+    /// The reserved name `default` addresses the default root.
+    pub fn for_library(name: &str) -> AppResult<Self> {
+        let app_config_dir = path_resolver::resolve_app_config_dir()?;
+        if library_profiles::is_default_library(name) {
+            let (save_games_dir, _) = path_resolver::resolve_default_save_games_dir()?;
+            return Ok(Self::new(save_games_dir, app_config_dir));
+        }
+        let profile = library_profiles::find_profile(name)?;
+        Ok(Self::new(profile.save_games_dir, app_config_dir).with_library(profile.name))
+    }
+
+    /// SaveGames directory (visible saves live directly inside it)
+    #[inline]
+    pub fn save_games_dir(&self) -> &Path {
+        &self.save_games_dir
+    }
+
+    /// MAINSAVE.sav path
+    #[inline]
+    pub fn mainsave_path(&self) -> PathBuf {
+        self.save_games_dir.join("MAINSAVE.sav")
+    }
+
+    /// Library profile name, `None` for the default root
+    #[inline]
+    pub fn library(&self) -> Option<&str> {
+        self.library.as_deref()
+    }
+
+    /// App config directory (`ETBSaveManager`)
+    #[inline]
+    pub fn app_config_dir(&self) -> &Path {
+        &self.app_config_dir
+    }
+
+    /// A save is "hidden" when it sits in a subfolder rather than directly in SaveGames.
+    /// Compares path components, so separators never cause a mismatch.
+    #[inline]
+    pub fn is_hidden(&self, path: &Path) -> bool {
+        path.parent() != Some(self.save_games_dir.as_path())
+    }
+
+    pub fn validate_save_games_path(&self, path: &Path) -> AppResult<()> {
+        validate_path_under_base(path, &self.save_games_dir)
+    }
+
+    #[allow(dead_code)]
+    pub fn validate_app_config_path(&self, path: &Path) -> AppResult<()> {
+        validate_path_under_base(path, &self.app_config_dir)
+    }
+}
+
+/// Tauri managed state wrapping the active [`SavePaths`].
+///
+/// Resolution may fail (no LOCALAPPDATA, no Proton prefix, no override yet); the
+/// error is kept and returned by `current()` until `reload()` succeeds.
+pub struct SavePathsState(RwLock<AppResult<Arc<SavePaths>>>);
+
+impl SavePathsState {
+    pub fn resolve() -> Self {
+        let resolved = SavePaths::resolve().map(Arc::new);
+        if let Err(ref e) = resolved {
+            tracing::warn!("SaveGames directory not resolved: {}", e);
+        }
+        Self(RwLock::new(resolved))
+    }
+
+    /// The active paths. Cheap to clone into `spawn_blocking` closures.
+    pub fn current(&self) -> AppResult<Arc<SavePaths>> {
+        self.0
+            .read()
+            .map_err(|e| AppError::General(format!("SavePaths lock poisoned: {}", e)))?
+            .clone()
+    }
+
+    /// Paths for `library`, or the active paths when no library is named
+    pub fn for_library(&self, library: Option<&str>) -> AppResult<Arc<SavePaths>> {
+        match library {
+            Some(name) => SavePaths::for_library(name).map(Arc::new),
+            None => self.current(),
+        }
+    }
+
+    /// Re-resolve after the override changed and swap it in for all later commands
+    pub fn reload(&self) -> AppResult<Arc<SavePaths>> {
+        self.replace(SavePaths::resolve().map(Arc::new))
+    }
+
+    /// Install paths resolved off the async runtime; see [`Self::reload`]
+    pub fn replace(&self, resolved: AppResult<Arc<SavePaths>>) -> AppResult<Arc<SavePaths>> {
+        *self
+            .0
+            .write()
+            .map_err(|e| AppError::General(format!("SavePaths lock poisoned: {}", e)))? =
+            resolved.clone();
+        resolved
+    }
+}
@@ -0,0 +1,568 @@
+//! Save query module - Server-side search, filter and sort over the library
+//! A query is whitespace-separated terms, e.g.
+//! `level:Poolrooms difficulty:Nightmare player:7656119 has:Crowbar sort:-date`.
+//! `key:value` terms filter, a leading `-` negates one, bare words match the save
+//! name, and values may be double-quoted. Level, difficulty and inventory terms
+//! are answered from the metadata index, so only changed saves are parsed.
+
+use crate::common::{run_blocking, visible_saves_for_listing};
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{self, Difficulty};
+use crate::get_file_path;
+use crate::library_events::emit_mainsave_unreadable;
+use crate::metadata_index::{self, DetailDepth, IndexedDetails, IndexedPlayer};
+use crate::save_loader::save_meta;
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_utils::SaveFileMeta;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    /// Substring of the save name
+    Name(String),
+    /// Current level (as the cards show it)
+    Level(String),
+    /// Difficulty stored in the save
+    Difficulty(String),
+    Mode(String),
+    /// Steam id prefix or full PlayerData key
+    Player(String),
+    /// Item carried by a player (by the `player:` ones, when given)
+    Has(String),
+    /// Listed in MAINSAVE
+    Visible(bool),
+    /// Lives in the hidden folder
+    Hidden(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    filter: Filter,
+    negated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SortKey {
+    /// Library listing order
+    #[default]
+    Listing,
+    Date,
+    Name,
+    Level,
+    Difficulty,
+    Size,
+}
+
+/// A parsed query
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SaveQuery {
+    terms: Vec<Term>,
+    sort: SortKey,
+    descending: bool,
+}
+
+/// Split on whitespace outside double quotes; quotes are dropped
+fn tokenize(query: &str) -> AppResult<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err(AppError::Validation("Unterminated quote in query".into()));
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn parse_bool(key: &str, value: &str) -> AppResult<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(AppError::Validation(format!(
+            "'{}:' expects true or false, got '{}'",
+            key, value
+        ))),
+    }
+}
+
+impl SaveQuery {
+    pub fn parse(query: &str) -> AppResult<Self> {
+        let mut parsed = Self::default();
+        for token in tokenize(query)? {
+            let (negated, token) = match token.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, token.as_str()),
+            };
+            let Some((key, value)) = token.split_once(':') else {
+                parsed.terms.push(Term {
+                    filter: Filter::Name(token.to_string()),
+                    negated,
+                });
+                continue;
+            };
+            let key = key.to_ascii_lowercase();
+            if value.is_empty() {
+                return Err(AppError::Validation(format!(
+                    "Missing value for '{}:' in query",
+                    key
+                )));
+            }
+            let value_string = value.to_string();
+            let filter = match key.as_str() {
+                "name" => Filter::Name(value_string),
+                "level" => Filter::Level(value_string),
+                "difficulty" => {
+                    if Difficulty::from_label(value).is_none() {
+                        return Err(AppError::Validation(format!(
+                            "Unknown difficulty '{}' (Easy, Normal, Hard or Nightmare)",
+                            value
+                        )));
+                    }
+                    Filter::Difficulty(value_string)
+                }
+                "mode" => Filter::Mode(value_string),
+                "player" => Filter::Player(value_string),
+                "has" => Filter::Has(value_string),
+                "visible" => Filter::Visible(parse_bool(&key, value)?),
+                "hidden" => Filter::Hidden(parse_bool(&key, value)?),
+                "sort" => {
+                    if negated {
+                        return Err(AppError::Validation(
+                            "Use 'sort:-field' for descending order".into(),
+                        ));
+                    }
+                    let (descending, field) = match value.strip_prefix('-') {
+                        Some(field) => (true, field),
+                        None => (false, value),
+                    };
+                    parsed.descending = descending;
+                    parsed.sort = match field.to_ascii_lowercase().as_str() {
+                        "date" => SortKey::Date,
+                        "name" => SortKey::Name,
+                        "level" => SortKey::Level,
+                        "difficulty" => SortKey::Difficulty,
+                        "size" => SortKey::Size,
+                        other => {
+                            return Err(AppError::Validation(format!(
+                                "Unknown sort field '{}' (date, name, level, difficulty or size)",
+                                other
+                            )))
+                        }
+                    };
+                    continue;
+                }
+                other => {
+                    return Err(AppError::Validation(format!(
+                        "Unknown query key '{}:'",
+                        other
+                    )))
+                }
+            };
+            parsed.terms.push(Term { filter, negated });
+        }
+        Ok(parsed)
+    }
+
+    /// How deep the details of every save must be read to evaluate the query
+    fn depth(&self) -> Option<DetailDepth> {
+        let filters = self.terms.iter().map(|t| &t.filter);
+        if filters
+            .clone()
+            .any(|f| matches!(f, Filter::Player(_) | Filter::Has(_)))
+        {
+            Some(DetailDepth::Full)
+        } else if filters
+            .clone()
+            .any(|f| matches!(f, Filter::Level(_) | Filter::Difficulty(_)))
+            || matches!(self.sort, SortKey::Level | SortKey::Difficulty)
+        {
+            Some(DetailDepth::Basic)
+        } else {
+            None
+        }
+    }
+
+    fn matches(&self, meta: &SaveFileMeta, details: Option<&IndexedDetails>) -> bool {
+        let players: &[IndexedPlayer] = details
+            .and_then(|d| d.extended.as_ref())
+            .map_or(&[], |e| &e.players);
+        // `has:` looks at the players the `player:` terms pick, or at everyone
+        let picked: Vec<&IndexedPlayer> = players
+            .iter()
+            .filter(|p| {
+                self.terms.iter().all(|t| match &t.filter {
+                    Filter::Player(id) if !t.negated => player_matches(p, id),
+                    _ => true,
+                })
+            })
+            .collect();
+        // Positive `player:` and `has:` terms must all hold for one and the same player
+        let needs_player = self
+            .terms
+            .iter()
+            .any(|t| !t.negated && matches!(t.filter, Filter::Player(_) | Filter::Has(_)));
+        if needs_player
+            && !picked.iter().any(|p| {
+                self.terms.iter().all(|t| match &t.filter {
+                    Filter::Has(item) if !t.negated => carries(p, item),
+                    _ => true,
+                })
+            })
+        {
+            return false;
+        }
+
+        self.terms.iter().all(|term| {
+            let hit = match &term.filter {
+                Filter::Name(text) => contains_ignore_case(&meta.name, text),
+                Filter::Level(level) => {
+                    details.is_some_and(|d| d.current_level.eq_ignore_ascii_case(level))
+                }
+                Filter::Difficulty(difficulty) => details
+                    .map_or(meta.difficulty.as_str(), |d| d.actual_difficulty.as_str())
+                    .eq_ignore_ascii_case(difficulty),
+                Filter::Mode(mode) => meta.mode.eq_ignore_ascii_case(mode),
+                Filter::Player(id) => players.iter().any(|p| player_matches(p, id)),
+                Filter::Has(item) => picked.iter().any(|p| carries(p, item)),
+                Filter::Visible(visible) => meta.is_visible.unwrap_or(false) == *visible,
+                Filter::Hidden(hidden) => meta.hidden == *hidden,
+            };
+            hit != term.negated
+        })
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn player_matches(player: &IndexedPlayer, id: &str) -> bool {
+    player.key.eq_ignore_ascii_case(id) || etb_save::pure_player_id(&player.key).starts_with(id)
+}
+
+fn carries(player: &IndexedPlayer, item: &str) -> bool {
+    player.items.iter().any(|i| i.eq_ignore_ascii_case(item))
+}
+
+/// One matching save
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveQueryHit {
+    pub meta: SaveFileMeta,
+    /// `None` when the save can't be read
+    pub details: Option<IndexedDetails>,
+}
+
+/// A page of query results; `total` counts every match
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveQueryPage {
+    pub items: Vec<SaveQueryHit>,
+    pub total: u32,
+    pub offset: u32,
+    pub has_more: bool,
+}
+
+struct Candidate {
+    path: PathBuf,
+    meta: SaveFileMeta,
+    details: Option<IndexedDetails>,
+    modified: Option<SystemTime>,
+}
+
+/// Evaluate `query` against the library and cut the requested page. An unreadable
+/// MAINSAVE makes every save hidden and is returned alongside the page.
+pub fn run_query(
+    paths: &SavePaths,
+    query: &SaveQuery,
+    offset: u32,
+    limit: u32,
+) -> AppResult<(SaveQueryPage, Option<AppError>)> {
+    let (paths_result, (visible_saves, mainsave_error)) = rayon::join(
+        || get_file_path::list_save_paths(paths),
+        || visible_saves_for_listing(paths),
+    );
+    // Ids are listing positions, as in the metadata pages and the library stream
+    let mut candidates: Vec<Candidate> = paths_result?
+        .into_iter()
+        .filter_map(|path| {
+            let meta = save_meta(paths, 0, &path, &visible_saves)?;
+            Some((path, meta))
+        })
+        .enumerate()
+        .map(|(i, (path, mut meta))| {
+            meta.id = i as u32;
+            Candidate {
+                modified: path.metadata().and_then(|m| m.modified()).ok(),
+                path,
+                meta,
+                details: None,
+            }
+        })
+        .collect();
+
+    if let Some(depth) = query.depth() {
+        let files: Vec<PathBuf> = candidates.iter().map(|c| c.path.clone()).collect();
+        for (candidate, details) in candidates
+            .iter_mut()
+            .zip(metadata_index::details_for(paths, &files, depth))
+        {
+            candidate.details = details;
+        }
+    }
+    candidates.retain(|c| query.matches(&c.meta, c.details.as_ref()));
+
+    let level = |c: &Candidate| c.details.as_ref().map(|d| d.current_level.clone());
+    let difficulty = |c: &Candidate| {
+        Difficulty::from_label(
+            c.details
+                .as_ref()
+                .map_or(c.meta.difficulty.as_str(), |d| d.actual_difficulty.as_str()),
+        )
+    };
+    // Stable, so ties keep listing order
+    candidates.sort_by(|a, b| {
+        let order = match query.sort {
+            SortKey::Listing => Ordering::Equal,
+            SortKey::Date => a.modified.cmp(&b.modified),
+            SortKey::Name => a.meta.name.to_lowercase().cmp(&b.meta.name.to_lowercase()),
+            SortKey::Level => level(a).cmp(&level(b)),
+            SortKey::Difficulty => difficulty(a).cmp(&difficulty(b)),
+            SortKey::Size => a.meta.file_size.cmp(&b.meta.file_size),
+        };
+        if query.descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+
+    let total = candidates.len() as u32;
+    let start = offset.min(total) as usize;
+    let end = offset.saturating_add(limit).min(total) as usize;
+    let mut page: Vec<Candidate> = candidates.drain(start..end).collect();
+
+    // The cards need level and difficulty even when the query didn't
+    if query.depth().is_none() && !page.is_empty() {
+        let files: Vec<PathBuf> = page.iter().map(|c| c.path.clone()).collect();
+        for (candidate, details) in page.iter_mut().zip(metadata_index::details_for(
+            paths,
+            &files,
+            DetailDepth::Basic,
+        )) {
+            candidate.details = details;
+        }
+    }
+
+    let page = SaveQueryPage {
+        items: page
+            .into_iter()
+            .map(|c| SaveQueryHit {
+                meta: c.meta,
+                details: c.details,
+            })
+            .collect(),
+        total,
+        offset,
+        has_more: end < total as usize,
+    };
+    Ok((page, mainsave_error))
+}
+
+/// Search the library with the query language described in this module's docs.
+/// Returns one page of the matches; an unreadable MAINSAVE is reported with a
+/// `mainsave-unreadable` event.
+#[tauri::command]
+pub async fn query_saves(
+    app: AppHandle,
+    state: tauri::State<'_, SavePathsState>,
+    query: String,
+    offset: u32,
+    limit: u32,
+    library: Option<String>,
+) -> AppResult<SaveQueryPage> {
+    let start_time = Instant::now();
+    let paths = state.for_library(library.as_deref())?;
+    let parsed = SaveQuery::parse(&query)?;
+    let (page, mainsave_error) =
+        run_blocking(move || run_query(&paths, &parsed, offset, limit)).await?;
+    emit_mainsave_unreadable(&app, mainsave_error);
+    tracing::info!(
+        "query_saves {:?}: {} match(es), took {:.2}ms",
+        query,
+        page.total,
+        start_time.elapsed().as_secs_f64() * 1000.0
+    );
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_index::ExtendedDetails;
+
+    const ALICE: &str = "76561198000000001_+_|0123456789abcdef0123456789abcdef";
+    const BOB: &str = "76561198000000002_+_|fedcba9876543210fedcba9876543210";
+
+    fn term(filter: Filter, negated: bool) -> Term {
+        Term { filter, negated }
+    }
+
+    fn meta(name: &str) -> SaveFileMeta {
+        SaveFileMeta {
+            id: 0,
+            name: name.to_string(),
+            difficulty: "Normal".to_string(),
+            mode: "Solo".to_string(),
+            date: String::new(),
+            hidden: false,
+            path: String::new(),
+            is_visible: Some(true),
+            file_size: 0,
+        }
+    }
+
+    /// Details with one carried item per player
+    fn details(level: &str, players: &[(&str, &str)]) -> IndexedDetails {
+        IndexedDetails {
+            path: String::new(),
+            current_level: level.to_string(),
+            actual_difficulty: "Hard".to_string(),
+            extended: Some(ExtendedDetails {
+                players: players
+                    .iter()
+                    .map(|(key, item)| IndexedPlayer {
+                        key: key.to_string(),
+                        items: vec![item.to_string()],
+                    })
+                    .collect(),
+                levels_completed: 0,
+                levels_total: 0,
+            }),
+        }
+    }
+
+    fn matches(query: &str, meta: &SaveFileMeta, details: Option<&IndexedDetails>) -> bool {
+        SaveQuery::parse(query).unwrap().matches(meta, details)
+    }
+
+    #[test]
+    fn parses_quoted_values_and_negation() {
+        let query = SaveQuery::parse(r#"level:"The Hub" "my run" -mode:Solo"#).unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(Filter::Level("The Hub".into()), false),
+                term(Filter::Name("my run".into()), false),
+                term(Filter::Mode("Solo".into()), true),
+            ]
+        );
+        assert_eq!(query.sort, SortKey::Listing);
+    }
+
+    #[test]
+    fn lone_dash_is_a_name_term() {
+        let query = SaveQuery::parse("-").unwrap();
+        assert_eq!(query.terms, vec![term(Filter::Name("-".into()), false)]);
+    }
+
+    #[test]
+    fn keys_and_bools_are_case_insensitive() {
+        let query = SaveQuery::parse("VISIBLE:Yes hidden:0").unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(Filter::Visible(true), false),
+                term(Filter::Hidden(false), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_prefix_sets_descending() {
+        let query = SaveQuery::parse("sort:-date").unwrap();
+        assert_eq!((query.sort, query.descending), (SortKey::Date, true));
+        assert!(query.terms.is_empty());
+
+        let query = SaveQuery::parse("sort:Name").unwrap();
+        assert_eq!((query.sort, query.descending), (SortKey::Name, false));
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for query in [
+            "-sort:date",
+            "sort:colour",
+            "colour:red",
+            "level:",
+            "difficulty:Impossible",
+            "visible:maybe",
+            "\"unterminated",
+        ] {
+            assert!(
+                matches!(SaveQuery::parse(query), Err(AppError::Validation(_))),
+                "{:?} should be rejected",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn name_terms_match_substrings_and_negate() {
+        let save = meta("Nightmare Run");
+        assert!(matches("run", &save, None));
+        assert!(matches("night -easy", &save, None));
+        assert!(!matches("-night", &save, None));
+        assert!(!matches("walk", &save, None));
+    }
+
+    #[test]
+    fn level_needs_details_and_difficulty_falls_back_to_meta() {
+        let save = meta("run");
+        let parsed = details("Poolrooms", &[]);
+        assert!(matches("level:poolrooms", &save, Some(&parsed)));
+        assert!(!matches("level:poolrooms", &save, None));
+        assert!(matches("difficulty:hard", &save, Some(&parsed)));
+        assert!(matches("difficulty:normal", &save, None));
+    }
+
+    #[test]
+    fn player_and_has_must_match_the_same_player() {
+        let save = meta("run");
+        let parsed = details("Level0", &[(ALICE, "Crowbar"), (BOB, "AlmondWater")]);
+        let found = |query: &str| matches(query, &save, Some(&parsed));
+
+        assert!(found("player:76561198000000001"));
+        assert!(found("player:76561198000000001 has:crowbar"));
+        assert!(found("player:76561198000000002 has:AlmondWater"));
+        assert!(!found("player:76561198000000001 has:AlmondWater"));
+        // Without player: any one player must carry every item
+        assert!(found("has:Crowbar"));
+        assert!(!found("has:Crowbar has:AlmondWater"));
+        assert!(!found("has:Crowbar -has:AlmondWater"));
+        assert!(!found("player:76561198000000003"));
+        assert!(found("-player:76561198000000003"));
+    }
+
+    #[test]
+    fn player_terms_need_player_data() {
+        assert!(!matches("has:Crowbar", &meta("run"), None));
+        assert!(matches("-has:Crowbar", &meta("run"), None));
+    }
+}
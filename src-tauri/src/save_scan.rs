@@ -0,0 +1,470 @@
+//! Save scan module - Partial GVAS reader for the card grid fields
+//! Walks the root property tags of a UE4 save and reads only CurrentLevel,
+//! Difficulty and UnlockedFun; every other payload (PlayerData, LevelsCompleted,
+//! ...) is skipped by its tagged size without being read. Anything unexpected is
+//! an error, and `scan_or_parse` then falls back to the full `Save::read`.
+
+use crate::cli_handlers;
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{self, Difficulty, CURRENT_LEVEL, DIFFICULTY, PIPES, UNLOCKED_FUN};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+/// GVAS magic, little-endian
+const GVAS_MAGIC: u32 = u32::from_le_bytes(*b"GVAS");
+/// Longest FString the scanner accepts; longer means a misread stream
+const MAX_STRING_LEN: usize = 64 * 1024;
+
+/// The root fields the card grid shows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanSummary {
+    /// Stored CurrentLevel name, `None` when absent
+    pub raw_current_level: Option<String>,
+    /// Difficulty enumerator label, `None` when absent (Normal)
+    pub difficulty_label: Option<String>,
+    /// Only guaranteed when CurrentLevel is Pipes, the one level it changes
+    pub unlocked_fun: bool,
+}
+
+impl ScanSummary {
+    /// Current level as the frontend names it (see [`etb_save::display_level`])
+    pub fn current_level(&self) -> String {
+        etb_save::display_level(self.raw_current_level.as_deref(), self.unlocked_fun)
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty_label
+            .as_deref()
+            .map_or(Difficulty::Normal, Difficulty::from_enumerator)
+    }
+}
+
+fn scan_error(message: impl Into<String>) -> AppError {
+    AppError::Parse(format!("Partial scan failed: {}", message.into()))
+}
+
+struct Scanner<R> {
+    /// Buffered so skipped payloads become in-buffer seeks
+    reader: BufReader<R>,
+}
+
+impl<R: Read + Seek> Scanner<R> {
+    fn bytes<const N: usize>(&mut self) -> AppResult<[u8; N]> {
+        let mut buf = [0u8; N];
+        self.reader
+            .read_exact(&mut buf)
+            .map_err(|e| scan_error(e.to_string()))?;
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> AppResult<u8> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> AppResult<u16> {
+        Ok(u16::from_le_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> AppResult<u32> {
+        Ok(u32::from_le_bytes(self.bytes()?))
+    }
+
+    fn skip(&mut self, len: i64) -> AppResult<()> {
+        self.reader
+            .seek_relative(len)
+            .map_err(|e| scan_error(e.to_string()))
+    }
+
+    /// FString: i32 length incl. NUL; negative means UTF-16
+    fn string(&mut self) -> AppResult<String> {
+        let len = i32::from_le_bytes(self.bytes()?);
+        let count = len.unsigned_abs() as usize;
+        if count > MAX_STRING_LEN {
+            return Err(scan_error(format!("implausible string length {}", len)));
+        }
+        if len < 0 {
+            let units = (0..count)
+                .map(|_| self.u16())
+                .collect::<AppResult<Vec<u16>>>()?;
+            let end = units.iter().position(|&c| c == 0).unwrap_or(units.len());
+            String::from_utf16(&units[..end]).map_err(|e| scan_error(e.to_string()))
+        } else {
+            let mut buf = vec![0u8; count];
+            self.reader
+                .read_exact(&mut buf)
+                .map_err(|e| scan_error(e.to_string()))?;
+            let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+            Ok(String::from_utf8_lossy(&buf[..end]).into_owned())
+        }
+    }
+
+    /// Header up to the root properties. Returns whether tags carry a property GUID.
+    fn header(&mut self) -> AppResult<bool> {
+        if self.u32()? != GVAS_MAGIC {
+            return Err(scan_error("not a GVAS file"));
+        }
+        let save_game_version = self.u32()?;
+        let _ue4_version = self.u32()?;
+        if save_game_version >= 3 && save_game_version != 34 {
+            // UE5 package version; 5.4+ changed the tag layout
+            self.u32()?;
+        }
+        let major = self.u16()?;
+        let minor = self.u16()?;
+        if (major, minor) >= (5, 4) {
+            return Err(scan_error(format!(
+                "engine {}.{} tags unsupported",
+                major, minor
+            )));
+        }
+        let _patch = self.u16()?;
+        let _build = self.u32()?;
+        let _engine = self.string()?;
+        let modern = (major, minor) >= (4, 12);
+        if modern {
+            let _format_version = self.u32()?;
+            let count = self.u32()?;
+            // Each custom version is a GUID plus an i32
+            self.skip(i64::from(count) * 20)?;
+        }
+        let _save_game_type = self.string()?;
+        Ok(modern)
+    }
+
+    fn scan(&mut self) -> AppResult<ScanSummary> {
+        let property_guid = self.header()?;
+        let mut summary = ScanSummary::default();
+        let mut found_level = false;
+
+        loop {
+            let name = self.string()?;
+            if name == "None" {
+                break;
+            }
+            let type_name = self.string()?;
+            let size = self.u32()?;
+            let _index = self.u32()?;
+
+            let mut bool_value = false;
+            let mut enum_name = None;
+            match type_name.as_str() {
+                "BoolProperty" => bool_value = self.u8()? != 0,
+                "ByteProperty" | "EnumProperty" => enum_name = Some(self.string()?),
+                "ArrayProperty" | "SetProperty" => {
+                    self.string()?;
+                }
+                "MapProperty" => {
+                    self.string()?;
+                    self.string()?;
+                }
+                "StructProperty" => {
+                    self.string()?;
+                    self.skip(16)?;
+                }
+                other if other.ends_with("Property") => {}
+                other => return Err(scan_error(format!("unknown property type '{}'", other))),
+            }
+            if property_guid && self.u8()? != 0 {
+                self.skip(16)?;
+            }
+
+            match (name.as_str(), type_name.as_str()) {
+                (CURRENT_LEVEL, "NameProperty") => {
+                    summary.raw_current_level = Some(self.string()?);
+                    found_level = true;
+                }
+                (UNLOCKED_FUN, "BoolProperty") => summary.unlocked_fun = bool_value,
+                (DIFFICULTY, "ByteProperty") if enum_name.as_deref() != Some("None") => {
+                    summary.difficulty_label = Some(self.string()?);
+                }
+                _ => self.skip(i64::from(size))?,
+            }
+
+            // UnlockedFun only matters for Pipes and is only stored when true
+            let level_settled = found_level
+                && (summary.unlocked_fun || summary.raw_current_level.as_deref() != Some(PIPES));
+            if level_settled && summary.difficulty_label.is_some() {
+                break;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// Read the card fields from a GVAS stream without parsing the whole save
+pub fn scan_reader<R: Read + Seek>(reader: R) -> AppResult<ScanSummary> {
+    Scanner {
+        reader: BufReader::new(reader),
+    }
+    .scan()
+}
+
+pub fn scan_file(path: &Path) -> AppResult<ScanSummary> {
+    let file = File::open(path).map_err(|e| format!("Failed to open save: {}", e))?;
+    scan_reader(file)
+}
+
+/// Partial scan, falling back to the full parse when the scan fails
+pub fn scan_or_parse(path: &Path) -> AppResult<ScanSummary> {
+    scan_file(path).or_else(|e| {
+        tracing::debug!("{:?}: {}; falling back to full parse", path, e);
+        let save = cli_handlers::parse_sav_file(path)?;
+        Ok(ScanSummary {
+            raw_current_level: save.raw_current_level().map(str::to_string),
+            difficulty_label: save.difficulty_label().map(str::to_string),
+            unlocked_fun: save.unlocked_fun(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::etb_save::EtbSave;
+    use std::io::Cursor;
+
+    fn fstring(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as i32 + 1).to_le_bytes());
+        out.extend(s.as_bytes());
+        out.push(0);
+    }
+
+    fn fstring_utf16(out: &mut Vec<u8>, s: &str) {
+        let units: Vec<u16> = s.encode_utf16().chain([0]).collect();
+        out.extend((-(units.len() as i32)).to_le_bytes());
+        for unit in units {
+            out.extend(unit.to_le_bytes());
+        }
+    }
+
+    /// Minimal GVAS writer: a header, then root property tags in call order
+    struct Gvas(Vec<u8>);
+
+    impl Gvas {
+        fn new(save_game_version: u32, major: u16, minor: u16) -> Self {
+            let mut b = Vec::new();
+            b.extend(b"GVAS");
+            b.extend(save_game_version.to_le_bytes());
+            b.extend(522u32.to_le_bytes());
+            if save_game_version >= 3 && save_game_version != 34 {
+                b.extend(1009u32.to_le_bytes());
+            }
+            b.extend(major.to_le_bytes());
+            b.extend(minor.to_le_bytes());
+            b.extend(1u16.to_le_bytes());
+            b.extend(0u32.to_le_bytes());
+            fstring(
+                &mut b,
+                &format!("++UE{}+Release-{}.{}", major, major, minor),
+            );
+            // Custom version format, then one (GUID, version) entry
+            b.extend(3u32.to_le_bytes());
+            b.extend(1u32.to_le_bytes());
+            b.extend([0xAB; 16]);
+            b.extend(7i32.to_le_bytes());
+            fstring(&mut b, "/Script/EscapeTheBackrooms.ETBSaveGame");
+            Self(b)
+        }
+
+        /// UE 5.1 layout (save game version 3 carries a UE5 package version)
+        fn ue5() -> Self {
+            Self::new(3, 5, 1)
+        }
+
+        /// UE 4.27 layout (no UE5 package version)
+        fn ue4() -> Self {
+            Self::new(2, 4, 27)
+        }
+
+        fn tag(mut self, name: &str, ty: &str, tag_data: &[u8], value: &[u8]) -> Self {
+            fstring(&mut self.0, name);
+            fstring(&mut self.0, ty);
+            self.0.extend((value.len() as u32).to_le_bytes());
+            self.0.extend(0u32.to_le_bytes());
+            self.0.extend(tag_data);
+            // No property GUID
+            self.0.push(0);
+            self.0.extend(value);
+            self
+        }
+
+        fn name(self, name: &str, value: &str) -> Self {
+            let mut v = Vec::new();
+            fstring(&mut v, value);
+            self.tag(name, "NameProperty", &[], &v)
+        }
+
+        fn name_utf16(self, name: &str, value: &str) -> Self {
+            let mut v = Vec::new();
+            fstring_utf16(&mut v, value);
+            self.tag(name, "NameProperty", &[], &v)
+        }
+
+        fn str(self, name: &str, value: &str) -> Self {
+            let mut v = Vec::new();
+            fstring(&mut v, value);
+            self.tag(name, "StrProperty", &[], &v)
+        }
+
+        fn int(self, name: &str, value: i32) -> Self {
+            self.tag(name, "IntProperty", &[], &value.to_le_bytes())
+        }
+
+        fn bool(self, name: &str, value: bool) -> Self {
+            self.tag(name, "BoolProperty", &[u8::from(value)], &[])
+        }
+
+        fn byte_enum(self, name: &str, enumerator: &str) -> Self {
+            let (mut enum_name, mut v) = (Vec::new(), Vec::new());
+            fstring(&mut enum_name, "E_Difficulty");
+            fstring(&mut v, enumerator);
+            self.tag(name, "ByteProperty", &enum_name, &v)
+        }
+
+        fn difficulty(self, enumerator: &str) -> Self {
+            self.byte_enum(DIFFICULTY, enumerator)
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            fstring(&mut self.0, "None");
+            self.0.extend(0u32.to_le_bytes());
+            self.0
+        }
+    }
+
+    fn scan(bytes: &[u8]) -> AppResult<ScanSummary> {
+        scan_reader(Cursor::new(bytes))
+    }
+
+    /// The card fields as the full parse sees them
+    fn full_parse(bytes: &[u8]) -> (String, Option<String>) {
+        let save = EtbSave::read(&mut Cursor::new(bytes)).expect("full parse");
+        (
+            save.current_level(),
+            save.difficulty_label().map(str::to_string),
+        )
+    }
+
+    fn assert_matches_full_parse(bytes: &[u8]) -> ScanSummary {
+        let summary = scan(bytes).expect("scan");
+        assert_eq!(
+            (summary.current_level(), summary.difficulty_label.clone()),
+            full_parse(bytes)
+        );
+        summary
+    }
+
+    #[test]
+    fn pipes_with_unlocked_fun_reads_past_level_and_difficulty() {
+        let bytes = Gvas::ue5()
+            .str("SaveName", "run")
+            .name(CURRENT_LEVEL, PIPES)
+            .difficulty("E_Difficulty::NewEnumerator2")
+            .int("Seed", 42)
+            .bool(UNLOCKED_FUN, true)
+            .finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.current_level(), "Pipes2");
+        assert_eq!(summary.difficulty(), Difficulty::Nightmare);
+    }
+
+    #[test]
+    fn pipes_without_unlocked_fun_is_the_first_visit() {
+        let bytes = Gvas::ue5()
+            .difficulty("E_Difficulty::NewEnumerator1")
+            .name(CURRENT_LEVEL, PIPES)
+            .int("Seed", 7)
+            .finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.current_level(), "Pipes1");
+        assert_eq!(summary.difficulty(), Difficulty::Hard);
+    }
+
+    #[test]
+    fn stops_once_level_and_difficulty_are_known() {
+        // Anything after the stop point is never read, not even a broken tag
+        let mut bytes = Gvas::ue5()
+            .name(CURRENT_LEVEL, "Poolrooms")
+            .difficulty("E_Difficulty::NewEnumerator0")
+            .finish();
+        bytes.truncate(bytes.len() - 4 - 9);
+        bytes.extend([0xFF; 8]);
+        let summary = scan(&bytes).expect("scan");
+        assert_eq!(summary.current_level(), "Poolrooms");
+        assert_eq!(summary.difficulty(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn absent_difficulty_is_normal() {
+        let bytes = Gvas::ue5()
+            .name(CURRENT_LEVEL, "Level0")
+            .bool("HasCompletedMainEnding", true)
+            .int("Seed", 1)
+            .finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.difficulty_label, None);
+        assert_eq!(summary.difficulty(), Difficulty::Normal);
+    }
+
+    #[test]
+    fn only_the_exact_difficulty_name_counts() {
+        let bytes = Gvas::ue5()
+            .name(CURRENT_LEVEL, "Level0")
+            .byte_enum("DifficultyPreset", "E_Difficulty::NewEnumerator2")
+            .difficulty("E_Difficulty::NewEnumerator1")
+            .finish();
+        let summary = scan(&bytes).expect("scan");
+        assert_eq!(summary.difficulty(), Difficulty::Hard);
+    }
+
+    #[test]
+    fn absent_current_level_is_level0() {
+        let bytes = Gvas::ue5().int("Seed", 1).finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.raw_current_level, None);
+        assert_eq!(summary.current_level(), "Level0");
+    }
+
+    #[test]
+    fn reads_utf16_strings() {
+        let bytes = Gvas::ue5()
+            .name_utf16(CURRENT_LEVEL, "Level0ü")
+            .difficulty("E_Difficulty::NewEnumerator1")
+            .finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.raw_current_level.as_deref(), Some("Level0ü"));
+    }
+
+    #[test]
+    fn reads_ue4_headers() {
+        let bytes = Gvas::ue4()
+            .name(CURRENT_LEVEL, PIPES)
+            .bool(UNLOCKED_FUN, true)
+            .finish();
+        let summary = assert_matches_full_parse(&bytes);
+        assert_eq!(summary.current_level(), "Pipes2");
+    }
+
+    #[test]
+    fn rejects_ue54_tags() {
+        let bytes = Gvas::new(3, 5, 4).name(CURRENT_LEVEL, "Level0").finish();
+        assert!(scan(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_non_gvas_data() {
+        assert!(scan(b"not a save at all").is_err());
+    }
+
+    #[test]
+    fn truncated_stream_is_an_error() {
+        let bytes = Gvas::ue5()
+            .str("SaveName", "run")
+            .name(CURRENT_LEVEL, PIPES)
+            .finish();
+        // Cut inside the CurrentLevel value, before the terminating None
+        let cut = bytes.len() - 4 - 9 - 3;
+        assert!(scan(&bytes[..cut]).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Save schema module - Declarative registry of every known ETB property path
+//! uesave 0.7 needs a schema for each property it writes. Writers record them from
+//! this table instead of building tags by hand, and `validate` checks a tree
+//! against its recorded schemas before anything is serialized.
+
+use crate::error::{AppError, AppResult};
+use crate::etb_save::{
+    CURRENT_LEVEL, DIFFICULTY, HAS_COMPLETED_MAIN_ENDING, IS_MEG_POWER_ON,
+    IS_MEG_SECURITY_UNLOCKED, IS_MEG_UNLOCKED, LEVELS_COMPLETED, PLAYER_DATA, UNLOCKED_FUN,
+};
+use crate::save_shared::{
+    DISPLAY_NAME_FIELD, HAS_COMPLETED_FIELD, HAS_UNLOCKED_HUB_FIELD, INVENTORY_PROP_NAME,
+    LEVEL_NAME_FIELD, SANITY_PROP_NAME, TIME_FIELD, WORLD_FIELD, WORLD_ITEMS_FIELD,
+    WORLD_SANITY_FIELD,
+};
+use std::mem::discriminant;
+use uesave::{
+    FGuid, Properties, Property, PropertyTagDataPartial, PropertyTagPartial, PropertyType, Save,
+    StructType, StructValue, ValueVec,
+};
+
+/// MAINSAVE list of visible archive names
+pub const SINGLEPLAYER_SAVES: &str = "SingleplayerSaves";
+/// Checkpoint counter some saves carry at the root
+pub const GRASSROOMS_CHECKPOINT: &str = "GrassroomsCheckpoint";
+
+/// Shape of a property as uesave records it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    Scalar(PropertyType),
+    /// ByteProperty holding a label of the named enum
+    Enum(&'static str),
+    /// StructProperty; `None` for an untyped struct
+    Struct(Option<&'static str>),
+    StructArray(&'static str),
+    ScalarArray(PropertyType),
+    /// Map with string keys and untyped struct values
+    StrStructMap,
+    /// Map with string keys and scalar values
+    StrScalarMap(PropertyType),
+}
+
+impl SchemaKind {
+    pub fn tag(self) -> PropertyTagPartial {
+        PropertyTagPartial {
+            id: None,
+            data: self.data(),
+        }
+    }
+
+    fn data(self) -> PropertyTagDataPartial {
+        let struct_data = |name: Option<&str>| PropertyTagDataPartial::Struct {
+            struct_type: StructType::Struct(name.map(str::to_string)),
+            id: FGuid::nil(),
+        };
+        match self {
+            SchemaKind::Scalar(ty) => PropertyTagDataPartial::Other(ty),
+            SchemaKind::Enum(name) => PropertyTagDataPartial::Byte(Some(name.to_string())),
+            SchemaKind::Struct(name) => struct_data(name),
+            SchemaKind::StructArray(name) => {
+                PropertyTagDataPartial::Array(Box::new(struct_data(Some(name))))
+            }
+            SchemaKind::ScalarArray(ty) => {
+                PropertyTagDataPartial::Array(Box::new(PropertyTagDataPartial::Other(ty)))
+            }
+            SchemaKind::StrStructMap => PropertyTagDataPartial::Map {
+                key_type: Box::new(PropertyTagDataPartial::Other(PropertyType::StrProperty)),
+                value_type: Box::new(struct_data(None)),
+            },
+            SchemaKind::StrScalarMap(ty) => PropertyTagDataPartial::Map {
+                key_type: Box::new(PropertyTagDataPartial::Other(PropertyType::StrProperty)),
+                value_type: Box::new(PropertyTagDataPartial::Other(ty)),
+            },
+        }
+    }
+}
+
+/// One known property path (root name first, nested field names after it)
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaEntry {
+    pub path: &'static [&'static str],
+    pub kind: SchemaKind,
+}
+
+const fn entry(path: &'static [&'static str], kind: SchemaKind) -> SchemaEntry {
+    SchemaEntry { path, kind }
+}
+
+/// Every property path the app writes, in save and MAINSAVE files
+pub const REGISTRY: &[SchemaEntry] = &[
+    entry(
+        &[CURRENT_LEVEL],
+        SchemaKind::Scalar(PropertyType::NameProperty),
+    ),
+    entry(
+        &[UNLOCKED_FUN],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(&[DIFFICULTY], SchemaKind::Enum("E_Difficulty")),
+    entry(
+        &[HAS_COMPLETED_MAIN_ENDING],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[IS_MEG_UNLOCKED],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[IS_MEG_POWER_ON],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[IS_MEG_SECURITY_UNLOCKED],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[GRASSROOMS_CHECKPOINT],
+        SchemaKind::Scalar(PropertyType::IntProperty),
+    ),
+    // LevelsCompleted: array of S_LevelStats
+    entry(&[LEVELS_COMPLETED], SchemaKind::StructArray("S_LevelStats")),
+    entry(
+        &[LEVELS_COMPLETED, DISPLAY_NAME_FIELD],
+        SchemaKind::Scalar(PropertyType::StrProperty),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, HAS_COMPLETED_FIELD],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, HAS_UNLOCKED_HUB_FIELD],
+        SchemaKind::Scalar(PropertyType::BoolProperty),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, LEVEL_NAME_FIELD],
+        SchemaKind::Scalar(PropertyType::NameProperty),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, TIME_FIELD],
+        SchemaKind::Scalar(PropertyType::FloatProperty),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, WORLD_FIELD],
+        SchemaKind::Struct(Some("S_WorldCommon")),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, WORLD_FIELD, WORLD_ITEMS_FIELD],
+        SchemaKind::StructArray("S_DroppedItem"),
+    ),
+    entry(
+        &[LEVELS_COMPLETED, WORLD_FIELD, WORLD_SANITY_FIELD],
+        SchemaKind::Scalar(PropertyType::FloatProperty),
+    ),
+    // PlayerData: steam/EOS key -> player struct
+    entry(&[PLAYER_DATA], SchemaKind::StrStructMap),
+    entry(
+        &[PLAYER_DATA, INVENTORY_PROP_NAME],
+        SchemaKind::ScalarArray(PropertyType::NameProperty),
+    ),
+    entry(
+        &[PLAYER_DATA, SANITY_PROP_NAME],
+        SchemaKind::Scalar(PropertyType::FloatProperty),
+    ),
+    // MAINSAVE.sav
+    entry(
+        &[SINGLEPLAYER_SAVES],
+        SchemaKind::ScalarArray(PropertyType::StrProperty),
+    ),
+];
+
+/// Registry entry for a dotted path (`LevelsCompleted.World_14_...`)
+pub fn lookup(path: &str) -> Option<&'static SchemaEntry> {
+    REGISTRY.iter().find(|entry| entry.path.join(".") == path)
+}
+
+/// Record `kind` at `path` unless a schema of the same shape is already there,
+/// so struct ids read from the original file survive
+fn record_kind(save: &mut Save, path: &str, kind: SchemaKind) {
+    let tag = kind.tag();
+    let keep = save
+        .schemas
+        .get(path)
+        .is_some_and(|existing| discriminant(&existing.data) == discriminant(&tag.data));
+    if !keep {
+        save.schemas.record(path.to_string(), tag);
+    }
+}
+
+/// Record the registry schema for `path`; returns false for an unknown path
+pub fn record(save: &mut Save, path: &str) -> bool {
+    match lookup(path) {
+        Some(entry) => {
+            record_kind(save, path, entry.kind);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Record `path` from the registry, or as `fallback` when the registry doesn't know it
+pub fn record_or(save: &mut Save, path: &str, fallback: SchemaKind) {
+    if !record(save, path) {
+        record_kind(save, path, fallback);
+    }
+}
+
+/// Record `path` and every registered path nested below it
+pub fn record_tree(save: &mut Save, path: &str) {
+    let root: Vec<&str> = path.split('.').collect();
+    for entry in REGISTRY
+        .iter()
+        .filter(|entry| entry.path.starts_with(&root))
+    {
+        record_kind(save, &entry.path.join("."), entry.kind);
+    }
+}
+
+/// Every property path in `root` that has no recorded schema (dotted, as uesave
+/// looks them up), in tree order
+pub fn missing_schemas(save: &Save) -> Vec<String> {
+    let mut missing = Vec::new();
+    walk(save, &save.root.properties, "", &mut missing);
+    missing
+}
+
+/// Pre-write check: every property must have a schema or uesave can't write it
+pub fn validate(save: &Save) -> AppResult<()> {
+    let missing = missing_schemas(save);
+    match missing.first() {
+        None => Ok(()),
+        Some(first) => Err(AppError::Validation(format!(
+            "No schema recorded for property '{}'{}",
+            first,
+            if missing.len() > 1 {
+                format!(" (and {} more)", missing.len() - 1)
+            } else {
+                String::new()
+            }
+        ))),
+    }
+}
+
+fn walk(save: &Save, props: &Properties, prefix: &str, missing: &mut Vec<String>) {
+    for (key, prop) in props.0.iter() {
+        let path = if prefix.is_empty() {
+            key.1.clone()
+        } else {
+            format!("{}.{}", prefix, key.1)
+        };
+        if save.schemas.get(&path).is_none() && !missing.contains(&path) {
+            missing.push(path.clone());
+        }
+        match prop {
+            Property::Struct(StructValue::Struct(inner)) => walk(save, inner, &path, missing),
+            Property::Array(ValueVec::Struct(values)) => {
+                for value in values {
+                    if let StructValue::Struct(inner) = value {
+                        walk(save, inner, &path, missing);
+                    }
+                }
+            }
+            Property::Map(entries) => {
+                for entry in entries {
+                    if let Property::Struct(StructValue::Struct(inner)) = &entry.value {
+                        walk(save, inner, &path, missing);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,568 @@
+//! Save trash module - Manages soft-deleted saves (`*.sav.trash`)
+//! `soft_delete` renames a save in place, so trashed files never show up in the
+//! library listing. This module lists them with their parsed card fields, restores
+//! them (under a new name when the original is taken), empties the trash and
+//! purges entries older than the age set in `trash_config.json`.
+//! A rename keeps the file's mtime, so the time each file was trashed is recorded
+//! in `trash_log.json`; files missing from the log are logged when first seen.
+
+use crate::common::{
+    add_save_to_mainsave, extract_archive_name, preview_mainsave, run_blocking, write_atomic,
+    MainsaveOp,
+};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::{lock_save, lock_saves};
+use crate::save_backup::backup_file;
+use crate::save_diff::{DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_scan;
+use crate::save_utils;
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use walkdir::WalkDir;
+
+/// Auto-purge settings, stored in the app config dir
+const TRASH_CONFIG_FILE: &str = "trash_config.json";
+/// When each file was trashed (`trash path -> RFC 3339`), in the app config dir
+const TRASH_LOG_FILE: &str = "trash_log.json";
+/// Extension soft-deleted saves carry
+const TRASH_EXTENSION: &str = ".sav.trash";
+/// Highest `(n)` suffix tried when looking for a free restore name
+const MAX_RENAME_ATTEMPTS: u32 = 999;
+
+/// Serializes trash log read-modify-writes
+static LOG_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+
+fn log_lock() -> AppResult<MutexGuard<'static, ()>> {
+    Ok(LOG_LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .map_err(|e| format!("Trash log lock poisoned: {}", e))?)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrashConfig {
+    /// Delete trashed saves once they are older than `max_age_days`
+    pub auto_purge: bool,
+    pub max_age_days: i64,
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            auto_purge: true,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// One trashed save
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// The `.sav.trash` file
+    pub path: String,
+    /// Where a plain restore puts it back
+    pub original_path: String,
+    pub archive_name: String,
+    pub name: String,
+    /// Difficulty in the file name
+    pub difficulty: String,
+    pub hidden: bool,
+    /// `None` when the file can't be read
+    pub current_level: Option<String>,
+    pub actual_difficulty: Option<String>,
+    pub file_size: u64,
+    /// RFC 3339
+    pub trashed_at: String,
+    /// When auto-purge will delete it; `None` with auto-purge off
+    pub expires_at: Option<String>,
+    /// Another save holds the original name, so a restore needs a new one
+    pub name_taken: bool,
+}
+
+/// A restored save; the file is back even when MAINSAVE couldn't be updated
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    /// Where the save was restored to
+    pub path: String,
+    /// Why MAINSAVE may not list the save yet
+    pub mainsave_error: Option<String>,
+}
+
+/// Result of emptying or purging the trash
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashReport {
+    pub removed: usize,
+    pub freed_bytes: u64,
+}
+
+fn config_path(paths: &SavePaths) -> PathBuf {
+    paths.app_config_dir().join(TRASH_CONFIG_FILE)
+}
+
+fn log_path(paths: &SavePaths) -> PathBuf {
+    paths.app_config_dir().join(TRASH_LOG_FILE)
+}
+
+/// Load auto-purge settings. Missing or corrupt files degrade to the defaults.
+pub fn load_trash_config(paths: &SavePaths) -> TrashConfig {
+    fs::read_to_string(config_path(paths))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn write_trash_config(paths: &SavePaths, config: &TrashConfig) -> AppResult<()> {
+    if config.max_age_days < 1 {
+        return Err(AppError::Validation(
+            "Trash retention must be at least one day".into(),
+        ));
+    }
+    fs::create_dir_all(paths.app_config_dir())
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize trash config: {}", e))?;
+    fs::write(config_path(paths), json)
+        .map_err(|e| format!("Failed to write trash config: {}", e))?;
+    Ok(())
+}
+
+fn load_log(paths: &SavePaths) -> BTreeMap<String, String> {
+    fs::read_to_string(log_path(paths))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Best-effort: a lost log only restarts the purge clock of the files it listed
+fn write_log(paths: &SavePaths, log: &BTreeMap<String, String>) {
+    if fs::create_dir_all(paths.app_config_dir()).is_err() {
+        return;
+    }
+    let Ok(json) = serde_json::to_string_pretty(log) else {
+        return;
+    };
+    let _ = write_atomic(&log_path(paths), json.as_bytes());
+}
+
+/// Note that `trash_path` was trashed just now (called by `soft_delete`)
+pub fn record_trashed(paths: &SavePaths, trash_path: &Path) {
+    let Ok(_guard) = log_lock() else {
+        return;
+    };
+    let mut log = load_log(paths);
+    log.insert(
+        trash_path.to_string_lossy().into_owned(),
+        Local::now().to_rfc3339(),
+    );
+    write_log(paths, &log);
+}
+
+fn is_trash_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.to_ascii_lowercase().ends_with(TRASH_EXTENSION))
+}
+
+/// The `.sav` a trash file was renamed from
+fn original_path_of(trash_path: &Path) -> PathBuf {
+    trash_path.with_extension("")
+}
+
+/// The trash file for `path`, which is either that file or the original `.sav`
+pub(crate) fn trash_path_for(path: &Path) -> PathBuf {
+    if is_trash_file(path) {
+        path.to_path_buf()
+    } else {
+        path.with_extension("sav.trash")
+    }
+}
+
+/// Every `.sav.trash` in SaveGames and its hidden folders
+pub(crate) fn trash_files(paths: &SavePaths) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(paths.save_games_dir())
+        .max_depth(2)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file() && is_trash_file(e.path()))
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// When `path` was trashed, as logged. The mtime is never used: a rename keeps
+/// it, so it tells when the save was last played, not when it was trashed.
+fn trashed_at(log: &BTreeMap<String, String>, path: &Path) -> Option<DateTime<Local>> {
+    log.get(path.to_string_lossy().as_ref())
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// The trash files with their trash times. Files the log doesn't know yet (trashed
+/// by an older version or by hand) are logged as trashed now, so their purge clock
+/// starts when they are first seen; entries of files that are gone are pruned.
+fn trash_with_times(paths: &SavePaths) -> AppResult<Vec<(PathBuf, Option<DateTime<Local>>)>> {
+    let _guard = log_lock()?;
+    let files = trash_files(paths);
+    let mut log = load_log(paths);
+    let root = paths.save_games_dir();
+    let before = log.clone();
+    log.retain(|p, _| !Path::new(p).starts_with(root) || Path::new(p).is_file());
+    let now = Local::now().to_rfc3339();
+    for path in &files {
+        log.entry(path.to_string_lossy().into_owned())
+            .or_insert_with(|| now.clone());
+    }
+    if log != before {
+        write_log(paths, &log);
+    }
+    Ok(files
+        .into_iter()
+        .map(|path| {
+            let at = trashed_at(&log, &path);
+            (path, at)
+        })
+        .collect())
+}
+
+/// Every trashed save, newest first
+pub fn list_trash(paths: &SavePaths) -> AppResult<Vec<TrashEntry>> {
+    let config = load_trash_config(paths);
+    let mut entries: Vec<(Option<DateTime<Local>>, TrashEntry)> = trash_with_times(paths)?
+        .into_iter()
+        .map(|(path, at)| {
+            let original = original_path_of(&path);
+            let file_name = original
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            let archive_name = extract_archive_name(file_name).to_string();
+            let meta = save_utils::build_save_meta(paths, 0, &original, String::new(), false);
+            let (name, difficulty) = match &meta {
+                Ok(meta) => (meta.name.clone(), meta.difficulty.clone()),
+                Err(_) => (archive_name.clone(), String::new()),
+            };
+            let scan = save_scan::scan_or_parse(&path).ok();
+            let entry = TrashEntry {
+                path: path.to_string_lossy().into_owned(),
+                original_path: original.to_string_lossy().into_owned(),
+                archive_name,
+                name,
+                difficulty,
+                hidden: paths.is_hidden(&path),
+                current_level: scan.as_ref().map(|s| s.current_level()),
+                actual_difficulty: scan.as_ref().map(|s| s.difficulty().label().to_string()),
+                file_size: path.metadata().map(|m| m.len()).unwrap_or(0),
+                trashed_at: at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                expires_at: at
+                    .filter(|_| config.auto_purge)
+                    .map(|t| (t + Duration::days(config.max_age_days)).to_rfc3339()),
+                name_taken: original.exists(),
+            };
+            (at, entry)
+        })
+        .collect();
+    entries.sort_by_key(|(at, _)| Reverse(*at));
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// List a restored save in MAINSAVE, returning the error instead of failing the
+/// restore that already happened
+pub(crate) fn list_restored(paths: &SavePaths, archive_name: &str) -> Option<String> {
+    add_save_to_mainsave(paths, archive_name)
+        .inspect_err(|e| {
+            tracing::warn!(
+                "'{}' restored but MAINSAVE not updated: {}",
+                archive_name,
+                e
+            )
+        })
+        .err()
+        .map(|e| e.to_string())
+}
+
+/// Check `trash_path` is a `.sav.trash` inside SaveGames that exists
+pub(crate) fn validate_trash_path(paths: &SavePaths, trash_path: &Path) -> AppResult<()> {
+    if !is_trash_file(trash_path) {
+        return Err(AppError::Validation(format!(
+            "Not a trashed save: {}",
+            trash_path.display()
+        )));
+    }
+    paths.validate_save_games_path(trash_path)?;
+    if !trash_path.is_file() {
+        return Err(format!("Trash file not found: {}", trash_path.display()).into());
+    }
+    Ok(())
+}
+
+/// `(mode, name, difficulty)` of `MODE_<name>_<Difficulty>.sav`
+fn split_file_name(path: &Path) -> AppResult<(&str, &str, &str)> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file path")?;
+    let stem = extract_archive_name(file_name);
+    match (stem.find('_'), stem.rfind('_')) {
+        (Some(first), Some(last)) if first < last => {
+            Ok((&stem[..first], &stem[first + 1..last], &stem[last + 1..]))
+        }
+        _ => Err(format!("Filename format mismatch: {}", file_name).into()),
+    }
+}
+
+/// Where a restore of `trash_path` goes: `new_name` if given (must be free), else
+/// the original name, else the first free `<name> (n)`. Paths in `claimed` count as
+/// taken (targets of other restores in the same batch).
+pub(crate) fn restore_target(
+    paths: &SavePaths,
+    trash_path: &Path,
+    new_name: Option<&str>,
+    claimed: &HashSet<PathBuf>,
+) -> AppResult<PathBuf> {
+    let original = original_path_of(trash_path);
+    let dir = original.parent().ok_or("Invalid file path")?;
+    let (mode, original_name, difficulty) = split_file_name(&original)?;
+    let file_name = |name: &str| format!("{}_{}_{}.sav", mode, name, difficulty);
+    let taken = |path: &Path| path.exists() || claimed.contains(path);
+
+    let target = match new_name.map(str::trim) {
+        Some(name) => {
+            if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+                return Err(AppError::Validation(format!("Invalid save name: {}", name)));
+            }
+            let target = dir.join(file_name(name));
+            if taken(&target) {
+                return Err(AppError::Validation(format!(
+                    "A save named '{}' already exists",
+                    name
+                )));
+            }
+            target
+        }
+        None if !taken(&original) => original,
+        None => (2..=MAX_RENAME_ATTEMPTS)
+            .map(|n| dir.join(file_name(&format!("{} ({})", original_name, n))))
+            .find(|target| !taken(target))
+            .ok_or_else(|| format!("No free name to restore '{}' under", original_name))?,
+    };
+    paths.validate_save_games_path(&target)?;
+    Ok(target)
+}
+
+/// Dry run of [`restore_trash`]
+pub fn preview_restore_trash(
+    paths: &SavePaths,
+    trash_path: &Path,
+    new_name: Option<&str>,
+) -> AppResult<DryRunReport> {
+    validate_trash_path(paths, trash_path)?;
+    let target = restore_target(paths, trash_path, new_name, &HashSet::new())?;
+    let file_name = target.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let mut report = DryRunReport {
+        files: vec![FileOp::Rename {
+            from: trash_path.to_string_lossy().into_owned(),
+            to: target.to_string_lossy().into_owned(),
+        }],
+        ..Default::default()
+    };
+    if target != original_path_of(trash_path) {
+        report.notes.push(format!(
+            "{} is taken; restoring as {}",
+            original_path_of(trash_path).display(),
+            target.display()
+        ));
+    }
+    if paths.is_hidden(&target) {
+        report
+            .notes
+            .push("The save is in a hidden subfolder and stays out of MAINSAVE".to_string());
+    } else {
+        report.push_diff(
+            paths.mainsave_path().to_string_lossy(),
+            preview_mainsave(
+                paths,
+                &[MainsaveOp::Add(extract_archive_name(file_name).to_string())],
+            )?,
+        );
+    }
+    Ok(report)
+}
+
+/// Move a trashed save back into the library and list it in MAINSAVE (unless it
+/// sits in a hidden subfolder). The restored path differs from the original when
+/// that name is taken.
+pub fn restore_trash(
+    paths: &SavePaths,
+    trash_path: &Path,
+    new_name: Option<&str>,
+) -> AppResult<RestoreResult> {
+    validate_trash_path(paths, trash_path)?;
+    let target = restore_target(paths, trash_path, new_name, &HashSet::new())?;
+    let _locks = lock_saves(paths, [trash_path, target.as_path()])?;
+    // Another process may have restored it or taken the name while we waited
+    if !trash_path.is_file() || target.exists() {
+        return Err(AppError::Validation(format!(
+            "{:?} changed while waiting for the lock; try again",
+            trash_path
+        )));
+    }
+    fs::rename(trash_path, &target).map_err(|e| format!("Failed to restore file: {}", e))?;
+
+    tracing::info!("Restored {:?} as {:?}", trash_path, target);
+
+    let mainsave_error = if paths.is_hidden(&target) {
+        None
+    } else {
+        let file_name = target.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        list_restored(paths, extract_archive_name(file_name))
+    };
+    Ok(RestoreResult {
+        path: target.to_string_lossy().into_owned(),
+        mainsave_error,
+    })
+}
+
+/// Back up, then delete each of `files`
+fn remove_trash(paths: &SavePaths, files: &[PathBuf]) -> AppResult<TrashReport> {
+    let mut report = TrashReport::default();
+    for path in files {
+        let _lock = lock_save(paths, path)?;
+        // Restored or purged by another process meanwhile
+        let Ok(metadata) = path.metadata() else {
+            continue;
+        };
+        let size = metadata.len();
+        backup_file(paths, path, "purge")?;
+        fs::remove_file(path).map_err(|e| format!("Failed to delete trash file: {}", e))?;
+        report.removed += 1;
+        report.freed_bytes += size;
+    }
+    Ok(report)
+}
+
+/// Dry run of [`empty_trash`]
+pub fn preview_empty_trash(paths: &SavePaths) -> DryRunReport {
+    DryRunReport {
+        files: trash_files(paths)
+            .into_iter()
+            .map(|path| FileOp::Delete {
+                path: path.to_string_lossy().into_owned(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Permanently delete every trashed save
+pub fn empty_trash(paths: &SavePaths) -> AppResult<TrashReport> {
+    let report = remove_trash(paths, &trash_files(paths))?;
+    tracing::info!(
+        "Emptied trash: {} file(s), {} bytes",
+        report.removed,
+        report.freed_bytes
+    );
+    Ok(report)
+}
+
+/// Delete trashed saves older than the configured age (nothing with auto-purge off)
+pub fn purge_expired(paths: &SavePaths) -> AppResult<TrashReport> {
+    let config = load_trash_config(paths);
+    if !config.auto_purge || !paths.save_games_dir().is_dir() {
+        return Ok(TrashReport::default());
+    }
+    let cutoff = Local::now() - Duration::days(config.max_age_days);
+    let expired: Vec<PathBuf> = trash_with_times(paths)?
+        .into_iter()
+        .filter(|(_, at)| at.is_some_and(|at| at < cutoff))
+        .map(|(path, _)| path)
+        .collect();
+    let report = remove_trash(paths, &expired)?;
+    if report.removed > 0 {
+        tracing::info!(
+            "Purged {} trashed save(s) older than {} days",
+            report.removed,
+            config.max_age_days
+        );
+    }
+    Ok(report)
+}
+
+/// Trashed saves of the active library, newest first.
+#[tauri::command]
+pub async fn list_trash_files(
+    state: tauri::State<'_, SavePathsState>,
+) -> AppResult<Vec<TrashEntry>> {
+    let paths = state.current()?;
+    run_blocking(move || list_trash(&paths)).await
+}
+
+/// Restore a trashed save (`trash_path` is the `.sav.trash` file), optionally under
+/// `new_name`.
+#[tauri::command]
+pub async fn restore_trash_file(
+    state: tauri::State<'_, SavePathsState>,
+    trash_path: String,
+    new_name: Option<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<RestoreResult>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let trash_path = Path::new(&trash_path);
+        if dry_run.unwrap_or(false) {
+            return preview_restore_trash(&paths, trash_path, new_name.as_deref())
+                .map(Outcome::DryRun);
+        }
+        restore_trash(&paths, trash_path, new_name.as_deref()).map(Outcome::Applied)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn empty_trash_files(
+    state: tauri::State<'_, SavePathsState>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<TrashReport>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return Ok(Outcome::DryRun(preview_empty_trash(&paths)));
+        }
+        empty_trash(&paths).map(Outcome::Applied)
+    })
+    .await
+}
+
+/// Run the age-based purge now (the save watcher also runs it periodically).
+#[tauri::command]
+pub async fn purge_expired_trash(
+    state: tauri::State<'_, SavePathsState>,
+) -> AppResult<TrashReport> {
+    let paths = state.current()?;
+    run_blocking(move || purge_expired(&paths)).await
+}
+
+#[tauri::command]
+pub async fn get_trash_config(state: tauri::State<'_, SavePathsState>) -> AppResult<TrashConfig> {
+    let paths = state.current()?;
+    run_blocking(move || Ok(load_trash_config(&paths))).await
+}
+
+#[tauri::command]
+pub async fn set_trash_config(
+    state: tauri::State<'_, SavePathsState>,
+    config: TrashConfig,
+) -> AppResult<()> {
+    let paths = state.current()?;
+    run_blocking(move || write_trash_config(&paths, &config)).await
+}
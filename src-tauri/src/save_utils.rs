@@ -2,9 +2,10 @@
 //! Optimized version: Using phf perfect hash, Cow to reduce allocation
 
 use crate::error::AppResult;
+use crate::save_paths::SavePaths;
 use regex::Regex;
 use serde::Serialize;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::sync::OnceLock;
 
 /// Cached save file name regex
@@ -21,23 +22,6 @@ fn get_save_file_regex() -> &'static Regex {
     })
 }
 
-/// Cache SaveGames root directory
-static SAVE_GAMES_BASE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
-
-#[inline]
-fn get_save_games_base_dir() -> Option<&'static PathBuf> {
-    SAVE_GAMES_BASE_DIR
-        .get_or_init(|| {
-            std::env::var("LOCALAPPDATA")
-                .ok()
-                .map(|local_appdata| {
-                    PathBuf::from(local_appdata).join("EscapeTheBackrooms\\Saved\\SaveGames")
-                })
-                .filter(|dir| dir.exists())
-        })
-        .as_ref()
-}
-
 #[derive(Serialize)]
 pub struct SaveFileInfo {
     pub id: u32,
@@ -55,7 +39,7 @@ pub struct SaveFileInfo {
 
 /// Lightweight save file metadata - derived from filename + filesystem only,
 /// no .sav parsing. Used for fast incremental loading.
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct SaveFileMeta {
     pub id: u32,
     pub name: String,
@@ -71,16 +55,19 @@ pub struct SaveFileMeta {
 /// Paginated response for load_save_metadata_page.
 /// Returns a slice of metadata items plus the total count so the
 /// frontend can show progress and detect the end of the list.
+/// `cursor` names the library snapshot the page was cut from; pass it back
+/// to get the following pages from the same snapshot.
 #[derive(Serialize)]
 pub struct SaveFileMetaPage {
     pub items: Vec<SaveFileMeta>,
     pub total: u32,
     pub offset: u32,
     pub has_more: bool,
+    pub cursor: String,
 }
 
 /// Detailed info that requires .sav parsing, loaded on demand.
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct SaveFileDetail {
     pub path: String,
     pub current_level: String,
@@ -110,6 +97,7 @@ fn map_mode(_raw: &str) -> &'static str {
 
 #[allow(dead_code)]
 pub fn build_save_info<S: Into<String>>(
+    paths: &SavePaths,
     index: u32,
     path: &Path,
     current_level: S,
@@ -130,7 +118,7 @@ pub fn build_save_info<S: Into<String>>(
     let difficulty_raw = caps.get(3).ok_or("Failed to extract difficulty")?.as_str();
 
     let (difficulty, difficulty_class) = map_difficulty(difficulty_raw);
-    let hidden = path.parent() != get_save_games_base_dir().map(|p| p.as_path());
+    let hidden = paths.is_hidden(path);
 
     Ok(SaveFileInfo {
         id: index,
@@ -150,6 +138,7 @@ pub fn build_save_info<S: Into<String>>(
 /// Build lightweight save metadata from filename + filesystem only.
 /// No .sav file opening - very fast even for 1000+ files.
 pub fn build_save_meta(
+    paths: &SavePaths,
     index: u32,
     path: &Path,
     date: String,
@@ -178,7 +167,7 @@ pub fn build_save_meta(
         difficulty: map_difficulty(difficulty_raw).0.to_string(),
         mode: map_mode(mode_raw).to_string(),
         date,
-        hidden: path.parent() != get_save_games_base_dir().map(|p| p.as_path()),
+        hidden: paths.is_hidden(path),
         path: path.to_str().unwrap_or_default().to_string(),
         is_visible: Some(is_visible),
         file_size,
@@ -188,9 +177,9 @@ pub fn build_save_meta(
 /// Build SaveFileInfo with optional current_level and actual_difficulty.
 /// Used by save_loader for incremental loading where level/difficulty may not be parsed yet.
 pub fn build_save_file_info(
+    paths: &SavePaths,
     index: u32,
     path: &Path,
-    _archive_name: &str,
     date: String,
     current_level: Option<String>,
     actual_difficulty: Option<String>,
@@ -210,7 +199,7 @@ pub fn build_save_file_info(
     let difficulty_raw = caps.get(3).ok_or("Failed to extract difficulty")?.as_str();
 
     let (difficulty, difficulty_class) = map_difficulty(difficulty_raw);
-    let hidden = path.parent() != get_save_games_base_dir().map(|p| p.as_path());
+    let hidden = paths.is_hidden(path);
 
     Ok(SaveFileInfo {
         id: index,
@@ -0,0 +1,258 @@
+//! Save verify module - Round-trip verification before any save hits the disk
+//! Serializes the intended tree, re-parses the bytes and compares the result
+//! structurally (and byte-for-byte when the save was not modified), so a uesave
+//! regression aborts the write instead of silently corrupting a run.
+
+use crate::common::run_blocking;
+use crate::error::{AppError, AppResult};
+use crate::save_paths::SavePathsState;
+use crate::save_schema;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use uesave::Save;
+
+/// Stop collecting structural mismatches after this many
+const MAX_MISMATCHES: usize = 20;
+/// Truncate values shown in a mismatch
+const MAX_VALUE_LEN: usize = 120;
+
+/// One structural difference between the intended and the re-parsed tree
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeMismatch {
+    /// JSON pointer into the serialized save (e.g. `/root/properties/...`)
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// First byte where the written output differs from the original file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ByteMismatch {
+    pub offset: usize,
+    pub expected_len: usize,
+    pub actual_len: usize,
+}
+
+/// Outcome of a round-trip check
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundTripReport {
+    /// Serialized bytes failed to parse again
+    pub reparse_error: Option<String>,
+    pub tree_mismatches: Vec<TreeMismatch>,
+    /// Only checked when the tree equals the original file's tree
+    pub byte_mismatch: Option<ByteMismatch>,
+    /// The intended tree equals the one parsed from the original bytes
+    pub unmodified: bool,
+    pub written_len: usize,
+}
+
+impl RoundTripReport {
+    pub fn is_ok(&self) -> bool {
+        self.reparse_error.is_none()
+            && self.tree_mismatches.is_empty()
+            && self.byte_mismatch.is_none()
+    }
+
+    /// One-line summary for error messages
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(e) = &self.reparse_error {
+            parts.push(format!("written bytes do not parse: {}", e));
+        }
+        if let Some(first) = self.tree_mismatches.first() {
+            parts.push(format!(
+                "{} structural mismatch(es), first at {} (expected {}, got {})",
+                self.tree_mismatches.len(),
+                first.path,
+                first.expected,
+                first.actual
+            ));
+        }
+        if let Some(b) = &self.byte_mismatch {
+            parts.push(format!(
+                "unmodified save re-serialized differently at byte {} (expected {} bytes, got {})",
+                b.offset, b.expected_len, b.actual_len
+            ));
+        }
+        if parts.is_empty() {
+            "round-trip OK".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+/// Serialize `save` and check the result against `save` itself and, when given,
+/// the bytes it was originally read from. Returns the bytes plus the report.
+pub fn check_round_trip(
+    save: &Save,
+    original: Option<&[u8]>,
+) -> AppResult<(Vec<u8>, RoundTripReport)> {
+    let mut bytes = Vec::new();
+    save.write(&mut bytes)
+        .map_err(|e| format!("Failed to write save: {:?}", e))?;
+
+    let mut report = RoundTripReport {
+        written_len: bytes.len(),
+        ..Default::default()
+    };
+    let intended = tree_of(save)?;
+
+    match Save::read(&mut Cursor::new(&bytes)) {
+        Ok(reparsed) => compare_trees(
+            &intended,
+            &tree_of(&reparsed)?,
+            "",
+            &mut report.tree_mismatches,
+        ),
+        Err(e) => report.reparse_error = Some(format!("{:?}", e)),
+    }
+
+    if let Some(original) = original {
+        // An original that no longer parses can't be "unmodified"
+        if let Ok(original_save) = Save::read(&mut Cursor::new(original)) {
+            report.unmodified = tree_of(&original_save)? == intended;
+        }
+        if report.unmodified && bytes != original {
+            report.byte_mismatch = Some(ByteMismatch {
+                offset: bytes
+                    .iter()
+                    .zip(original)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| bytes.len().min(original.len())),
+                expected_len: original.len(),
+                actual_len: bytes.len(),
+            });
+        }
+    }
+
+    Ok((bytes, report))
+}
+
+/// Serialize `save` for writing, verified. `context` names the file in errors.
+/// Fails with [`AppError::Validation`] when a property has no schema, and with
+/// [`AppError::Verification`] (writing nothing either way) when the round trip
+/// doesn't reproduce the intended tree, or an unmodified save's original bytes.
+pub fn verified_bytes(save: &Save, original: Option<&[u8]>, context: &str) -> AppResult<Vec<u8>> {
+    save_schema::validate(save)?;
+    let (bytes, report) = check_round_trip(save, original)?;
+    if !report.is_ok() {
+        tracing::error!(
+            "Round-trip verification failed for {}: {:?}",
+            context,
+            report
+        );
+        return Err(AppError::Verification(format!(
+            "Refusing to write {}: {}",
+            context,
+            report.summary()
+        )));
+    }
+    Ok(bytes)
+}
+
+/// JSON view of a save used for structural comparison. The recorded schemas are
+/// parser bookkeeping, not file content (edits add entries a re-parse won't see).
+fn tree_of(save: &Save) -> AppResult<Value> {
+    let mut tree =
+        serde_json::to_value(save).map_err(|e| format!("Failed to serialize save tree: {}", e))?;
+    if let Value::Object(map) = &mut tree {
+        map.remove("schemas");
+    }
+    Ok(tree)
+}
+
+fn compare_trees(expected: &Value, actual: &Value, path: &str, out: &mut Vec<TreeMismatch>) {
+    if out.len() >= MAX_MISMATCHES || expected == actual {
+        return;
+    }
+    match (expected, actual) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let child = format!("{}/{}", path, escape_pointer(key));
+                match b.get(key) {
+                    Some(other) => compare_trees(value, other, &child, out),
+                    None => push_mismatch(out, child, Some(value), None),
+                }
+            }
+            for (key, value) in b {
+                if !a.contains_key(key) {
+                    let child = format!("{}/{}", path, escape_pointer(key));
+                    push_mismatch(out, child, None, Some(value));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                compare_trees(x, y, &format!("{}/{}", path, i), out);
+            }
+        }
+        _ => push_mismatch(out, path.to_string(), Some(expected), Some(actual)),
+    }
+}
+
+fn push_mismatch(
+    out: &mut Vec<TreeMismatch>,
+    path: String,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+) {
+    if out.len() >= MAX_MISMATCHES {
+        return;
+    }
+    out.push(TreeMismatch {
+        path: if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        },
+        expected: describe(expected),
+        actual: describe(actual),
+    });
+}
+
+fn describe(value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return "<missing>".to_string();
+    };
+    let text = value.to_string();
+    if text.chars().count() > MAX_VALUE_LEN {
+        format!("{}…", text.chars().take(MAX_VALUE_LEN).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// RFC 6901 escaping for pointer segments
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Check that an existing save re-serializes byte-for-byte
+pub fn verify_file(path: &Path) -> AppResult<RoundTripReport> {
+    let original = fs::read(path).map_err(|e| format!("Failed to read save file: {}", e))?;
+    let save = Save::read(&mut Cursor::new(&original))
+        .map_err(|e| AppError::Parse(format!("Failed to parse save: {:?}", e)))?;
+    check_round_trip(&save, Some(&original)).map(|(_, report)| report)
+}
+
+/// Report whether a save on disk survives a read → write round trip unchanged.
+#[tauri::command]
+pub async fn verify_save_roundtrip(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+) -> AppResult<RoundTripReport> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let path = Path::new(&file_path);
+        paths.validate_save_games_path(path)?;
+        verify_file(path)
+    })
+    .await
+}
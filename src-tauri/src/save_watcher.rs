@@ -0,0 +1,244 @@
+//! Save watcher module - Auto-snapshots saves while the game rewrites them
+//! Watches the active SaveGames directory, debounces bursts of writes and stores
+//! every changed top-level `.sav` in the snapshot store (hidden saves and the
+//! trash are never written by the game), which gives each save a timeline the
+//! frontend can roll back through. Each batch also drives the library events
+//! and invalidates pagination cursors of the changed library, and the watcher
+//! periodically runs the trash auto-purge.
+//! Follows library/override switches.
+
+use crate::error::{AppError, AppResult};
+use crate::library_cursor::LibraryCursors;
+use crate::library_events::LibraryTracker;
+use crate::save_backup::archive_name_of;
+use crate::save_bulk;
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_trash;
+use crate::snapshot_store::{self, SnapshotEntry};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Quiet period before a burst of game writes counts as one change
+const DEBOUNCE: Duration = Duration::from_secs(2);
+/// How often the watcher checks whether the active SaveGames directory moved
+const ROOT_POLL: Duration = Duration::from_secs(3);
+/// How often expired trash is purged and crashed bulk deletes are settled (also on
+/// every library switch)
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Watcher snapshots kept per save; older ones are dropped and garbage-collected
+const MAX_WATCHER_SNAPSHOTS: usize = 200;
+/// Snapshot source recorded for watcher snapshots
+pub const SOURCE_WATCHER: &str = "watcher";
+
+/// Emitted with a [`SnapshotEvent`] after a changed save was stored
+pub const EVENT_SNAPSHOT: &str = "save-watcher://snapshot";
+/// Emitted with a [`WatcherStatus`] when the watcher starts, moves or fails
+pub const EVENT_STATUS: &str = "save-watcher://status";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotEvent {
+    pub path: String,
+    pub snapshot: SnapshotEntry,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub watched_dir: Option<String>,
+    pub snapshots_taken: u64,
+    pub last_error: Option<String>,
+}
+
+/// Tauri managed state shared with the watcher thread
+#[derive(Default)]
+pub struct SaveWatcherState(Arc<Mutex<WatcherStatus>>);
+
+impl SaveWatcherState {
+    pub fn status(&self) -> AppResult<WatcherStatus> {
+        Ok(self
+            .0
+            .lock()
+            .map_err(|e| AppError::General(format!("Watcher status lock poisoned: {}", e)))?
+            .clone())
+    }
+}
+
+fn update_status(
+    app: &AppHandle,
+    status: &Mutex<WatcherStatus>,
+    f: impl FnOnce(&mut WatcherStatus),
+) {
+    let Ok(mut status) = status.lock() else {
+        return;
+    };
+    f(&mut status);
+    if let Err(e) = app.emit(EVENT_STATUS, status.clone()) {
+        tracing::warn!("Failed to emit watcher status: {}", e);
+    }
+}
+
+/// Start the watcher thread. Requires [`SaveWatcherState`] and [`SavePathsState`]
+/// to be managed already.
+pub fn start(app: AppHandle) {
+    let status = app.state::<SaveWatcherState>().0.clone();
+    let spawned = std::thread::Builder::new()
+        .name("save-watcher".into())
+        .spawn(move || {
+            if let Err(e) = run(&app, &status) {
+                tracing::error!("Save watcher stopped: {}", e);
+                update_status(&app, &status, |s| {
+                    s.running = false;
+                    s.last_error = Some(e.to_string());
+                });
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::error!("Failed to spawn save watcher: {}", e);
+    }
+}
+
+fn run(app: &AppHandle, status: &Mutex<WatcherStatus>) -> AppResult<()> {
+    let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+    let mut debouncer =
+        new_debouncer(DEBOUNCE, tx).map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    // Active root last seen, and the directory actually being watched
+    let mut root: Option<PathBuf> = None;
+    let mut watched: Option<PathBuf> = None;
+    let mut library = LibraryTracker::default();
+    let mut purged_at: Option<Instant> = None;
+    update_status(app, status, |s| s.running = true);
+
+    loop {
+        // Follow library switches and override changes
+        let current = app.state::<SavePathsState>().current().ok();
+        let current_root = current.as_ref().map(|p| p.save_games_dir().to_path_buf());
+        if current_root != root || (watched.is_none() && root.as_ref().is_some_and(|d| d.is_dir()))
+        {
+            root = current_root;
+            purged_at = None;
+            if let Some(old) = watched.take() {
+                let _ = debouncer.watcher().unwatch(&old);
+            }
+            let mut error = None;
+            if let Some(dir) = root.as_ref().filter(|d| d.is_dir()) {
+                match debouncer.watcher().watch(dir, RecursiveMode::Recursive) {
+                    Ok(()) => {
+                        tracing::info!("Watching {:?} for save changes", dir);
+                        watched = Some(dir.clone());
+                        if let Some(paths) = current.as_ref() {
+                            library.reset(paths);
+                        }
+                    }
+                    Err(e) => error = Some(format!("Failed to watch {}: {}", dir.display(), e)),
+                }
+            }
+            update_status(app, status, |s| {
+                s.watched_dir = watched.as_ref().map(|d| d.to_string_lossy().into_owned());
+                s.last_error = error;
+            });
+        }
+
+        if let (Some(paths), true) = (
+            current.as_ref(),
+            purged_at.is_none_or(|t| t.elapsed() >= PURGE_INTERVAL),
+        ) {
+            purged_at = Some(Instant::now());
+            if let Err(e) = save_trash::purge_expired(paths) {
+                tracing::warn!("Trash auto-purge failed: {}", e);
+            }
+            save_bulk::recover_staged_deletes(paths);
+        }
+
+        let events = match rx.recv_timeout(ROOT_POLL) {
+            Ok(Ok(events)) => events,
+            Ok(Err(e)) => {
+                tracing::warn!("File watcher error: {}", e);
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(AppError::General("File watcher channel closed".into()))
+            }
+        };
+        let (Some(paths), Some(dir)) = (current, watched.as_ref()) else {
+            continue;
+        };
+        // One snapshot per file per debounced batch
+        let changed: BTreeSet<PathBuf> = events
+            .into_iter()
+            .map(|e| e.path)
+            .filter(|p| p.parent() == Some(dir.as_path()) && is_complete_save(p))
+            .collect();
+        for path in changed {
+            match snapshot_changed(&paths, &path) {
+                Ok(Some(snapshot)) => {
+                    update_status(app, status, |s| s.snapshots_taken += 1);
+                    let event = SnapshotEvent {
+                        path: path.to_string_lossy().into_owned(),
+                        snapshot,
+                    };
+                    if let Err(e) = app.emit(EVENT_SNAPSHOT, event) {
+                        tracing::warn!("Failed to emit snapshot event: {}", e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Auto-snapshot of {:?} failed: {}", path, e),
+            }
+        }
+        let events = library.refresh(&paths);
+        if !events.is_empty() {
+            app.state::<LibraryCursors>().invalidate_root(dir);
+        }
+        for event in events {
+            event.emit(app);
+        }
+    }
+}
+
+/// A `.sav` that exists and starts with the GVAS magic (skips temp files and
+/// files caught mid-write)
+fn is_complete_save(path: &Path) -> bool {
+    if path.extension().and_then(|e| e.to_str()) != Some("sav") {
+        return false;
+    }
+    let mut magic = [0u8; 4];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == b"GVAS")
+}
+
+/// Snapshot `path` unless its content is already the newest snapshot; returns the
+/// new entry
+fn snapshot_changed(paths: &SavePaths, path: &Path) -> AppResult<Option<SnapshotEntry>> {
+    let save_name = archive_name_of(paths, path)?;
+    let previous = snapshot_store::list_snapshots(paths, Some(&save_name))?
+        .into_iter()
+        .next()
+        .map(|s| s.id);
+    let entry = snapshot_store::take_snapshot(paths, path, "game-write", SOURCE_WATCHER)?;
+    if previous.as_deref() == Some(entry.id.as_str()) {
+        return Ok(None);
+    }
+    if snapshot_store::prune_source(paths, &save_name, SOURCE_WATCHER, MAX_WATCHER_SNAPSHOTS)? > 0 {
+        snapshot_store::gc(paths)?;
+    }
+    Ok(Some(entry))
+}
+
+#[tauri::command]
+pub fn get_save_watcher_status(
+    state: tauri::State<'_, SaveWatcherState>,
+) -> AppResult<WatcherStatus> {
+    state.status()
+}
@@ -0,0 +1,624 @@
+//! Snapshot store module - Content-addressed, deduplicated archive of save copies
+//! Blobs are gzip-compressed and named by the SHA-256 of the raw save bytes
+//! (`<app config>/snapshots/blobs/ab/abcd….sav.gz`), so identical copies are stored
+//! once. `manifest.json` records what each snapshot is; blobs no entry points to
+//! are removed by `gc`.
+
+use crate::common::{mainsave_lock, preview_mainsave, run_blocking, write_atomic, MainsaveOp};
+use crate::error::{AppError, AppResult};
+use crate::file_lock::{self, lock_save, FileLock};
+use crate::library_profiles::DEFAULT_LIBRARY;
+use crate::save_backup::{archive_name_of, backup_file, MAINSAVE_ARCHIVE};
+use crate::save_diff::{preview_replace, DryRunReport, FileOp, Outcome};
+use crate::save_paths::{SavePaths, SavePathsState};
+use crate::save_trash::{list_restored, RestoreResult};
+use chrono::Local;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// Store root, inside the app config dir
+const SNAPSHOT_DIR: &str = "snapshots";
+const BLOB_DIR: &str = "blobs";
+const MANIFEST_FILE: &str = "manifest.json";
+const BLOB_EXTENSION: &str = "sav.gz";
+/// Manifest format version
+const MANIFEST_VERSION: u32 = 1;
+/// Operation recorded for snapshots the user asked for
+pub const SOURCE_MANUAL: &str = "manual";
+
+/// Serialize manifest read-modify-write cycles across threads
+static STORE_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+
+/// Held store lock: the in-process mutex plus an OS lock on `snapshots/store.lock`,
+/// so the app's watcher and a scripted `etbsave` can't interleave manifest
+/// updates, or collect a blob whose entry isn't written yet
+struct StoreGuard {
+    _file: FileLock,
+    _guard: MutexGuard<'static, ()>,
+}
+
+fn store_lock(paths: &SavePaths) -> AppResult<StoreGuard> {
+    let guard = STORE_LOCK
+        .get_or_init(|| Mutex::new(()))
+        .lock()
+        .map_err(|e| format!("Snapshot store lock poisoned: {}", e))?;
+    Ok(StoreGuard {
+        _file: file_lock::lock_in(&store_dir(paths), "store")?,
+        _guard: guard,
+    })
+}
+
+/// One snapshot in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotEntry {
+    pub id: String,
+    /// SHA-256 of the raw save bytes (hex), also the blob name
+    pub hash: String,
+    /// Archive name (`MAINSAVE` for MAINSAVE.sav)
+    pub save_name: String,
+    /// Library profile the save belonged to (`default` for the default root)
+    pub library: String,
+    /// Path relative to SaveGames at snapshot time
+    pub original_path: String,
+    /// RFC 3339
+    pub created_at: String,
+    pub reason: String,
+    /// Operation that took the snapshot (`manual`, ...)
+    pub source: String,
+    /// Uncompressed size
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u32,
+    snapshots: Vec<SnapshotEntry>,
+}
+
+/// Outcome of a garbage collection
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub removed_blobs: usize,
+    pub freed_bytes: u64,
+}
+
+/// Store size overview
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreStats {
+    pub snapshots: usize,
+    pub blobs: usize,
+    /// Sum of the snapshots' uncompressed sizes
+    pub logical_bytes: u64,
+    /// Bytes actually on disk (compressed, deduplicated)
+    pub stored_bytes: u64,
+}
+
+fn store_dir(paths: &SavePaths) -> PathBuf {
+    paths.app_config_dir().join(SNAPSHOT_DIR)
+}
+
+fn blob_path(paths: &SavePaths, hash: &str) -> PathBuf {
+    store_dir(paths)
+        .join(BLOB_DIR)
+        .join(&hash[..2])
+        .join(format!("{}.{}", hash, BLOB_EXTENSION))
+}
+
+fn library_name(paths: &SavePaths) -> String {
+    paths.library().unwrap_or(DEFAULT_LIBRARY).to_string()
+}
+
+/// Missing manifest = empty store; a corrupt one is an error so it is never overwritten
+fn load_manifest(paths: &SavePaths) -> AppResult<Manifest> {
+    let path = store_dir(paths).join(MANIFEST_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(Manifest {
+            version: MANIFEST_VERSION,
+            snapshots: Vec::new(),
+        });
+    };
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Parse(format!("Failed to parse snapshot manifest: {}", e)))
+}
+
+fn write_manifest(paths: &SavePaths, manifest: &Manifest) -> AppResult<()> {
+    let dir = store_dir(paths);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create snapshot store: {}", e))?;
+    let json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize snapshot manifest: {}", e))?;
+    write_atomic(&dir.join(MANIFEST_FILE), json.as_bytes())
+}
+
+fn hash_of(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Write the blob for `bytes` unless the store already has it
+fn store_blob(paths: &SavePaths, hash: &str, bytes: &[u8]) -> AppResult<()> {
+    let path = blob_path(paths, hash);
+    if path.is_file() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create blob directory: {}", e))?;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(bytes)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| AppError::from(format!("Failed to compress snapshot: {}", e)))
+        .and_then(|compressed| write_atomic(&path, &compressed))
+}
+
+/// Decompress a blob and check it still hashes to its name
+fn read_blob(paths: &SavePaths, hash: &str) -> AppResult<Vec<u8>> {
+    let file = fs::File::open(blob_path(paths, hash))
+        .map_err(|e| format!("Snapshot blob {} missing: {}", hash, e))?;
+    let mut bytes = Vec::new();
+    GzDecoder::new(file)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to decompress snapshot blob {}: {}", hash, e))?;
+    if hash_of(&bytes) != hash {
+        return Err(AppError::Verification(format!(
+            "Snapshot blob {} is corrupt (hash mismatch)",
+            hash
+        )));
+    }
+    Ok(bytes)
+}
+
+/// The newest snapshot of the save when it already holds `hash`
+fn unchanged_since<'a>(
+    paths: &SavePaths,
+    manifest: &'a Manifest,
+    save_name: &str,
+    library: &str,
+    hash: &str,
+) -> Option<&'a SnapshotEntry> {
+    manifest
+        .snapshots
+        .iter()
+        .rev()
+        .find(|s| s.save_name == save_name && s.library == library)
+        .filter(|latest| latest.hash == hash && blob_path(paths, hash).is_file())
+}
+
+/// Snapshot the current bytes of a save (or MAINSAVE). When the newest snapshot
+/// of the same save already has these bytes, that entry is returned instead.
+pub fn take_snapshot(
+    paths: &SavePaths,
+    path: &Path,
+    reason: &str,
+    source: &str,
+) -> AppResult<SnapshotEntry> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read save for snapshot: {}", e))?;
+    let save_name = archive_name_of(paths, path)?;
+    let library = library_name(paths);
+    let hash = hash_of(&bytes);
+
+    let _lock = store_lock(paths)?;
+    let mut manifest = load_manifest(paths)?;
+    if let Some(latest) = unchanged_since(paths, &manifest, &save_name, &library, &hash) {
+        tracing::debug!("Snapshot of {} unchanged since {}", save_name, latest.id);
+        return Ok(latest.clone());
+    }
+
+    store_blob(paths, &hash, &bytes)?;
+    let original_path = path
+        .strip_prefix(paths.save_games_dir())
+        .unwrap_or_else(|_| Path::new(path.file_name().unwrap_or_default()));
+    let entry = SnapshotEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        hash,
+        save_name,
+        library,
+        original_path: original_path.to_string_lossy().into_owned(),
+        created_at: Local::now().to_rfc3339(),
+        reason: reason.to_string(),
+        source: source.to_string(),
+        size: bytes.len() as u64,
+    };
+    manifest.version = MANIFEST_VERSION;
+    manifest.snapshots.push(entry.clone());
+    write_manifest(paths, &manifest)?;
+
+    tracing::info!("Snapshot {} of {:?} ({})", entry.id, path, reason);
+    Ok(entry)
+}
+
+/// Dry run of [`take_snapshot`]
+pub fn preview_take_snapshot(paths: &SavePaths, path: &Path) -> AppResult<DryRunReport> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read save for snapshot: {}", e))?;
+    let save_name = archive_name_of(paths, path)?;
+    let hash = hash_of(&bytes);
+    let manifest = load_manifest(paths)?;
+
+    let mut report = DryRunReport::default();
+    if let Some(latest) = unchanged_since(paths, &manifest, &save_name, &library_name(paths), &hash)
+    {
+        report.notes.push(format!(
+            "{} is unchanged since snapshot {}; nothing is stored",
+            save_name, latest.id
+        ));
+        return Ok(report);
+    }
+    let blob = blob_path(paths, &hash);
+    if !blob.is_file() {
+        report.files.push(FileOp::Create {
+            path: blob.to_string_lossy().into_owned(),
+        });
+    }
+    report.files.push(FileOp::Write {
+        path: store_dir(paths)
+            .join(MANIFEST_FILE)
+            .to_string_lossy()
+            .into_owned(),
+    });
+    report
+        .notes
+        .push(format!("New snapshot of {} ({})", save_name, hash));
+    Ok(report)
+}
+
+/// Snapshots of the current library, newest first, optionally for one save
+pub fn list_snapshots(paths: &SavePaths, save_name: Option<&str>) -> AppResult<Vec<SnapshotEntry>> {
+    let library = library_name(paths);
+    let mut snapshots: Vec<SnapshotEntry> = load_manifest(paths)?
+        .snapshots
+        .into_iter()
+        .filter(|s| s.library == library && save_name.is_none_or(|name| s.save_name == name))
+        .collect();
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+/// A snapshot of the current library; ids taken in another library are not found
+fn find_snapshot(paths: &SavePaths, id: &str) -> AppResult<SnapshotEntry> {
+    let library = library_name(paths);
+    load_manifest(paths)?
+        .snapshots
+        .into_iter()
+        .find(|s| s.id == id && s.library == library)
+        .ok_or_else(|| snapshot_not_found(id))
+}
+
+fn snapshot_not_found(id: &str) -> AppError {
+    AppError::Validation(format!("Snapshot not found: {}", id))
+}
+
+/// Remove the current library's snapshot `id` from `manifest`
+fn remove_entry(paths: &SavePaths, manifest: &mut Manifest, id: &str) -> AppResult<()> {
+    let library = library_name(paths);
+    let before = manifest.snapshots.len();
+    manifest
+        .snapshots
+        .retain(|s| s.id != id || s.library != library);
+    if manifest.snapshots.len() == before {
+        return Err(snapshot_not_found(id));
+    }
+    Ok(())
+}
+
+/// Raw bytes of a snapshot
+pub fn snapshot_bytes(paths: &SavePaths, id: &str) -> AppResult<Vec<u8>> {
+    let entry = find_snapshot(paths, id)?;
+    read_blob(paths, &entry.hash)
+}
+
+/// A snapshot, the file it restores to and its bytes
+fn restore_plan(paths: &SavePaths, id: &str) -> AppResult<(SnapshotEntry, PathBuf, Vec<u8>)> {
+    let entry = find_snapshot(paths, id)?;
+    let bytes = read_blob(paths, &entry.hash)?;
+
+    let target = paths.save_games_dir().join(&entry.original_path);
+    paths.validate_save_games_path(&target)?;
+    Ok((entry, target, bytes))
+}
+
+/// Write a snapshot back to its original place in the current library (the file
+/// it replaces is backed up first).
+pub fn restore_snapshot(paths: &SavePaths, id: &str) -> AppResult<RestoreResult> {
+    let (entry, target, bytes) = restore_plan(paths, id)?;
+
+    let is_mainsave = entry.save_name == MAINSAVE_ARCHIVE;
+    // MAINSAVE writers hold the MAINSAVE lock; a save only needs its own
+    let _mainsave_lock = is_mainsave.then(|| mainsave_lock(paths)).transpose()?;
+    let _save_lock = (!is_mainsave)
+        .then(|| lock_save(paths, &target))
+        .transpose()?;
+
+    backup_file(paths, &target, "restore-snapshot")?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create target directory: {}", e))?;
+    }
+    write_atomic(&target, &bytes)?;
+    tracing::info!("Restored snapshot {} to {:?}", id, target);
+
+    let mainsave_error = if is_mainsave || paths.is_hidden(&target) {
+        None
+    } else {
+        list_restored(paths, &entry.save_name)
+    };
+    Ok(RestoreResult {
+        path: target.to_string_lossy().into_owned(),
+        mainsave_error,
+    })
+}
+
+/// Dry run of [`restore_snapshot`]
+pub fn preview_restore_snapshot(paths: &SavePaths, id: &str) -> AppResult<DryRunReport> {
+    let (entry, target, bytes) = restore_plan(paths, id)?;
+    let mut report = preview_replace(&target, &bytes);
+    if entry.save_name != MAINSAVE_ARCHIVE && !paths.is_hidden(&target) {
+        report.push_diff(
+            paths.mainsave_path().to_string_lossy(),
+            preview_mainsave(paths, &[MainsaveOp::Add(entry.save_name)])?,
+        );
+    }
+    Ok(report)
+}
+
+/// Keep only the newest `keep` snapshots of a save taken by `source`. Returns how
+/// many entries were dropped; blobs are left for `gc`.
+pub fn prune_source(
+    paths: &SavePaths,
+    save_name: &str,
+    source: &str,
+    keep: usize,
+) -> AppResult<usize> {
+    let library = library_name(paths);
+    let _lock = store_lock(paths)?;
+    let mut manifest = load_manifest(paths)?;
+    let matches =
+        |s: &SnapshotEntry| s.library == library && s.save_name == save_name && s.source == source;
+    let excess = manifest
+        .snapshots
+        .iter()
+        .filter(|s| matches(s))
+        .count()
+        .saturating_sub(keep);
+    if excess == 0 {
+        return Ok(0);
+    }
+    // Manifest is in creation order, so the first matches are the oldest
+    let mut dropped = 0;
+    manifest.snapshots.retain(|s| {
+        if dropped < excess && matches(s) {
+            dropped += 1;
+            false
+        } else {
+            true
+        }
+    });
+    write_manifest(paths, &manifest)?;
+    Ok(dropped)
+}
+
+/// Drop a snapshot from the manifest, then collect its blob if nothing else uses it
+pub fn delete_snapshot(paths: &SavePaths, id: &str) -> AppResult<GcReport> {
+    {
+        let _lock = store_lock(paths)?;
+        let mut manifest = load_manifest(paths)?;
+        remove_entry(paths, &mut manifest, id)?;
+        write_manifest(paths, &manifest)?;
+    }
+    gc(paths)
+}
+
+fn blob_files(paths: &SavePaths) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(store_dir(paths).join(BLOB_DIR))
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Blobs (and leftover temp files) no entry of `manifest` references
+fn unreferenced_blobs(paths: &SavePaths, manifest: &Manifest) -> Vec<PathBuf> {
+    let live: HashSet<String> = manifest
+        .snapshots
+        .iter()
+        .map(|s| format!("{}.{}", s.hash, BLOB_EXTENSION))
+        .collect();
+    blob_files(paths)
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .is_none_or(|name| !live.contains(name.to_string_lossy().as_ref()))
+        })
+        .collect()
+}
+
+/// Remove blobs no manifest entry references, and leftover temp files
+pub fn gc(paths: &SavePaths) -> AppResult<GcReport> {
+    let _lock = store_lock(paths)?;
+    let manifest = load_manifest(paths)?;
+
+    let mut report = GcReport::default();
+    for path in unreferenced_blobs(paths, &manifest) {
+        let size = path.metadata().map(|m| m.len()).unwrap_or(0);
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                report.removed_blobs += 1;
+                report.freed_bytes += size;
+            }
+            Err(e) => tracing::warn!("Failed to remove snapshot blob {:?}: {}", path, e),
+        }
+    }
+    if report.removed_blobs > 0 {
+        tracing::info!(
+            "Snapshot GC removed {} blob(s), {} bytes",
+            report.removed_blobs,
+            report.freed_bytes
+        );
+    }
+    Ok(report)
+}
+
+/// The blobs `manifest` leaves unreferenced, as deletes
+fn preview_collect(paths: &SavePaths, manifest: &Manifest) -> DryRunReport {
+    let blobs = unreferenced_blobs(paths, manifest);
+    let freed: u64 = blobs
+        .iter()
+        .filter_map(|p| p.metadata().ok())
+        .map(|m| m.len())
+        .sum();
+    let mut report = DryRunReport {
+        files: blobs
+            .iter()
+            .map(|path| FileOp::Delete {
+                path: path.to_string_lossy().into_owned(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    if !blobs.is_empty() {
+        report.notes.push(format!("Frees {} bytes", freed));
+    }
+    report
+}
+
+/// Dry run of [`gc`]
+pub fn preview_gc(paths: &SavePaths) -> AppResult<DryRunReport> {
+    Ok(preview_collect(paths, &load_manifest(paths)?))
+}
+
+/// Dry run of [`delete_snapshot`]
+pub fn preview_delete_snapshot(paths: &SavePaths, id: &str) -> AppResult<DryRunReport> {
+    let mut manifest = load_manifest(paths)?;
+    remove_entry(paths, &mut manifest, id)?;
+    let mut report = preview_collect(paths, &manifest);
+    report.files.insert(
+        0,
+        FileOp::Write {
+            path: store_dir(paths)
+                .join(MANIFEST_FILE)
+                .to_string_lossy()
+                .into_owned(),
+        },
+    );
+    Ok(report)
+}
+
+/// Snapshot count and disk usage of the whole store (all libraries)
+pub fn store_stats(paths: &SavePaths) -> AppResult<StoreStats> {
+    let manifest = load_manifest(paths)?;
+    let blobs = blob_files(paths);
+    Ok(StoreStats {
+        snapshots: manifest.snapshots.len(),
+        blobs: blobs.len(),
+        logical_bytes: manifest.snapshots.iter().map(|s| s.size).sum(),
+        stored_bytes: blobs
+            .iter()
+            .filter_map(|p| p.metadata().ok())
+            .map(|m| m.len())
+            .sum(),
+    })
+}
+
+/// Snapshot a save (or MAINSAVE.sav) into the store.
+#[tauri::command]
+pub async fn create_snapshot(
+    state: tauri::State<'_, SavePathsState>,
+    file_path: String,
+    reason: Option<String>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<SnapshotEntry>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        let path = Path::new(&file_path);
+        paths.validate_save_games_path(path)?;
+        if dry_run.unwrap_or(false) {
+            return preview_take_snapshot(&paths, path).map(Outcome::DryRun);
+        }
+        take_snapshot(
+            &paths,
+            path,
+            reason.as_deref().unwrap_or(SOURCE_MANUAL),
+            SOURCE_MANUAL,
+        )
+        .map(Outcome::Applied)
+    })
+    .await
+}
+
+/// List snapshots of the current library, newest first.
+#[tauri::command]
+pub async fn list_save_snapshots(
+    state: tauri::State<'_, SavePathsState>,
+    save_name: Option<String>,
+) -> AppResult<Vec<SnapshotEntry>> {
+    let paths = state.current()?;
+    run_blocking(move || list_snapshots(&paths, save_name.as_deref())).await
+}
+
+/// Restore a snapshot over the current file.
+#[tauri::command]
+pub async fn restore_save_snapshot(
+    state: tauri::State<'_, SavePathsState>,
+    snapshot_id: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<RestoreResult>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return preview_restore_snapshot(&paths, &snapshot_id).map(Outcome::DryRun);
+        }
+        restore_snapshot(&paths, &snapshot_id).map(Outcome::Applied)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_save_snapshot(
+    state: tauri::State<'_, SavePathsState>,
+    snapshot_id: String,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<GcReport>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return preview_delete_snapshot(&paths, &snapshot_id).map(Outcome::DryRun);
+        }
+        delete_snapshot(&paths, &snapshot_id).map(Outcome::Applied)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn gc_snapshots(
+    state: tauri::State<'_, SavePathsState>,
+    dry_run: Option<bool>,
+) -> AppResult<Outcome<GcReport>> {
+    let paths = state.current()?;
+    run_blocking(move || {
+        if dry_run.unwrap_or(false) {
+            return preview_gc(&paths).map(Outcome::DryRun);
+        }
+        gc(&paths).map(Outcome::Applied)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_snapshot_stats(state: tauri::State<'_, SavePathsState>) -> AppResult<StoreStats> {
+    let paths = state.current()?;
+    run_blocking(move || store_stats(&paths)).await
+}
@@ -1,4 +1,5 @@
 use crate::error::AppResult;
+use crate::save_paths::SavePathsState;
 use std::env;
 use tauri::Window;
 
@@ -7,6 +8,16 @@ pub fn get_local_appdata() -> AppResult<String> {
     Ok(env::var("LOCALAPPDATA")?)
 }
 
+/// Resolved SaveGames directory (override, LOCALAPPDATA or Proton prefix).
+#[tauri::command]
+pub fn get_save_games_path(state: tauri::State<'_, SavePathsState>) -> AppResult<String> {
+    Ok(state
+        .current()?
+        .save_games_dir()
+        .to_string_lossy()
+        .into_owned())
+}
+
 #[tauri::command]
 pub fn restart_app(app: tauri::AppHandle) {
     app.restart();